@@ -1,40 +1,136 @@
 use std::collections::HashMap;
 
-use crate::keybind::{parse_keybinding, KeyBinding};
+use crate::{
+    config::default_keybinds,
+    keybind::{format_keybinding, parse_keybinding, KeyBinding},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Action {
     SplitVertical,
     SplitHorizontal,
+    SplitGrid,
+    DeletePane,
     ResizeLeft,
     ResizeRight,
     ResizeUp,
     ResizeDown,
     SwapVertical,
     SwapHorizontal,
+    MovePaneForward,
+    MovePaneBackward,
+    MovePaneLeft,
+    MovePaneRight,
+    MovePaneUp,
+    MovePaneDown,
+    BalancePanes,
     SaveState,
+    SaveAsTemplate,
     RestoreState,
     FocusNext,
+    FocusLeft,
+    FocusRight,
+    FocusUp,
+    FocusDown,
+    ScrollUp,
+    ScrollDown,
+    CopyOutput,
+    SavePaneOutput,
+    RestartPane,
+    EditCommand,
+    ResetTerminal,
+    ToggleWrap,
+    FlipTab,
+    OpenBelow,
+    ToggleZoom,
+    NextTab,
+    PrevTab,
+    NewTab,
+    Search,
+    Help,
     Quit,
 }
 
-pub fn actions_from_config(map: &HashMap<String, String>) -> HashMap<KeyBinding, Action> {
+pub const RUNTIME_ACTIONS: &[(&str, Action)] = &[
+    ("split_vertical", Action::SplitVertical),
+    ("split_horizontal", Action::SplitHorizontal),
+    ("split_grid", Action::SplitGrid),
+    ("delete_pane", Action::DeletePane),
+    ("resize_left", Action::ResizeLeft),
+    ("resize_right", Action::ResizeRight),
+    ("resize_up", Action::ResizeUp),
+    ("resize_down", Action::ResizeDown),
+    ("swap_vertical", Action::SwapVertical),
+    ("swap_horizontal", Action::SwapHorizontal),
+    ("move_pane_forward", Action::MovePaneForward),
+    ("move_pane_backward", Action::MovePaneBackward),
+    ("move_pane_left", Action::MovePaneLeft),
+    ("move_pane_right", Action::MovePaneRight),
+    ("move_pane_up", Action::MovePaneUp),
+    ("move_pane_down", Action::MovePaneDown),
+    ("balance_panes", Action::BalancePanes),
+    ("save_state", Action::SaveState),
+    ("save_as_template", Action::SaveAsTemplate),
+    ("restore_state", Action::RestoreState),
+    ("focus_next", Action::FocusNext),
+    ("focus_left", Action::FocusLeft),
+    ("focus_right", Action::FocusRight),
+    ("focus_up", Action::FocusUp),
+    ("focus_down", Action::FocusDown),
+    ("scroll_up", Action::ScrollUp),
+    ("scroll_down", Action::ScrollDown),
+    ("copy_output", Action::CopyOutput),
+    ("save_pane_output", Action::SavePaneOutput),
+    ("restart_pane", Action::RestartPane),
+    ("edit_command", Action::EditCommand),
+    ("reset_terminal", Action::ResetTerminal),
+    ("toggle_wrap", Action::ToggleWrap),
+    ("flip_tab", Action::FlipTab),
+    ("open_below", Action::OpenBelow),
+    ("toggle_zoom", Action::ToggleZoom),
+    ("next_tab", Action::NextTab),
+    ("prev_tab", Action::PrevTab),
+    ("new_tab", Action::NewTab),
+    ("search", Action::Search),
+    ("help", Action::Help),
+    ("quit", Action::Quit),
+];
+
+// Names missing from the config fall back to the documented defaults, so configs written
+// before an action existed still get its key. User bindings are inserted last and win.
+pub fn runtime_actions_from_config(map: &HashMap<String, String>) -> HashMap<KeyBinding, Action> {
+    let defaults = default_keybinds();
     let mut out = HashMap::new();
-    insert_action(map, &mut out, "split_vertical", Action::SplitVertical);
-    insert_action(map, &mut out, "split_horizontal", Action::SplitHorizontal);
-    insert_action(map, &mut out, "resize_left", Action::ResizeLeft);
-    insert_action(map, &mut out, "resize_right", Action::ResizeRight);
-    insert_action(map, &mut out, "resize_up", Action::ResizeUp);
-    insert_action(map, &mut out, "resize_down", Action::ResizeDown);
-    insert_action(map, &mut out, "swap_vertical", Action::SwapVertical);
-    insert_action(map, &mut out, "swap_horizontal", Action::SwapHorizontal);
-    insert_action(map, &mut out, "save_state", Action::SaveState);
-    insert_action(map, &mut out, "restore_state", Action::RestoreState);
-    insert_action(map, &mut out, "focus_next", Action::FocusNext);
-    insert_action(map, &mut out, "quit", Action::Quit);
+    for (name, action) in RUNTIME_ACTIONS {
+        if !map.contains_key(*name) {
+            insert_action(&defaults, &mut out, name, *action);
+        }
+    }
+    for (name, action) in RUNTIME_ACTIONS {
+        insert_action(map, &mut out, name, *action);
+    }
     out
 }
 
+// One line per bound action, in table order, so the overlay reflects the user's config.
+pub fn help_lines(actions: &HashMap<KeyBinding, Action>) -> Vec<String> {
+    RUNTIME_ACTIONS
+        .iter()
+        .filter_map(|(name, action)| {
+            let mut keys: Vec<String> = actions
+                .iter()
+                .filter(|(_, bound)| *bound == action)
+                .map(|(binding, _)| format_keybinding(binding))
+                .collect();
+            if keys.is_empty() {
+                return None;
+            }
+            keys.sort();
+            Some(format!("{:<16} {name}", keys.join(", ")))
+        })
+        .collect()
+}
+
 fn insert_action(
     map: &HashMap<String, String>,
     out: &mut HashMap<KeyBinding, Action>,
@@ -47,3 +143,67 @@ fn insert_action(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{help_lines, runtime_actions_from_config, Action};
+
+    fn action_for(map: &HashMap<String, String>, key: KeyEvent) -> Option<Action> {
+        runtime_actions_from_config(map)
+            .into_iter()
+            .find(|(binding, _)| binding.matches(key))
+            .map(|(_, action)| action)
+    }
+
+    #[test]
+    fn defaults_cover_documented_keys() {
+        let empty = HashMap::new();
+        let plain = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        assert_eq!(action_for(&empty, plain('v')), Some(Action::SplitVertical));
+        assert_eq!(
+            action_for(
+                &empty,
+                KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT)
+            ),
+            Some(Action::ResizeLeft)
+        );
+        assert_eq!(
+            action_for(
+                &empty,
+                KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::SaveState)
+        );
+        assert_eq!(
+            action_for(
+                &empty,
+                KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::Quit)
+        );
+    }
+
+    #[test]
+    fn user_binding_overrides_default_key() {
+        let map = HashMap::from([("quit".to_string(), "v".to_string())]);
+        let v = KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE);
+        assert_eq!(action_for(&map, v), Some(Action::Quit));
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(action_for(&map, ctrl_c), None);
+    }
+
+    #[test]
+    fn help_lists_configured_keys_in_table_order() {
+        let mut map = HashMap::new();
+        map.insert("split_vertical".to_string(), "Ctrl+Alt+v".to_string());
+        let lines = help_lines(&runtime_actions_from_config(&map));
+        assert_eq!(lines[0], format!("{:<16} split_vertical", "Ctrl+Alt+v"));
+        assert!(lines.contains(&format!("{:<16} resize_left", "H")));
+        assert!(lines.contains(&format!("{:<16} scroll_up", "Shift+PageUp")));
+        assert!(lines.contains(&format!("{:<16} help", "?")));
+    }
+}
@@ -1,23 +1,120 @@
-use clap::{Parser, Subcommand};
+use clap::{builder::PossibleValuesParser, Parser, Subcommand, ValueEnum};
+
+use crate::layout::PRESET_NAMES;
 
 #[derive(Parser, Debug)]
 #[command(name = "pudding", version, about = "Minimal pane multiplexer")]
 pub struct Cli {
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "既定の config.json の代わりに読み込む設定ファイル"
+    )]
+    pub config: Option<String>,
+    #[arg(
+        long,
+        help = "既定値・設定ファイルを反映した実際の設定を JSON で出力して終了"
+    )]
+    pub print_config: bool,
     #[command(subcommand)]
     pub command: Option<Command>,
 }
 
+// Both halves are flattened, so the command line sees one flat list of subcommands.
 #[derive(Subcommand, Debug)]
 pub enum Command {
+    #[command(flatten)]
+    WithConfig(ConfigCommand),
+    #[command(flatten)]
+    Standalone(StandaloneCommand),
+}
+
+// Commands that need the loaded config.
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
     #[command(about = "テンプレートを編集")]
     Template {
         #[command(subcommand)]
         command: TemplateCommand,
     },
+    #[command(about = "保存した状態を管理")]
+    State {
+        #[command(subcommand)]
+        command: StateCommand,
+    },
     #[command(about = "テンプレートを適用して起動")]
     Run {
-        #[arg(long, default_value = "default")]
-        template: String,
+        #[arg(
+            long,
+            value_name = "TEMPLATE",
+            conflicts_with = "file",
+            help = "起動するテンプレート（省略時 default）"
+        )]
+        template: Option<String>,
+        #[arg(
+            long,
+            help = "保存済みテンプレートの代わりに JSON / KDL ファイルを読み込む"
+        )]
+        file: Option<String>,
+        #[arg(
+            long,
+            value_name = "STATE",
+            conflicts_with = "file",
+            help = "保存した状態から起動（--template より優先）"
+        )]
+        state: Option<String>,
+        #[arg(
+            long,
+            value_name = "PRESET",
+            value_parser = PossibleValuesParser::new(PRESET_NAMES),
+            conflicts_with_all = ["template", "file", "state"],
+            help = "組み込みのレイアウトで起動（テンプレート不要）"
+        )]
+        preset: Option<String>,
+        #[arg(
+            long,
+            help = "全ペインの起動コマンドを一時的に置き換える（テンプレートは変更しない）"
+        )]
+        command: Option<String>,
+        #[arg(
+            long,
+            requires = "command",
+            help = "--command をデフォルトコマンドのペインにだけ適用"
+        )]
+        only_default: bool,
+        #[arg(
+            long,
+            help = "起動せずに、読み込んだレイアウトと現在の端末サイズでの各ペインの位置を表示"
+        )]
+        dry_run: bool,
+    },
+    #[command(about = "テンプレートを編集（--last で直近のテンプレート）")]
+    Edit {
+        #[arg(
+            long,
+            default_value = "default",
+            value_name = "TEMPLATE",
+            conflicts_with = "last"
+        )]
+        name: String,
+        #[arg(long)]
+        last: bool,
+    },
+}
+
+// Commands that run before the config is loaded, so a broken config cannot stop them.
+#[derive(Subcommand, Debug)]
+pub enum StandaloneCommand {
+    #[command(about = "環境と設定を診断")]
+    Doctor {
+        #[arg(long, help = "結果を JSON で出力")]
+        json: bool,
+    },
+    #[command(about = "シェル補完スクリプトを出力")]
+    Completions {
+        #[arg(value_enum)]
+        shell: CompletionShell,
     },
 }
 
@@ -25,12 +122,126 @@ pub enum Command {
 pub enum TemplateCommand {
     #[command(about = "AAエディタでテンプレートを編集")]
     Edit {
-        #[arg(long, default_value = "default")]
+        #[arg(long, default_value = "default", value_name = "TEMPLATE")]
         name: String,
     },
     #[command(about = "テンプレートを適用して起動")]
     Apply {
-        #[arg(long, default_value = "default")]
+        #[arg(long, default_value = "default", value_name = "TEMPLATE")]
+        name: String,
+    },
+    #[command(about = "テンプレートを標準出力に表示")]
+    Show {
+        #[arg(long, default_value = "default", value_name = "TEMPLATE")]
+        name: String,
+        #[arg(long, value_enum, default_value_t = TemplateFormat::Json)]
+        format: TemplateFormat,
+    },
+    #[command(about = "テンプレートを zellij のレイアウトとして書き出す")]
+    Export {
+        #[arg(long, default_value = "default", value_name = "TEMPLATE")]
         name: String,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Zellij)]
+        format: ExportFormat,
+        #[arg(long, conflicts_with = "stdout")]
+        output: Option<String>,
+        #[arg(long)]
+        stdout: bool,
+    },
+    #[command(about = "テンプレートを別名で複製")]
+    Copy {
+        #[arg(value_name = "TEMPLATE")]
+        from: String,
+        #[arg(value_name = "TEMPLATE")]
+        to: String,
+        #[arg(long, help = "既存の複製先を上書きする")]
+        force: bool,
+    },
+    #[command(about = "テンプレートの名前を変更")]
+    Rename {
+        #[arg(value_name = "TEMPLATE")]
+        from: String,
+        #[arg(value_name = "TEMPLATE")]
+        to: String,
+        #[arg(long, help = "既存の変更先を上書きする")]
+        force: bool,
     },
+    #[command(about = "2つのテンプレートの差分を表示")]
+    Diff {
+        #[arg(value_name = "TEMPLATE")]
+        a: String,
+        #[arg(value_name = "TEMPLATE")]
+        b: String,
+        #[arg(long, help = "KDL レイアウトのテキストとして差分を取る")]
+        kdl: bool,
+        #[arg(
+            long,
+            conflicts_with = "kdl",
+            help = "位置ではなくノード ID で対応付けて差分を取る"
+        )]
+        ids: bool,
+    },
+    #[command(about = "zellij の KDL レイアウトをテンプレートとして取り込む")]
+    Import {
+        path: String,
+        #[arg(long)]
+        name: String,
+    },
+    #[command(about = "保存済みテンプレートを一覧表示")]
+    List {
+        #[arg(long, help = "見出しなしで1行1件の名前だけを出力")]
+        plain: bool,
+        #[arg(long, conflicts_with = "plain", help = "結果を JSON で出力")]
+        json: bool,
+    },
+    #[command(about = "テンプレートを検証して問題を一覧表示")]
+    Validate {
+        #[arg(help = "テンプレート名またはファイルパス")]
+        name_or_path: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StateCommand {
+    #[command(about = "保存済みの状態を一覧表示（名前・保存日時・ペイン数）")]
+    List {
+        #[arg(long, help = "結果を JSON で出力")]
+        json: bool,
+    },
+    #[command(about = "状態を JSON で表示")]
+    Show { name: String },
+    #[command(about = "状態を削除")]
+    Delete {
+        name: String,
+        #[arg(long, help = "確認なしで削除する（必須）")]
+        force: bool,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum TemplateFormat {
+    Kdl,
+    Json,
+}
+
+impl TemplateFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            TemplateFormat::Kdl => "kdl",
+            TemplateFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum ExportFormat {
+    Kdl,
+    Zellij,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
 }
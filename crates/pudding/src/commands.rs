@@ -0,0 +1,30 @@
+mod completions;
+mod doctor;
+mod print_config;
+mod run_dry_run;
+mod state_delete;
+mod state_list;
+mod state_show;
+mod store_entry;
+mod template_copy;
+mod template_diff;
+mod template_export;
+mod template_import;
+mod template_list;
+mod template_show;
+mod template_validate;
+
+pub use self::completions::print_completions;
+pub use self::doctor::run_doctor;
+pub use self::print_config::print_config;
+pub use self::run_dry_run::print_dry_run;
+pub use self::state_delete::delete_state_command;
+pub use self::state_list::list_states_command;
+pub use self::state_show::show_state;
+pub use self::template_copy::{copy_template_command, rename_template_command};
+pub use self::template_diff::diff_templates_command;
+pub use self::template_export::export_template;
+pub use self::template_import::import_template;
+pub use self::template_list::list_templates_command;
+pub use self::template_show::show_template;
+pub use self::template_validate::validate_template_target;
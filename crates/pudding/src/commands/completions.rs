@@ -0,0 +1,107 @@
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+
+use crate::cli::{Cli, CompletionShell};
+
+const BIN_NAME: &str = "pudding";
+const LIST_COMMAND: &str = "pudding template list --plain 2>/dev/null";
+
+pub fn print_completions(shell: CompletionShell) {
+    print!("{}", completion_script(shell));
+}
+
+fn completion_script(shell: CompletionShell) -> String {
+    let target = match shell {
+        CompletionShell::Bash => Shell::Bash,
+        CompletionShell::Zsh => Shell::Zsh,
+        CompletionShell::Fish => Shell::Fish,
+    };
+    let mut buf = Vec::new();
+    generate(target, &mut Cli::command(), BIN_NAME, &mut buf);
+    let script = String::from_utf8_lossy(&buf).into_owned();
+    match shell {
+        CompletionShell::Bash => format!("{script}{}", bash_templates()),
+        CompletionShell::Zsh => zsh_templates(&script),
+        CompletionShell::Fish => format!("{script}{}", fish_templates()),
+    }
+}
+
+// Template-name arguments use value_name = "TEMPLATE"; `template import --name` is a new name.
+fn bash_templates() -> String {
+    format!(
+        r#"
+_pudding_templates() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    if [[ "${{prev}}" == "--template" || ( "${{prev}}" == "--name" && " ${{COMP_WORDS[*]}} " != *" import "* ) ]]; then
+        COMPREPLY=($(compgen -W "$({LIST_COMMAND})" -- "${{cur}}"))
+        return 0
+    fi
+    _pudding "$@"
+}}
+complete -F _pudding_templates -o bashdefault -o default {BIN_NAME}
+"#
+    )
+}
+
+fn zsh_templates(script: &str) -> String {
+    let helper = format!(
+        r#"
+_pudding_templates() {{
+    local -a templates
+    templates=(${{(f)"$({LIST_COMMAND})"}})
+    _describe 'template' templates
+}}
+"#
+    );
+    let script = script.replace(":TEMPLATE:_default", ":TEMPLATE:_pudding_templates");
+    match script.find("\n_pudding() {") {
+        Some(pos) => format!("{}{}{}", &script[..pos], helper, &script[pos..]),
+        None => format!("{helper}{script}"),
+    }
+}
+
+fn fish_templates() -> String {
+    format!(
+        r#"
+complete -c {BIN_NAME} -n "__fish_seen_subcommand_from run" -l template -x -a "({LIST_COMMAND})"
+complete -c {BIN_NAME} -n "__fish_seen_subcommand_from edit apply show export" -l name -x -a "({LIST_COMMAND})"
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::completion_script;
+    use crate::cli::CompletionShell;
+
+    #[test]
+    fn every_shell_queries_template_list() {
+        for shell in [
+            CompletionShell::Bash,
+            CompletionShell::Zsh,
+            CompletionShell::Fish,
+        ] {
+            let script = completion_script(shell);
+            assert!(script.contains("pudding template list --plain"));
+        }
+    }
+
+    #[test]
+    fn zsh_template_arguments_use_dynamic_helper() {
+        let script = completion_script(CompletionShell::Zsh);
+        assert!(!script.contains(":TEMPLATE:_default"));
+        let template_arg = script
+            .lines()
+            .find(|line| line.contains("--template="))
+            .unwrap();
+        assert!(template_arg.contains(":TEMPLATE:_pudding_templates"));
+    }
+
+    #[test]
+    fn bash_wraps_generated_function() {
+        let script = completion_script(CompletionShell::Bash);
+        assert!(script.contains("_pudding \"$@\""));
+        assert!(script.trim_end().ends_with("pudding"));
+    }
+}
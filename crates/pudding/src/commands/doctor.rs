@@ -0,0 +1,312 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde::Serialize;
+
+use super::store_entry::print_json;
+use crate::{
+    config::{config_file_path, Config},
+    paths::{config_dir, states_dir, templates_dir},
+    template::{list_templates, load_template},
+    zellij::{is_in_zellij_session, zellij_session_name},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Serialize)]
+struct Check {
+    #[serde(rename = "name")]
+    label: String,
+    status: Status,
+    detail: String,
+}
+
+impl Check {
+    fn new(status: Status, label: &str, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            label: label.to_string(),
+            detail: detail.into(),
+        }
+    }
+}
+
+pub fn run_doctor(config_path: Option<&Path>, json: bool) -> i32 {
+    let mut checks = vec![check_zellij()];
+    checks.push(check_zellij_session(
+        is_in_zellij_session(),
+        zellij_session_name(),
+    ));
+    checks.push(check_terminal_size(crossterm::terminal::size()));
+    let pudding_dir = config_dir().join("pudding");
+    checks.push(Check::new(
+        Status::Pass,
+        "config dir",
+        pudding_dir.display().to_string(),
+    ));
+    let (config_check, config) = check_config(config_path);
+    checks.push(config_check);
+    checks.push(check_private_dir("templates dir", &templates_dir()));
+    checks.push(check_private_dir("states dir", &states_dir()));
+    checks.extend(check_templates());
+    let default_command = config
+        .map(|config| config.default_command)
+        .unwrap_or_else(|| Config::default().default_command);
+    checks.push(check_default_command(&default_command));
+
+    if json {
+        if let Err(err) = print_json(&checks) {
+            eprintln!("error: {err:#}");
+            return 1;
+        }
+        return exit_code(&checks);
+    }
+    for check in &checks {
+        let tag = match check.status {
+            Status::Pass => "pass",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        };
+        println!("[{tag}] {}: {}", check.label, check.detail);
+    }
+    exit_code(&checks)
+}
+
+fn exit_code(checks: &[Check]) -> i32 {
+    if checks.iter().any(|c| c.status == Status::Fail) {
+        1
+    } else {
+        0
+    }
+}
+
+// zellij is only needed for exported layouts, so its absence is a warning.
+fn check_zellij() -> Check {
+    match Command::new("zellij").arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            Check::new(Status::Pass, "zellij", version)
+        }
+        Ok(output) => Check::new(
+            Status::Warn,
+            "zellij",
+            format!("`zellij --version` exited with {}", output.status),
+        ),
+        Err(_) => Check::new(
+            Status::Warn,
+            "zellij",
+            "not found on PATH (only needed to use exported layouts)",
+        ),
+    }
+}
+
+// Running inside zellij is fine either way; this is only reported to explain behaviour.
+fn check_zellij_session(inside: bool, name: Option<String>) -> Check {
+    let detail = match (inside, name) {
+        (true, Some(name)) => format!("inside session {name}"),
+        (true, None) => "inside a session".to_string(),
+        (false, _) => "not inside a session".to_string(),
+    };
+    Check::new(Status::Pass, "zellij session", detail)
+}
+
+// The runtime falls back to 80x24 without a terminal, so a failure is only a warning.
+fn check_terminal_size(size: io::Result<(u16, u16)>) -> Check {
+    match size {
+        Ok((cols, rows)) => Check::new(Status::Pass, "terminal size", format!("{cols}x{rows}")),
+        Err(err) => Check::new(
+            Status::Warn,
+            "terminal size",
+            format!("unavailable ({err}); runs fall back to 80x24"),
+        ),
+    }
+}
+
+fn check_config(config_path: Option<&Path>) -> (Check, Option<Config>) {
+    let path = config_path
+        .map(Path::to_path_buf)
+        .unwrap_or_else(config_file_path);
+    if config_path.is_none() && !path.exists() {
+        let detail = format!("{} missing (created on next run)", path.display());
+        return (Check::new(Status::Warn, "config", detail), None);
+    }
+    match Config::load_from(&path) {
+        Ok(config) => (
+            Check::new(Status::Pass, "config", path.display().to_string()),
+            Some(config),
+        ),
+        Err(err) => (Check::new(Status::Fail, "config", format!("{err:#}")), None),
+    }
+}
+
+fn check_private_dir(label: &str, dir: &Path) -> Check {
+    let meta = match fs::metadata(dir) {
+        Ok(meta) => meta,
+        Err(_) => {
+            let detail = format!("{} missing (created on first save)", dir.display());
+            return Check::new(Status::Warn, label, detail);
+        }
+    };
+    if !meta.is_dir() {
+        return Check::new(
+            Status::Fail,
+            label,
+            format!("{} is not a directory", dir.display()),
+        );
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = meta.permissions().mode() & 0o777;
+        if mode != 0o700 {
+            let detail = format!("{} has mode {mode:o}, expected 700", dir.display());
+            return Check::new(Status::Fail, label, detail);
+        }
+    }
+    Check::new(Status::Pass, label, dir.display().to_string())
+}
+
+fn check_templates() -> Vec<Check> {
+    let names = match list_templates() {
+        Ok(names) => names,
+        Err(err) => return vec![Check::new(Status::Fail, "templates", format!("{err:#}"))],
+    };
+    let mut checks = Vec::new();
+    for name in &names {
+        if let Err(err) = load_template(name) {
+            checks.push(Check::new(
+                Status::Fail,
+                &format!("template {name}"),
+                format!("{err:#}"),
+            ));
+        }
+    }
+    if checks.is_empty() {
+        checks.push(Check::new(
+            Status::Pass,
+            "templates",
+            format!("{} valid", names.len()),
+        ));
+    }
+    checks
+}
+
+fn check_default_command(command: &str) -> Check {
+    let path_var = env::var_os("PATH").unwrap_or_default();
+    match resolve_program(command, &path_var) {
+        Some(path) => Check::new(
+            Status::Pass,
+            "default_command",
+            format!("{command} -> {}", path.display()),
+        ),
+        None => Check::new(
+            Status::Fail,
+            "default_command",
+            format!("{command} not found on PATH"),
+        ),
+    }
+}
+
+pub(super) fn resolve_program(program: &str, path_var: &std::ffi::OsStr) -> Option<PathBuf> {
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return is_executable(&path).then_some(path);
+    }
+    env::split_paths(path_var)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(meta) = fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        meta.is_file() && meta.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        meta.is_file()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use super::{
+        check_private_dir, check_terminal_size, check_zellij_session, exit_code, resolve_program,
+        Check, Status,
+    };
+
+    #[test]
+    fn resolves_programs_on_given_path() {
+        let path = OsString::from("/nonexistent:/bin:/usr/bin");
+        assert!(resolve_program("sh", &path).is_some());
+        assert!(resolve_program("pudding-no-such-binary", &path).is_none());
+        assert!(resolve_program("/bin/sh", &OsString::new()).is_some());
+    }
+
+    #[test]
+    fn checks_serialize_with_lowercase_status() {
+        let checks = vec![Check::new(Status::Warn, "zellij", "missing")];
+        let value = serde_json::to_value(&checks).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([{"name": "zellij", "status": "warn", "detail": "missing"}])
+        );
+    }
+
+    #[test]
+    fn reports_session_and_terminal_size_without_failing() {
+        let named = check_zellij_session(true, Some("dev".to_string()));
+        assert_eq!(
+            (named.status, named.detail.as_str()),
+            (Status::Pass, "inside session dev")
+        );
+        let outside = check_zellij_session(false, None);
+        assert_eq!(outside.detail, "not inside a session");
+
+        assert_eq!(check_terminal_size(Ok((120, 40))).detail, "120x40");
+        let missing = check_terminal_size(Err(std::io::Error::other("not a tty")));
+        assert_eq!(missing.status, Status::Warn);
+    }
+
+    #[test]
+    fn fails_only_on_fail_status() {
+        let warn = vec![Check::new(Status::Warn, "x", "")];
+        assert_eq!(exit_code(&warn), 0);
+        let fail = vec![
+            Check::new(Status::Pass, "x", ""),
+            Check::new(Status::Fail, "y", ""),
+        ];
+        assert_eq!(exit_code(&fail), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_dir_requires_0700() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("pudding-doctor-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let loose = check_private_dir("dir", &dir).status;
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700)).unwrap();
+        let strict = check_private_dir("dir", &dir).status;
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loose, Status::Fail);
+        assert_eq!(strict, Status::Pass);
+        assert_eq!(check_private_dir("dir", &dir).status, Status::Warn);
+    }
+}
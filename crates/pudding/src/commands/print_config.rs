@@ -0,0 +1,79 @@
+use std::{collections::BTreeMap, env, path::Path};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::{doctor::resolve_program, store_entry::print_json};
+use crate::{
+    action::RUNTIME_ACTIONS,
+    config::{default_keybinds, Config},
+};
+
+// The config as the runtime sees it. Keybinds list every action: names the file leaves
+// out keep their default, just as `runtime_actions_from_config` resolves them.
+#[derive(Serialize)]
+struct EffectiveConfig {
+    config_path: String,
+    resolved_command: Option<String>,
+    keybinds: BTreeMap<String, String>,
+    #[serde(flatten)]
+    config: serde_json::Value,
+}
+
+pub fn print_config(config: &Config, path: &Path) -> Result<()> {
+    let path_var = env::var_os("PATH").unwrap_or_default();
+    let resolved = resolve_program(&config.default_command, &path_var);
+    print_json(&effective_config(config, path, resolved.as_deref())?)
+}
+
+fn effective_config(
+    config: &Config,
+    path: &Path,
+    resolved: Option<&Path>,
+) -> Result<EffectiveConfig> {
+    let defaults = default_keybinds();
+    let mut keybinds = BTreeMap::new();
+    for (name, _) in RUNTIME_ACTIONS {
+        if let Some(key) = config.keybinds.get(*name).or_else(|| defaults.get(*name)) {
+            keybinds.insert(name.to_string(), key.clone());
+        }
+    }
+    let mut value = serde_json::to_value(config)?;
+    if let Some(map) = value.as_object_mut() {
+        map.remove("keybinds");
+    }
+    Ok(EffectiveConfig {
+        config_path: path.display().to_string(),
+        resolved_command: resolved.map(|path| path.display().to_string()),
+        keybinds,
+        config: value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::effective_config;
+    use crate::config::Config;
+
+    #[test]
+    fn reflects_overrides_from_the_config_file() {
+        let dir = std::env::temp_dir().join(format!("pudding-print-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        let data = r#"{"default_command":"zsh","keybinds":{"quit":"Ctrl+q"}}"#;
+        std::fs::write(&path, data).unwrap();
+        let config = Config::load_from(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let resolved = Some(Path::new("/bin/zsh"));
+        let effective = effective_config(&config.unwrap(), &path, resolved).unwrap();
+        let value = serde_json::to_value(effective).unwrap();
+        assert_eq!(value["default_command"], "zsh");
+        assert_eq!(value["resolved_command"], "/bin/zsh");
+        assert_eq!(value["config_path"], path.display().to_string());
+        assert_eq!(value["keybinds"]["quit"], "Ctrl+q");
+        assert_eq!(value["keybinds"]["split_vertical"], "v");
+        assert_eq!(value["show_clock"], true);
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt::Write;
+
+use ratatui::layout::Rect;
+
+use crate::{
+    layout::layout_rects,
+    model::{Node, Orientation, Template},
+    runtime::planned_rects,
+};
+
+pub fn print_dry_run(template: &Template) {
+    let (area, _) = planned_rects(&template.layout);
+    print!("{}", render_dry_run(template, area));
+}
+
+// Every tab gets the same area, as the runtime lays hidden tabs out too.
+fn render_dry_run(template: &Template, area: Rect) -> String {
+    let mut out = format!(
+        "# dry run: {} ({}x{})\n",
+        template.name, area.width, area.height
+    );
+    for (index, layout) in template.layouts().enumerate() {
+        if !template.tabs.is_empty() {
+            let _ = writeln!(out, "tab {} {:?}", index + 1, template.tab_name(index));
+        }
+        let mut rects = Vec::new();
+        layout_rects(layout, area, &mut rects);
+        write_node(layout, &rects, 0, &mut out);
+    }
+    out
+}
+
+fn write_node(node: &Node, rects: &[(u64, Rect)], depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    match node {
+        Node::Bite {
+            id,
+            name,
+            command,
+            cwd,
+            ..
+        } => {
+            let _ = write!(out, "{indent}bite {id} {name:?} command={command:?}");
+            if let Some(cwd) = cwd {
+                let _ = write!(out, " cwd={cwd:?}");
+            }
+            match rects.iter().find(|(rect_id, _)| rect_id == id) {
+                Some((_, r)) => {
+                    let _ = writeln!(out, " at {},{} {}x{}", r.x, r.y, r.width, r.height);
+                }
+                None => out.push_str(" (not visible)\n"),
+            }
+        }
+        Node::Spoon {
+            id,
+            orientation,
+            ratio,
+            first,
+            second,
+        } => {
+            let direction = match orientation {
+                Orientation::Vertical => "vertical",
+                Orientation::Horizontal => "horizontal",
+            };
+            let _ = writeln!(out, "{indent}split {id} {direction} ratio={ratio:.2}");
+            write_node(first, rects, depth + 1, out);
+            write_node(second, rects, depth + 1, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+
+    use super::render_dry_run;
+    use crate::model::{Node, Orientation, Tab, Template};
+
+    #[test]
+    fn prints_tree_with_commands_cwd_and_rects() {
+        let mut left = Node::bite(2, "editor", "vim");
+        if let Node::Bite { cwd, .. } = &mut left {
+            *cwd = Some("/src".to_string());
+        }
+        let template = Template {
+            name: "work".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.5,
+                first: Box::new(left),
+                second: Box::new(Node::bite(3, "shell", "bash")),
+            },
+        };
+        let area = Rect::new(0, 0, 80, 22);
+
+        assert_eq!(
+            render_dry_run(&template, area),
+            "# dry run: work (80x22)\n\
+             split 1 vertical ratio=0.50\n  \
+             bite 2 \"editor\" command=\"vim\" cwd=\"/src\" at 0,0 40x22\n  \
+             bite 3 \"shell\" command=\"bash\" at 40,0 40x22\n"
+        );
+    }
+    #[test]
+    fn lists_every_tab_under_its_name() {
+        let template = Template {
+            name: "dev".to_string(),
+            tabs: vec![Tab {
+                name: "logs".to_string(),
+                layout: Node::bite(1, "tail", "tail"),
+            }],
+            active_tab: 0,
+            layout: Node::bite(1, "shell", "bash"),
+        };
+
+        assert_eq!(
+            render_dry_run(&template, Rect::new(0, 0, 80, 22)),
+            "# dry run: dev (80x22)\n\
+             tab 1 \"dev\"\n\
+             bite 1 \"shell\" command=\"bash\" at 0,0 80x22\n\
+             tab 2 \"logs\"\n\
+             bite 1 \"tail\" command=\"tail\" at 0,0 80x22\n"
+        );
+    }
+}
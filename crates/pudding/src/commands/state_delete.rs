@@ -0,0 +1,13 @@
+use anyhow::{bail, Result};
+
+use crate::template::{delete_state, state_path};
+
+pub fn delete_state_command(name: &str, force: bool) -> Result<()> {
+    if !force {
+        bail!("refusing to delete state {name} without --force");
+    }
+    let path = state_path(name);
+    delete_state(name)?;
+    println!("deleted {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::{fs, time::UNIX_EPOCH};
+
+use anyhow::Result;
+
+use super::store_entry::{print_json, StoreEntry};
+use crate::{
+    layout::collect_bites,
+    template::{list_states, load_state, state_path},
+    timestamp::utc_from_unix,
+};
+
+pub fn list_states_command(json: bool) -> Result<()> {
+    if json {
+        let entries: Vec<StoreEntry> = list_states()?
+            .iter()
+            .map(|name| {
+                let loaded = load_state(name).map_err(|err| format!("{err:#}"));
+                StoreEntry::new(name, &state_path(name), &loaded)
+            })
+            .collect();
+        return print_json(&entries);
+    }
+    let mut rows = Vec::new();
+    for name in list_states()? {
+        let state = match load_state(&name) {
+            Ok(state) => state,
+            Err(err) => {
+                eprintln!("warning: skipped state {name}: {err:#}");
+                continue;
+            }
+        };
+        let mut ids = Vec::new();
+        for layout in state.layouts() {
+            collect_bites(layout, &mut ids);
+        }
+        let saved_at = fs::metadata(state_path(&name))
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|elapsed| format_utc(elapsed.as_secs()))
+            .unwrap_or_else(|| "-".to_string());
+        rows.push((name, saved_at, ids.len()));
+    }
+    let width = rows
+        .iter()
+        .map(|(name, ..)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    println!("{:width$}  {:19}  PANES", "NAME", "SAVED (UTC)");
+    for (name, saved_at, panes) in rows {
+        println!("{name:width$}  {saved_at:19}  {panes}");
+    }
+    Ok(())
+}
+
+fn format_utc(secs: u64) -> String {
+    let t = utc_from_unix(secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        t.year, t.month, t.day, t.hour, t.minute, t.second
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_utc;
+
+    #[test]
+    fn formats_unix_seconds_as_utc() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14 22:13:20");
+    }
+}
@@ -0,0 +1,9 @@
+use anyhow::{Context, Result};
+
+use crate::template::load_state;
+
+pub fn show_state(name: &str) -> Result<()> {
+    let state = load_state(name).with_context(|| format!("failed to load state: {name}"))?;
+    println!("{}", serde_json::to_string_pretty(&state)?);
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{layout::collect_bites, model::Template};
+
+// Shared `--json` schema for `template list` and `state list`; keep field names stable.
+#[derive(Debug, Serialize)]
+pub(super) struct StoreEntry {
+    pub name: String,
+    pub path: String,
+    pub panes: Option<usize>,
+    pub valid: bool,
+}
+
+impl StoreEntry {
+    pub(super) fn new(name: &str, path: &Path, loaded: &Result<Template, String>) -> Self {
+        let panes = loaded.as_ref().ok().map(|template| {
+            let mut ids = Vec::new();
+            for layout in template.layouts() {
+                collect_bites(layout, &mut ids);
+            }
+            ids.len()
+        });
+        Self {
+            name: name.to_string(),
+            path: path.display().to_string(),
+            panes,
+            valid: loaded.is_ok(),
+        }
+    }
+}
+
+pub(super) fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::StoreEntry;
+    use crate::model::default_template;
+
+    #[test]
+    fn entries_round_trip_through_json_values() {
+        let entries = vec![
+            StoreEntry::new(
+                "default",
+                Path::new("/t/default.json"),
+                &Ok(default_template()),
+            ),
+            StoreEntry::new(
+                "broken",
+                Path::new("/t/broken.json"),
+                &Err("bad".to_string()),
+            ),
+        ];
+        let text = serde_json::to_string(&entries).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([
+                {"name": "default", "path": "/t/default.json", "panes": 1, "valid": true},
+                {"name": "broken", "path": "/t/broken.json", "panes": null, "valid": false}
+            ])
+        );
+    }
+}
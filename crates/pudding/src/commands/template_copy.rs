@@ -0,0 +1,36 @@
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+
+use crate::template::{load_stored_template, save_template, template_path};
+
+pub fn copy_template_command(from: &str, to: &str, force: bool) -> Result<()> {
+    copy_template(from, to, force)?;
+    println!("{from} -> {to}");
+    Ok(())
+}
+
+pub fn rename_template_command(from: &str, to: &str, force: bool) -> Result<()> {
+    if from == to {
+        bail!("source and destination are the same: {from}");
+    }
+    copy_template(from, to, force)?;
+    let source = template_path(from);
+    fs::remove_file(&source)
+        .with_context(|| format!("failed to remove template: {}", source.display()))?;
+    println!("{from} -> {to}");
+    Ok(())
+}
+
+fn copy_template(from: &str, to: &str, force: bool) -> Result<()> {
+    let mut template = load_stored_template(from)?;
+    template.name = to.to_string();
+    let dest = template_path(to);
+    if dest.exists() && !force {
+        bail!(
+            "template already exists: {} (use --force to overwrite)",
+            dest.display()
+        );
+    }
+    save_template(&template)
+}
@@ -0,0 +1,356 @@
+use std::{collections::BTreeMap, io::IsTerminal};
+
+use anyhow::Result;
+
+use crate::{
+    layout::collect_bites,
+    model::{Node, Orientation, Template},
+    template::{load_stored_template, to_kdl_document},
+};
+
+#[derive(Debug, PartialEq)]
+enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+pub fn diff_templates_command(a: &str, b: &str, kdl: bool, ids: bool) -> Result<()> {
+    let mut left = load_stored_template(a)?;
+    let mut right = load_stored_template(b)?;
+    left.name = a.to_string();
+    right.name = b.to_string();
+    let lines = if kdl {
+        diff_kdl(&left, &right)
+    } else if ids {
+        diff_by_id(&left.layout, &right.layout)
+    } else {
+        diff_layouts(&left.layout, &right.layout)
+    };
+    let color = std::io::stdout().is_terminal();
+    println!("--- {a}");
+    println!("+++ {b}");
+    for line in &lines {
+        println!("{}", render_line(line, color));
+    }
+    Ok(())
+}
+
+fn render_line(line: &DiffLine, color: bool) -> String {
+    let (prefix, text, code) = match line {
+        DiffLine::Same(text) => (' ', text, None),
+        DiffLine::Removed(text) => ('-', text, Some(31)),
+        DiffLine::Added(text) => ('+', text, Some(32)),
+    };
+    match code {
+        Some(code) if color => format!("\x1b[{code}m{prefix}{text}\x1b[0m"),
+        _ => format!("{prefix}{text}"),
+    }
+}
+
+fn diff_layouts(a: &Node, b: &Node) -> Vec<DiffLine> {
+    let mut out = Vec::new();
+    diff_nodes(a, b, "root", &mut out);
+    out
+}
+
+fn diff_nodes(a: &Node, b: &Node, path: &str, out: &mut Vec<DiffLine>) {
+    let mut changed = |field: &str, old: String, new: String| {
+        if old != new {
+            out.push(DiffLine::Removed(format!("{path} {field}: {old}")));
+            out.push(DiffLine::Added(format!("{path} {field}: {new}")));
+        }
+    };
+    match (a, b) {
+        (
+            Node::Bite {
+                name: name_a,
+                command: command_a,
+                ..
+            },
+            Node::Bite {
+                name: name_b,
+                command: command_b,
+                ..
+            },
+        ) => {
+            changed("name", format!("{name_a:?}"), format!("{name_b:?}"));
+            changed(
+                "command",
+                format!("{command_a:?}"),
+                format!("{command_b:?}"),
+            );
+        }
+        (
+            Node::Spoon {
+                orientation: orientation_a,
+                ratio: ratio_a,
+                first: first_a,
+                second: second_a,
+                ..
+            },
+            Node::Spoon {
+                orientation: orientation_b,
+                ratio: ratio_b,
+                first: first_b,
+                second: second_b,
+                ..
+            },
+        ) => {
+            changed(
+                "orientation",
+                orientation_label(*orientation_a).to_string(),
+                orientation_label(*orientation_b).to_string(),
+            );
+            changed("ratio", format!("{ratio_a:.2}"), format!("{ratio_b:.2}"));
+            diff_nodes(first_a, first_b, &format!("{path}.first"), out);
+            diff_nodes(second_a, second_b, &format!("{path}.second"), out);
+        }
+        _ => {
+            out.push(DiffLine::Removed(format!("{path}: {}", summarize(a))));
+            out.push(DiffLine::Added(format!("{path}: {}", summarize(b))));
+        }
+    }
+}
+
+// Matches nodes on id instead of position, so moved panes show up as edits, not replacements.
+fn diff_by_id(a: &Node, b: &Node) -> Vec<DiffLine> {
+    let left = nodes_by_id(a);
+    let right = nodes_by_id(b);
+    let mut ids: Vec<u64> = left.keys().chain(right.keys()).copied().collect();
+    ids.sort_unstable();
+    ids.dedup();
+    let mut out = Vec::new();
+    for id in ids {
+        match (left.get(&id), right.get(&id)) {
+            (Some(old), Some(new)) => diff_node_fields(old, new, id, &mut out),
+            (Some(old), None) => out.push(DiffLine::Removed(format!("#{id} {}", summarize(old)))),
+            (None, Some(new)) => out.push(DiffLine::Added(format!("#{id} {}", summarize(new)))),
+            (None, None) => {}
+        }
+    }
+    out
+}
+
+fn nodes_by_id(root: &Node) -> BTreeMap<u64, &Node> {
+    let mut nodes = BTreeMap::new();
+    index_nodes(root, &mut nodes);
+    nodes
+}
+
+fn index_nodes<'a>(node: &'a Node, out: &mut BTreeMap<u64, &'a Node>) {
+    out.insert(node.id(), node);
+    if let Node::Spoon { first, second, .. } = node {
+        index_nodes(first, out);
+        index_nodes(second, out);
+    }
+}
+
+fn diff_node_fields(a: &Node, b: &Node, id: u64, out: &mut Vec<DiffLine>) {
+    let mut changed = |field: &str, old: String, new: String| {
+        if old != new {
+            out.push(DiffLine::Removed(format!("#{id} {field}: {old}")));
+            out.push(DiffLine::Added(format!("#{id} {field}: {new}")));
+        }
+    };
+    match (a, b) {
+        (
+            Node::Bite {
+                name: name_a,
+                command: command_a,
+                ..
+            },
+            Node::Bite {
+                name: name_b,
+                command: command_b,
+                ..
+            },
+        ) => {
+            changed("name", format!("{name_a:?}"), format!("{name_b:?}"));
+            changed(
+                "command",
+                format!("{command_a:?}"),
+                format!("{command_b:?}"),
+            );
+        }
+        (
+            Node::Spoon {
+                orientation: orientation_a,
+                ratio: ratio_a,
+                ..
+            },
+            Node::Spoon {
+                orientation: orientation_b,
+                ratio: ratio_b,
+                ..
+            },
+        ) => {
+            changed(
+                "orientation",
+                orientation_label(*orientation_a).to_string(),
+                orientation_label(*orientation_b).to_string(),
+            );
+            changed("ratio", format!("{ratio_a:.2}"), format!("{ratio_b:.2}"));
+        }
+        _ => changed("kind", summarize(a), summarize(b)),
+    }
+}
+
+fn summarize(node: &Node) -> String {
+    match node {
+        Node::Bite { name, command, .. } => format!("pane {name:?} ({command:?})"),
+        Node::Spoon {
+            orientation, ratio, ..
+        } => {
+            let mut ids = Vec::new();
+            collect_bites(node, &mut ids);
+            format!(
+                "split {} {ratio:.2} ({} panes)",
+                orientation_label(*orientation),
+                ids.len()
+            )
+        }
+    }
+}
+
+fn orientation_label(orientation: Orientation) -> &'static str {
+    match orientation {
+        Orientation::Vertical => "vertical",
+        Orientation::Horizontal => "horizontal",
+    }
+}
+
+fn diff_kdl(a: &Template, b: &Template) -> Vec<DiffLine> {
+    let left = to_kdl_document(a);
+    let right = to_kdl_document(b);
+    diff_text(
+        &left.lines().collect::<Vec<_>>(),
+        &right.lines().collect::<Vec<_>>(),
+    )
+}
+
+// Plain LCS line diff; layouts are small enough that the quadratic table is fine.
+fn diff_text(a: &[&str], b: &[&str]) -> Vec<DiffLine> {
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out.push(DiffLine::Same(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push(DiffLine::Removed(a[i].to_string()));
+            i += 1;
+        } else {
+            out.push(DiffLine::Added(b[j].to_string()));
+            j += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_by_id, diff_layouts, diff_text, render_line, DiffLine};
+    use crate::model::{Node, Orientation};
+
+    fn split(ratio: f32, second: Node) -> Node {
+        Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio,
+            first: Box::new(Node::bite(2, "editor", "vim")),
+            second: Box::new(second),
+        }
+    }
+
+    #[test]
+    fn reports_changed_fields_by_path() {
+        let a = split(0.5, Node::bite(3, "shell", "bash"));
+        let b = split(0.6, Node::bite(3, "shell", "zsh"));
+        assert_eq!(
+            diff_layouts(&a, &b),
+            vec![
+                DiffLine::Removed("root ratio: 0.50".to_string()),
+                DiffLine::Added("root ratio: 0.60".to_string()),
+                DiffLine::Removed("root.second command: \"bash\"".to_string()),
+                DiffLine::Added("root.second command: \"zsh\"".to_string()),
+            ]
+        );
+        assert!(diff_layouts(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn reports_replaced_subtrees() {
+        let a = split(0.5, Node::bite(3, "shell", "bash"));
+        let b = split(0.5, split(0.5, Node::bite(4, "logs", "tail")));
+        let lines = diff_layouts(&a, &b);
+        assert_eq!(
+            lines,
+            vec![
+                DiffLine::Removed("root.second: pane \"shell\" (\"bash\")".to_string()),
+                DiffLine::Added("root.second: split vertical 0.50 (2 panes)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_diff_keeps_common_lines() {
+        let lines = diff_text(&["a", "b", "c"], &["a", "x", "c"]);
+        assert_eq!(
+            lines,
+            vec![
+                DiffLine::Same("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Added("x".to_string()),
+                DiffLine::Same("c".to_string()),
+            ]
+        );
+        assert_eq!(render_line(&lines[1], false), "-b");
+        assert_eq!(render_line(&lines[2], true), "\x1b[32m+x\x1b[0m");
+    }
+
+    #[test]
+    fn id_diff_reports_only_the_changed_command() {
+        let a = split(0.5, Node::bite(3, "shell", "bash"));
+        let b = split(0.5, Node::bite(3, "shell", "zsh"));
+        assert_eq!(
+            diff_by_id(&a, &b),
+            vec![
+                DiffLine::Removed("#3 command: \"bash\"".to_string()),
+                DiffLine::Added("#3 command: \"zsh\"".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn id_diff_reports_added_removed_and_renamed_bites() {
+        let a = split(0.5, Node::bite(3, "shell", "bash"));
+        let b = Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(Node::bite(2, "code", "vim")),
+            second: Box::new(Node::bite(4, "logs", "tail")),
+        };
+        assert_eq!(
+            diff_by_id(&a, &b),
+            vec![
+                DiffLine::Removed("#2 name: \"editor\"".to_string()),
+                DiffLine::Added("#2 name: \"code\"".to_string()),
+                DiffLine::Removed("#3 pane \"shell\" (\"bash\")".to_string()),
+                DiffLine::Added("#4 pane \"logs\" (\"tail\")".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::{
+    cli::ExportFormat,
+    paths::resolve_user_path,
+    template::{
+        ensure_dir_secure, load_stored_template, to_kdl_document, to_zellij_kdl, write_private_file,
+    },
+    zellij::layouts_dir,
+};
+
+pub fn export_template(
+    name: &str,
+    format: ExportFormat,
+    output: Option<&str>,
+    to_stdout: bool,
+) -> Result<()> {
+    let mut template = load_stored_template(name)?;
+    template.name = name.to_string();
+    let document = match format {
+        ExportFormat::Kdl => to_kdl_document(&template),
+        ExportFormat::Zellij => to_zellij_kdl(&template),
+    };
+    if to_stdout {
+        print!("{document}");
+        return Ok(());
+    }
+    let path = export_path(name, output);
+    if let Some(parent) = path.parent() {
+        ensure_dir_secure(parent)?;
+    }
+    write_private_file(&path, &document)?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+fn export_path(name: &str, output: Option<&str>) -> PathBuf {
+    match output {
+        Some(path) => resolve_user_path(path),
+        None => layouts_dir().join(format!("{name}.kdl")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_path, export_template};
+    use crate::cli::ExportFormat;
+
+    #[test]
+    fn defaults_to_zellij_layouts_dir() {
+        assert!(export_path("work", None).ends_with("layouts/work.kdl"));
+    }
+
+    #[test]
+    fn unknown_name_is_an_error() {
+        let err =
+            export_template("pudding-test-missing", ExportFormat::Kdl, None, true).unwrap_err();
+        assert_eq!(err.to_string(), "template not found: pudding-test-missing");
+    }
+
+    #[test]
+    fn output_overrides_destination() {
+        let path = export_path("work", Some("/tmp/out.kdl"));
+        assert_eq!(path, std::path::PathBuf::from("/tmp/out.kdl"));
+    }
+}
@@ -0,0 +1,24 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    config::Config,
+    paths::resolve_user_path,
+    template::{from_kdl_document, save_template, template_path},
+};
+
+pub fn import_template(path: &str, name: &str, config: &Config) -> Result<()> {
+    let path = resolve_user_path(path);
+    let data = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read layout: {}", path.display()))?;
+    let (template, warnings) = from_kdl_document(&data, &config.default_command)
+        .with_context(|| format!("failed to parse layout: {}", path.display()))?
+        .into_template(name);
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    save_template(&template).context("imported layout is not a valid template")?;
+    println!("{} -> {}", path.display(), template_path(name).display());
+    Ok(())
+}
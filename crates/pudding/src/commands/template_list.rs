@@ -0,0 +1,103 @@
+use std::io::IsTerminal;
+
+use anyhow::Result;
+
+use super::store_entry::{print_json, StoreEntry};
+use crate::{
+    layout::walk,
+    model::{Node, Template},
+    paths::templates_dir,
+    template::{list_templates, load_template, template_path},
+};
+
+pub fn list_templates_command(plain: bool, json: bool) -> Result<()> {
+    let names = list_templates()?;
+    if json {
+        let entries: Vec<StoreEntry> = names
+            .iter()
+            .map(|name| {
+                let loaded = load_template(name).map_err(|err| format!("{err:#}"));
+                StoreEntry::new(name, &template_path(name), &loaded)
+            })
+            .collect();
+        return print_json(&entries);
+    }
+    if plain || !std::io::stdout().is_terminal() {
+        print!("{}", render_plain(&names));
+        return Ok(());
+    }
+    let entries: Vec<(String, Result<Template, String>)> = names
+        .into_iter()
+        .map(|name| {
+            let loaded = load_template(&name).map_err(|err| format!("{err:#}"));
+            (name, loaded)
+        })
+        .collect();
+    print!("{}", render_table(&entries));
+    Ok(())
+}
+
+fn render_plain(names: &[String]) -> String {
+    names.iter().map(|name| format!("{name}\n")).collect()
+}
+
+fn render_table(entries: &[(String, Result<Template, String>)]) -> String {
+    let mut out = format!("# templates ({})\n", templates_dir().display());
+    if entries.is_empty() {
+        out.push_str("(none)\n");
+    }
+    let width = entries
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, loaded) in entries {
+        match loaded {
+            Ok(template) => {
+                let names = bite_names(&template.layout);
+                out.push_str(&format!(
+                    "  {name:width$}  {} panes  {}\n",
+                    names.len(),
+                    names.join(" | ")
+                ));
+            }
+            Err(err) => out.push_str(&format!("! {name:width$}  error: {err}\n")),
+        }
+    }
+    out
+}
+
+fn bite_names(layout: &Node) -> Vec<String> {
+    let mut names = Vec::new();
+    walk(layout, &mut |node| {
+        if let Node::Bite { name, .. } = node {
+            names.push(name.clone());
+        }
+    });
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_plain, render_table};
+    use crate::model::default_template;
+
+    #[test]
+    fn plain_output_is_one_name_per_line() {
+        let names = vec!["default".to_string(), "work".to_string()];
+        assert_eq!(render_plain(&names), "default\nwork\n");
+        assert_eq!(render_plain(&[]), "");
+    }
+
+    #[test]
+    fn table_summarizes_panes_and_marks_corrupt_files() {
+        let entries = vec![
+            ("broken".to_string(), Err("invalid json".to_string())),
+            ("default".to_string(), Ok(default_template())),
+        ];
+        let out = render_table(&entries);
+        assert!(out.starts_with("# templates ("));
+        assert!(out.contains("! broken   error: invalid json\n"));
+        assert!(out.contains("  default  1 panes  main\n"));
+    }
+}
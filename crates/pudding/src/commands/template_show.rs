@@ -0,0 +1,53 @@
+use std::io::IsTerminal;
+
+use anyhow::Result;
+
+use crate::{
+    cli::TemplateFormat,
+    model::Template,
+    template::{load_stored_template, to_kdl_document},
+};
+
+pub fn show_template(name: &str, format: TemplateFormat) -> Result<()> {
+    let mut template = load_stored_template(name)?;
+    template.name = name.to_string();
+    let body = render_template(&template, format)?;
+    if std::io::stdout().is_terminal() {
+        println!("# template: {} ({})", name, format.label());
+    }
+    print!("{}", body);
+    Ok(())
+}
+
+fn render_template(template: &Template, format: TemplateFormat) -> Result<String> {
+    match format {
+        TemplateFormat::Kdl => Ok(to_kdl_document(template)),
+        TemplateFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(template)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_template, show_template};
+    use crate::{cli::TemplateFormat, model::default_template};
+
+    #[test]
+    fn json_output_round_trips() {
+        let template = default_template();
+        let out = render_template(&template, TemplateFormat::Json).unwrap();
+        let parsed: crate::model::Template = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed.name, "default");
+    }
+
+    #[test]
+    fn unknown_name_is_an_error() {
+        let err = show_template("pudding-test-missing", TemplateFormat::Json).unwrap_err();
+        assert_eq!(err.to_string(), "template not found: pudding-test-missing");
+    }
+
+    #[test]
+    fn kdl_output_starts_with_layout() {
+        let out = render_template(&default_template(), TemplateFormat::Kdl).unwrap();
+        assert!(out.starts_with("layout {\n"));
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    model::Template,
+    template::{from_kdl_document, template_path, template_problems, Problem, Severity},
+};
+
+pub fn validate_template_target(target: &str) -> i32 {
+    let path = resolve_target(target);
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(err) => {
+            eprintln!("error: cannot read {}: {err}", path.display());
+            return 2;
+        }
+    };
+    let is_kdl = path.extension().is_some_and(|ext| ext == "kdl");
+    let problems = if is_kdl {
+        diagnose_kdl(&data)
+    } else {
+        diagnose(&data)
+    };
+    for problem in &problems {
+        println!("{problem}");
+    }
+    let code = exit_code(&problems);
+    if code == 0 {
+        println!("ok: {}", path.display());
+    }
+    code
+}
+
+fn resolve_target(target: &str) -> PathBuf {
+    let as_path = Path::new(target);
+    if as_path.exists()
+        || target.contains(std::path::MAIN_SEPARATOR)
+        || as_path.extension().is_some()
+    {
+        return as_path.to_path_buf();
+    }
+    template_path(target)
+}
+
+fn diagnose(data: &str) -> Vec<Problem> {
+    match serde_json::from_str::<Template>(data) {
+        Ok(template) => template_problems(&template),
+        Err(err) => vec![Problem {
+            severity: Severity::Error,
+            tab: 0,
+            node_id: None,
+            message: format!("invalid template json: {err}"),
+        }],
+    }
+}
+
+fn diagnose_kdl(data: &str) -> Vec<Problem> {
+    match from_kdl_document(data, "sh") {
+        Ok(imported) => {
+            let (template, warnings) = imported.into_template("imported");
+            let mut problems: Vec<Problem> = warnings
+                .into_iter()
+                .map(|message| Problem {
+                    severity: Severity::Warning,
+                    tab: 0,
+                    node_id: None,
+                    message,
+                })
+                .collect();
+            problems.extend(template_problems(&template));
+            problems
+        }
+        Err(err) => vec![Problem {
+            severity: Severity::Error,
+            tab: 0,
+            node_id: None,
+            message: format!("{err:#}"),
+        }],
+    }
+}
+
+fn exit_code(problems: &[Problem]) -> i32 {
+    if problems.iter().any(|p| p.severity == Severity::Error) {
+        1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diagnose, diagnose_kdl, exit_code, resolve_target};
+
+    #[test]
+    fn valid_template_exits_zero() {
+        let data =
+            r#"{"name":"ok","layout":{"type":"bite","id":1,"name":"main","command":"bash"}}"#;
+        assert_eq!(exit_code(&diagnose(data)), 0);
+    }
+
+    #[test]
+    fn collects_all_errors_and_exits_one() {
+        let data = r#"{"name":"ok","layout":{"type":"spoon","id":1,"orientation":"vertical","ratio":0.5,
+            "first":{"type":"bite","id":1,"name":"","command":"bash"},
+            "second":{"type":"bite","id":3,"name":"b","command":""}}}"#;
+        let problems = diagnose(data);
+        assert_eq!(problems.len(), 3);
+        assert_eq!(exit_code(&problems), 1);
+    }
+
+    #[test]
+    fn problems_in_later_tabs_name_the_tab() {
+        let data = r#"{"name":"ok","layout":{"type":"bite","id":1,"name":"main","command":"bash"},
+            "tabs":[{"name":"logs","layout":{"type":"bite","id":1,"name":"tail","command":""}}]}"#;
+        let rendered: Vec<String> = diagnose(data).iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            rendered,
+            vec!["error: tab 1: node 1: bite command must not be empty"]
+        );
+    }
+
+    #[test]
+    fn broken_json_is_reported() {
+        let problems = diagnose("{");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(exit_code(&problems), 1);
+    }
+
+    #[test]
+    fn kdl_warnings_do_not_fail_validation() {
+        let problems = diagnose_kdl("layout {\n pane borderless=true\n}");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(exit_code(&problems), 0);
+        assert_eq!(exit_code(&diagnose_kdl("layout {")), 1);
+    }
+
+    #[test]
+    fn bare_name_resolves_to_templates_dir() {
+        assert!(resolve_target("work").ends_with("pudding/templates/work.json"));
+        assert_eq!(
+            resolve_target("./custom.json"),
+            std::path::PathBuf::from("./custom.json")
+        );
+    }
+}
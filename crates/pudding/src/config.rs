@@ -10,59 +10,208 @@ use std::{
 #[cfg(unix)]
 use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
 
-use crate::paths::config_dir;
+use crate::{paths::config_dir, theme::Theme};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub default_command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shell_args: Vec<String>,
     pub keybinds: HashMap<String, String>,
+    #[serde(default = "default_scrollback_lines")]
+    pub scrollback_lines: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pane_log_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed_pane_archive_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_quit_secs: Option<u64>,
+    #[serde(default)]
+    pub confirm_delete: ConfirmDelete,
+    #[serde(default)]
+    pub detach_on_quit: bool,
+    #[serde(default = "default_confirm_quit")]
+    pub confirm_quit: bool,
+    #[serde(default = "default_max_panes")]
+    pub max_panes: usize,
+    #[serde(default = "default_resize_step")]
+    pub resize_step: f32,
+    #[serde(default = "default_editor_cursor_symbol")]
+    pub editor_cursor_symbol: String,
+    #[serde(default = "default_editor_cursor_color")]
+    pub editor_cursor_color: String,
+    #[serde(default)]
+    pub theme: Theme,
+    #[serde(default = "default_show_clock")]
+    pub show_clock: bool,
+    #[serde(default = "default_tab_name_format")]
+    pub tab_name_format: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmDelete {
+    #[default]
+    Always,
+    Running,
+    Never,
+}
+
+impl ConfirmDelete {
+    pub fn needs_confirm(self, running: bool) -> bool {
+        match self {
+            ConfirmDelete::Always => true,
+            ConfirmDelete::Running => running,
+            ConfirmDelete::Never => false,
+        }
+    }
 }
 
 impl Config {
     pub fn load() -> Result<Self> {
         let path = config_file_path();
         if let Ok(data) = fs::read_to_string(&path) {
-            return serde_json::from_str::<Config>(&data)
-                .with_context(|| format!("invalid config file: {}", path.display()));
+            return parse_config(&path, &data);
         }
         let cfg = Config::default();
-        cfg.save()
+        cfg.save_to(&path)
             .with_context(|| format!("failed to write config: {}", path.display()))?;
         Ok(cfg)
     }
 
-    pub fn save(&self) -> std::io::Result<()> {
-        let path = config_file_path();
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file: {}", path.display()))?;
+        parse_config(path, &data)
+    }
+
+    pub fn save_to(&self, path: &Path) -> std::io::Result<()> {
         if let Some(parent) = path.parent() {
             ensure_dir_secure(parent)?;
         }
         let data = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
-        write_private_file(&path, &data)
+        write_private_file(path, &data)
     }
 }
 
 impl Default for Config {
     fn default() -> Self {
-        let mut keybinds = HashMap::new();
-        keybinds.insert("split_vertical".to_string(), "v".to_string());
-        keybinds.insert("split_horizontal".to_string(), "h".to_string());
-        keybinds.insert("resize_left".to_string(), "H".to_string());
-        keybinds.insert("resize_right".to_string(), "L".to_string());
-        keybinds.insert("resize_up".to_string(), "K".to_string());
-        keybinds.insert("resize_down".to_string(), "J".to_string());
-        keybinds.insert("swap_vertical".to_string(), "S".to_string());
-        keybinds.insert("swap_horizontal".to_string(), "s".to_string());
-        keybinds.insert("save_state".to_string(), "Ctrl+S".to_string());
-        keybinds.insert("restore_state".to_string(), "Ctrl+R".to_string());
-        keybinds.insert("focus_next".to_string(), "Tab".to_string());
-        keybinds.insert("quit".to_string(), "Ctrl+C".to_string());
         Config {
             default_command: "bash".to_string(),
-            keybinds,
+            shell_args: Vec::new(),
+            keybinds: default_keybinds(),
+            scrollback_lines: default_scrollback_lines(),
+            pane_log_dir: None,
+            closed_pane_archive_dir: None,
+            idle_quit_secs: None,
+            confirm_delete: ConfirmDelete::default(),
+            detach_on_quit: false,
+            confirm_quit: default_confirm_quit(),
+            max_panes: default_max_panes(),
+            resize_step: default_resize_step(),
+            editor_cursor_symbol: default_editor_cursor_symbol(),
+            editor_cursor_color: default_editor_cursor_color(),
+            theme: Theme::default(),
+            show_clock: default_show_clock(),
+            tab_name_format: default_tab_name_format(),
         }
     }
 }
 
+pub fn default_keybinds() -> HashMap<String, String> {
+    let mut keybinds = HashMap::new();
+    keybinds.insert("split_vertical".to_string(), "v".to_string());
+    keybinds.insert("split_horizontal".to_string(), "h".to_string());
+    keybinds.insert("split_grid".to_string(), "Ctrl+Alt+g".to_string());
+    keybinds.insert("delete_pane".to_string(), "d".to_string());
+    keybinds.insert("resize_left".to_string(), "H".to_string());
+    keybinds.insert("resize_right".to_string(), "L".to_string());
+    keybinds.insert("resize_up".to_string(), "K".to_string());
+    keybinds.insert("resize_down".to_string(), "J".to_string());
+    keybinds.insert("swap_vertical".to_string(), "S".to_string());
+    keybinds.insert("swap_horizontal".to_string(), "s".to_string());
+    keybinds.insert("move_pane_forward".to_string(), "Ctrl+Alt+n".to_string());
+    keybinds.insert("move_pane_backward".to_string(), "Ctrl+Alt+p".to_string());
+    keybinds.insert("move_pane_left".to_string(), "Ctrl+Alt+Left".to_string());
+    keybinds.insert("move_pane_right".to_string(), "Ctrl+Alt+Right".to_string());
+    keybinds.insert("move_pane_up".to_string(), "Ctrl+Alt+Up".to_string());
+    keybinds.insert("move_pane_down".to_string(), "Ctrl+Alt+Down".to_string());
+    keybinds.insert("balance_panes".to_string(), "Ctrl+Alt+b".to_string());
+    keybinds.insert("save_state".to_string(), "Ctrl+S".to_string());
+    keybinds.insert("save_as_template".to_string(), "Ctrl+Alt+t".to_string());
+    keybinds.insert("restore_state".to_string(), "Ctrl+R".to_string());
+    keybinds.insert("focus_next".to_string(), "Tab".to_string());
+    keybinds.insert("focus_left".to_string(), "Alt+Left".to_string());
+    keybinds.insert("focus_right".to_string(), "Alt+Right".to_string());
+    keybinds.insert("focus_up".to_string(), "Alt+Up".to_string());
+    keybinds.insert("focus_down".to_string(), "Alt+Down".to_string());
+    keybinds.insert("scroll_up".to_string(), "Shift+PageUp".to_string());
+    keybinds.insert("scroll_down".to_string(), "Shift+PageDown".to_string());
+    keybinds.insert("copy_output".to_string(), "Ctrl+Alt+y".to_string());
+    keybinds.insert("save_pane_output".to_string(), "Ctrl+Alt+s".to_string());
+    keybinds.insert("restart_pane".to_string(), "Ctrl+Alt+r".to_string());
+    keybinds.insert("edit_command".to_string(), "Ctrl+Alt+e".to_string());
+    keybinds.insert("reset_terminal".to_string(), "Ctrl+Alt+l".to_string());
+    keybinds.insert("toggle_wrap".to_string(), "Ctrl+Alt+w".to_string());
+    keybinds.insert("flip_tab".to_string(), "Ctrl+Alt+f".to_string());
+    keybinds.insert("open_below".to_string(), "Ctrl+Alt+o".to_string());
+    keybinds.insert("toggle_zoom".to_string(), "Ctrl+Alt+z".to_string());
+    keybinds.insert("next_tab".to_string(), "Ctrl+Alt+PageDown".to_string());
+    keybinds.insert("prev_tab".to_string(), "Ctrl+Alt+PageUp".to_string());
+    keybinds.insert("new_tab".to_string(), "Ctrl+Alt+c".to_string());
+    keybinds.insert("search".to_string(), "/".to_string());
+    keybinds.insert("help".to_string(), "?".to_string());
+    keybinds.insert("quit".to_string(), "Ctrl+C".to_string());
+    keybinds
+}
+
+fn parse_config(path: &Path, data: &str) -> Result<Config> {
+    let mut config = serde_json::from_str::<Config>(data)
+        .with_context(|| format!("invalid config file: {}", path.display()))?;
+    config.resize_step = sanitize_resize_step(config.resize_step);
+    Ok(config)
+}
+
+fn sanitize_resize_step(step: f32) -> f32 {
+    if step.is_finite() {
+        step.clamp(0.01, 0.5)
+    } else {
+        default_resize_step()
+    }
+}
+
+fn default_scrollback_lines() -> usize {
+    2000
+}
+
+fn default_confirm_quit() -> bool {
+    true
+}
+
+fn default_show_clock() -> bool {
+    true
+}
+
+fn default_tab_name_format() -> String {
+    "tab-{n}".to_string()
+}
+
+fn default_max_panes() -> usize {
+    32
+}
+
+fn default_resize_step() -> f32 {
+    0.20
+}
+
+fn default_editor_cursor_symbol() -> String {
+    "x".to_string()
+}
+
+fn default_editor_cursor_color() -> String {
+    "cyan".to_string()
+}
+
 pub fn config_file_path() -> PathBuf {
     config_dir().join("pudding").join("config.json")
 }
@@ -86,3 +235,68 @@ fn write_private_file(path: &Path, content: &str) -> io::Result<()> {
 
     file.write_all(content.as_bytes())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{parse_config, Config, ConfirmDelete};
+
+    #[test]
+    fn confirm_delete_depends_on_child_liveness() {
+        let cases = [
+            (ConfirmDelete::Always, [true, true]),
+            (ConfirmDelete::Running, [true, false]),
+            (ConfirmDelete::Never, [false, false]),
+        ];
+        for (mode, [running, exited]) in cases {
+            assert_eq!(mode.needs_confirm(true), running, "{mode:?}");
+            assert_eq!(mode.needs_confirm(false), exited, "{mode:?}");
+        }
+        let cfg = parse_config(
+            Path::new("c.json"),
+            r#"{"default_command":"sh","keybinds":{},"confirm_delete":"running"}"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.confirm_delete, ConfirmDelete::Running);
+    }
+
+    #[test]
+    fn old_config_without_new_fields_still_loads() {
+        let cfg: Config =
+            serde_json::from_str(r#"{"default_command": "zsh", "keybinds": {"quit": "Ctrl+C"}}"#)
+                .unwrap();
+        assert_eq!(cfg.scrollback_lines, 2000);
+        assert_eq!(cfg.pane_log_dir, None);
+        assert_eq!(cfg.idle_quit_secs, None);
+    }
+
+    #[test]
+    fn load_from_errors_on_missing_file_without_creating_it() {
+        let path = std::env::temp_dir()
+            .join(format!("pudding-config-{}", std::process::id()))
+            .join("config.json");
+        let err = Config::load_from(&path).unwrap_err();
+        assert!(err.to_string().contains("failed to read config file"));
+        assert!(!path.exists());
+        Config::default().save_to(&path).unwrap();
+        let loaded = Config::load_from(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(loaded.default_command, "bash");
+    }
+
+    #[test]
+    fn resize_step_is_clamped_on_load() {
+        let parse = |step: &str| {
+            let data =
+                format!(r#"{{"default_command": "sh", "keybinds": {{}}, "resize_step": {step}}}"#);
+            parse_config(Path::new("config.json"), &data)
+                .unwrap()
+                .resize_step
+        };
+        assert_eq!(parse("0.05"), 0.05);
+        assert_eq!(parse("0"), 0.01);
+        assert_eq!(parse("-1"), 0.01);
+        assert_eq!(parse("3"), 0.5);
+    }
+}
@@ -1,88 +1,119 @@
-use std::{io, time::Duration};
+use std::time::Duration;
 
 use anyhow::Result;
-use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
+use crossterm::event::{Event, KeyCode, KeyEvent};
 use ratatui::{
-    backend::CrosstermBackend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
-    Terminal,
+    widgets::{Block, Borders, Clear, Paragraph},
 };
 
 mod editor_area;
+mod editor_keys;
+mod editor_ratio;
 
 use self::editor_area::{centered_rect, main_area};
+use self::editor_keys::{editor_command, EditorCommand, EDITOR_KEYS};
+use self::editor_ratio::parse_ratio;
 
 use crate::{
-    layout::{find_bite_at, layout_rects, next_id, split_bite},
-    model::{Node, Orientation, Template},
-    template::save_template,
+    config::Config,
+    help::{draw_help, help_size},
+    history::{command_history_path, History},
+    layout::{
+        adjacent_bite, attach_beside_root, balance_spoons, delete_bite, duplicate_node,
+        find_bite_at, initial_focus, layout_rects, next_id, parent_ratio_mut, resize_from_bite,
+        set_primary, split_bite, take_bite, toggle_orientation, PaneDirection,
+    },
+    line_edit::LineEdit,
+    model::{Node, Orientation, Tab, Template},
+    template::{cwd_is_rooted, new_tab_name, save_template, template_path, validate_store_name},
+    terminal::{with_terminal, CrosstermTerminal, TerminalBackend},
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum InputKind {
     Name,
     Command,
+    Cwd,
+    SaveAs,
+    Ratio,
 }
 
 struct InputMode {
     kind: InputKind,
-    buffer: String,
+    buffer: LineEdit,
+    // Set once the user has been warned that `buffer` names an existing template.
+    overwrite: bool,
+}
+
+impl InputMode {
+    fn new(kind: InputKind) -> Self {
+        Self {
+            kind,
+            buffer: LineEdit::new(),
+            overwrite: false,
+        }
+    }
 }
 
 pub struct EditorApp {
     template: Template,
+    config: Config,
     cursor_x: u16,
     cursor_y: u16,
     selected_id: u64,
     input: Option<InputMode>,
+    history: History,
+    help: bool,
+    // Asked before quitting with unsaved edits; `y` quits, any other key stays.
+    confirm_quit: bool,
+    // Asked before `X` deletes the active tab; `y` deletes, any other key keeps it.
+    confirm_delete_tab: bool,
+    // Tab picked as the destination for `m`; arrows and Tab cycle it, Enter moves the pane.
+    move_target: Option<usize>,
     message: String,
+    dirty: bool,
 }
 
 impl EditorApp {
-    pub fn new(template: Template) -> Self {
-        let selected_id = template.layout.id();
+    pub fn new(template: Template, config: Config) -> Self {
+        let selected_id = template.active_layout().id();
         Self {
             template,
+            config,
             cursor_x: 1,
             cursor_y: 1,
             selected_id,
             input: None,
-            message: "v/hで分割、nで名前、cでコマンド、sで保存、qで終了".to_string(),
+            history: History::default(),
+            help: false,
+            confirm_quit: false,
+            confirm_delete_tab: false,
+            move_target: None,
+            message:
+                "v/hで分割、H/J/K/Lでリサイズ、nで名前、cでコマンド、sで保存、?でヘルプ、qで終了"
+                    .to_string(),
+            dirty: false,
         }
     }
 
     pub fn run(mut self) -> Result<Template> {
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen)?;
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
-
-        let res = self.loop_ui(&mut terminal);
-
-        disable_raw_mode()?;
-        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-        terminal.show_cursor()?;
+        self.history = History::load(command_history_path());
+        self.run_with(&mut CrosstermTerminal::new(false))
+    }
 
-        res
+    fn run_with<T: TerminalBackend>(mut self, terminal: &mut T) -> Result<Template> {
+        with_terminal(terminal, |terminal| self.loop_ui(terminal))
     }
 
-    fn loop_ui(
-        &mut self,
-        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-    ) -> Result<Template> {
+    fn loop_ui<T: TerminalBackend>(&mut self, terminal: &mut T) -> Result<Template> {
         loop {
             terminal.draw(|f| self.draw(f))?;
 
-            if event::poll(Duration::from_millis(50))? {
-                if let Event::Key(key) = event::read()? {
+            if terminal.poll(Duration::from_millis(50))? {
+                if let Event::Key(key) = terminal.read()? {
                     if self.handle_key(key, terminal.size()?)? {
                         break;
                     }
@@ -102,23 +133,16 @@ impl EditorApp {
         let status = chunks[1];
 
         let mut rects = Vec::new();
-        layout_rects(&self.template.layout, main, &mut rects);
+        layout_rects(self.template.active_layout(), main, &mut rects);
 
         // Draw panes
         for (id, rect) in rects.iter() {
-            let (title, highlight) = match crate::layout::find_bite(&self.template.layout, *id) {
-                Some(Node::Bite { name, .. }) => (name.clone(), *id == self.selected_id),
-                _ => ("".to_string(), *id == self.selected_id),
-            };
-            let style = if highlight {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            };
-            let block = Block::default()
-                .title(title)
-                .borders(Borders::ALL)
-                .border_style(style);
+            let (title, highlight) =
+                match crate::layout::find_bite(self.template.active_layout(), *id) {
+                    Some(Node::Bite { name, .. }) => (name.clone(), *id == self.selected_id),
+                    _ => ("".to_string(), *id == self.selected_id),
+                };
+            let block = self.config.theme.pane_block(title, highlight);
             f.render_widget(block, *rect);
         }
 
@@ -127,16 +151,33 @@ impl EditorApp {
             let x = self.cursor_x.min(main.x + main.width - 1);
             let y = self.cursor_y.min(main.y + main.height - 1);
             if x >= main.x && y >= main.y {
+                let (symbol, color) = self.cursor_look();
                 let cell = f.buffer_mut().get_mut(x, y);
-                cell.set_symbol("x");
-                cell.set_style(Style::default().fg(Color::Cyan));
+                cell.set_symbol(symbol);
+                cell.set_style(Style::default().fg(color));
             }
         }
 
-        let status_text = Line::from(vec![
-            Span::raw("[Editor] "),
-            Span::raw(self.message.clone()),
-        ]);
+        let mut status_text = vec![Span::raw(if self.dirty {
+            "[Editor *] "
+        } else {
+            "[Editor] "
+        })];
+        let tabs = self.template.tab_count();
+        if tabs > 1 {
+            let active = self.template.active_tab;
+            status_text.push(Span::styled(
+                format!(
+                    "[tab {}/{} {}] ",
+                    active + 1,
+                    tabs,
+                    self.template.tab_name(active)
+                ),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        status_text.push(Span::raw(self.message.clone()));
+        let status_text = Line::from(status_text);
         let status_widget = Paragraph::new(status_text);
         f.render_widget(status_widget, status);
 
@@ -144,8 +185,11 @@ impl EditorApp {
             let prompt = match input.kind {
                 InputKind::Name => "名前を入力: ",
                 InputKind::Command => "コマンドを入力: ",
+                InputKind::Cwd => "作業ディレクトリ（空で解除）: ",
+                InputKind::SaveAs => "保存名を入力: ",
+                InputKind::Ratio => "比率 (0.25 / 25% / 1/3): ",
             };
-            let line = Line::from(vec![Span::raw(prompt), Span::raw(&input.buffer)]);
+            let line = Line::from(vec![Span::raw(prompt), Span::raw(input.buffer.as_str())]);
             let block = Block::default().borders(Borders::ALL).title("Input");
             let area = centered_rect(80, 3, area);
             f.render_widget(block, area);
@@ -159,13 +203,101 @@ impl EditorApp {
                 },
             );
             f.set_cursor(
-                area.x + 1 + prompt.len() as u16 + input.buffer.len() as u16,
+                area.x + 1 + Span::raw(prompt).width() as u16 + input.buffer.cursor_width(),
                 area.y + 1,
             );
         }
+
+        if self.confirm_quit {
+            let area = centered_rect(40, 3, area);
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Paragraph::new("保存せずに終了しますか？ (y/n)")
+                    .block(Block::default().borders(Borders::ALL).title("Confirm")),
+                area,
+            );
+        }
+
+        if self.confirm_delete_tab {
+            let prompt = format!(
+                "タブ「{}」を削除しますか？ (y/n)",
+                self.template.tab_name(self.template.active_tab)
+            );
+            let area = centered_rect(50, 3, area);
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Paragraph::new(prompt)
+                    .block(Block::default().borders(Borders::ALL).title("Confirm")),
+                area,
+            );
+        }
+
+        if let Some(target) = self.move_target {
+            let prompt = format!(
+                "移動先のタブ: {} ({}/{})  ←→で選択、Enterで移動、Escで取消",
+                self.template.tab_name(target),
+                target + 1,
+                self.template.tab_count()
+            );
+            let area = centered_rect(60, 3, area);
+            f.render_widget(Clear, area);
+            f.render_widget(
+                Paragraph::new(prompt)
+                    .block(Block::default().borders(Borders::ALL).title("Move pane")),
+                area,
+            );
+        }
+
+        if self.help {
+            let lines = Self::help_lines();
+            let (width, height) = help_size(&lines, area);
+            draw_help(f, centered_rect(width, height, area), &lines);
+        }
+    }
+
+    fn help_lines() -> Vec<String> {
+        EDITOR_KEYS
+            .iter()
+            .map(|key| format!("{:<14} {}", key.label, key.description))
+            .collect()
+    }
+
+    // Falls back to the cyan `x` when the configured symbol is empty or the color unknown.
+    fn cursor_look(&self) -> (&str, Color) {
+        let symbol = match self.config.editor_cursor_symbol.as_str() {
+            "" => "x",
+            symbol => symbol,
+        };
+        let color = self
+            .config
+            .editor_cursor_color
+            .parse::<Color>()
+            .unwrap_or(Color::Cyan);
+        (symbol, color)
     }
 
     fn handle_key(&mut self, key: KeyEvent, area: Rect) -> Result<bool> {
+        if self.help {
+            self.help = false;
+            return Ok(false);
+        }
+        if self.confirm_quit {
+            self.confirm_quit = false;
+            return Ok(matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')));
+        }
+        if self.confirm_delete_tab {
+            self.confirm_delete_tab = false;
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.delete_active_tab();
+            } else {
+                self.message = "タブの削除を取り消しました".to_string();
+            }
+            return Ok(false);
+        }
+        if let Some(target) = self.move_target.take() {
+            self.handle_move_key(target, key, main_area(area));
+            return Ok(false);
+        }
         if let Some(mut input) = self.input.take() {
             let close = self.handle_input_key(&mut input, key);
             if !close {
@@ -175,98 +307,238 @@ impl EditorApp {
         }
 
         let main = main_area(area);
-        match key.code {
-            KeyCode::Char('q') => return Ok(true),
-            KeyCode::Char('v') => self.split_at_cursor(main, Orientation::Vertical),
-            KeyCode::Char('h') => self.split_at_cursor(main, Orientation::Horizontal),
-            KeyCode::Char('n') => {
-                self.input = Some(InputMode {
-                    kind: InputKind::Name,
-                    buffer: String::new(),
-                });
+        match editor_command(key) {
+            Some(EditorCommand::Quit) => return Ok(self.request_quit()),
+            Some(command) => self.run_command(command, main),
+            None => {}
+        }
+
+        if let Some(id) = find_bite_at(
+            self.template.active_layout(),
+            main,
+            self.cursor_x,
+            self.cursor_y,
+        ) {
+            self.selected_id = id;
+        }
+        Ok(false)
+    }
+
+    fn run_command(&mut self, command: EditorCommand, main: Rect) {
+        match command {
+            EditorCommand::Cursor(direction) => self.move_cursor(main, direction),
+            EditorCommand::Select(direction) => self.select_toward(main, direction),
+            EditorCommand::Split(orientation) => self.split_at_cursor(main, orientation),
+            EditorCommand::Duplicate => self.duplicate_selected(),
+            EditorCommand::Delete => self.delete_selected(main),
+            EditorCommand::Resize(orientation, direction) => {
+                self.resize_selected(orientation, direction)
             }
-            KeyCode::Char('c') => {
-                self.input = Some(InputMode {
-                    kind: InputKind::Command,
-                    buffer: String::new(),
-                });
+            EditorCommand::EditRatio => self.open_ratio_input(),
+            EditorCommand::Balance => {
+                balance_spoons(self.template.active_layout_mut());
+                self.dirty = true;
+                self.message = "分割比率を均等にしました".to_string();
             }
-            KeyCode::Char('s') => match save_template(&self.template) {
-                Ok(_) => self.message = "テンプレートを保存しました".to_string(),
-                Err(_) => self.message = "保存に失敗しました".to_string(),
-            },
-            KeyCode::Left => {
-                if self.cursor_x > main.x {
-                    self.cursor_x -= 1;
+            EditorCommand::ToggleOrientation => {
+                if toggle_orientation(self.template.active_layout_mut(), self.selected_id) {
+                    self.dirty = true;
+                    self.message = "分割方向を切り替えました".to_string();
+                } else {
+                    self.message = "分割されていないペインです".to_string();
                 }
             }
-            KeyCode::Right => {
-                if self.cursor_x + 1 < main.x + main.width {
-                    self.cursor_x += 1;
-                }
+            EditorCommand::EditName => self.input = Some(InputMode::new(InputKind::Name)),
+            EditorCommand::EditCommand => self.input = Some(InputMode::new(InputKind::Command)),
+            EditorCommand::EditCwd => self.open_cwd_input(),
+            EditorCommand::SetPrimary => {
+                set_primary(self.template.active_layout_mut(), self.selected_id);
+                self.dirty = true;
+                self.message = "起動時のフォーカスに設定しました".to_string();
             }
-            KeyCode::Up => {
-                if self.cursor_y > main.y {
-                    self.cursor_y -= 1;
+            EditorCommand::SwitchTab(forward) => self.switch_tab(forward),
+            EditorCommand::AddTab => self.add_tab(),
+            EditorCommand::DeleteTab => {
+                if self.template.tab_count() < 2 {
+                    self.message = "最後のタブは削除できません".to_string();
+                } else {
+                    self.confirm_delete_tab = true;
                 }
             }
-            KeyCode::Down => {
-                if self.cursor_y + 1 < main.y + main.height {
-                    self.cursor_y += 1;
+            EditorCommand::MoveTab(forward) => self.move_tab(forward),
+            EditorCommand::MovePaneToTab => {
+                if self.template.tab_count() < 2 {
+                    self.message = "移動先のタブがありません".to_string();
+                } else {
+                    self.move_target =
+                        Some((self.template.active_tab + 1) % self.template.tab_count());
                 }
             }
-            _ => {}
+            EditorCommand::Save => match save_template(&self.template) {
+                Ok(_) => {
+                    self.dirty = false;
+                    self.message = "テンプレートを保存しました".to_string();
+                }
+                Err(_) => self.message = "保存に失敗しました".to_string(),
+            },
+            EditorCommand::SaveAs => self.input = Some(InputMode::new(InputKind::SaveAs)),
+            EditorCommand::Help => self.help = true,
+            EditorCommand::Quit => {}
         }
+    }
 
-        if let Some(id) = find_bite_at(&self.template.layout, main, self.cursor_x, self.cursor_y) {
-            self.selected_id = id;
+    // Stops at the edges of the layout area.
+    fn move_cursor(&mut self, main: Rect, direction: PaneDirection) {
+        match direction {
+            PaneDirection::Left if self.cursor_x > main.x => self.cursor_x -= 1,
+            PaneDirection::Right if self.cursor_x + 1 < main.x + main.width => self.cursor_x += 1,
+            PaneDirection::Up if self.cursor_y > main.y => self.cursor_y -= 1,
+            PaneDirection::Down if self.cursor_y + 1 < main.y + main.height => self.cursor_y += 1,
+            _ => {}
         }
-        Ok(false)
     }
 
     fn handle_input_key(&mut self, input: &mut InputMode, key: KeyEvent) -> bool {
         match key.code {
             KeyCode::Enter => {
-                self.apply_input(input);
-                return true;
+                return self.apply_input(input);
             }
-            KeyCode::Esc => {
-                return true;
+            KeyCode::Up if input.kind == InputKind::Command => {
+                if let Some(entry) = self.history.older(input.buffer.as_str()) {
+                    input.buffer = LineEdit::with_text(entry);
+                }
             }
-            KeyCode::Backspace => {
-                input.buffer.pop();
+            KeyCode::Down if input.kind == InputKind::Command => {
+                if let Some(entry) = self.history.newer() {
+                    input.buffer = LineEdit::with_text(entry);
+                }
             }
-            KeyCode::Char(c) => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    return false;
+            // While recalling history, Esc first brings back what was being typed.
+            KeyCode::Esc => match self.history.restore() {
+                Some(draft) => input.buffer = LineEdit::with_text(draft),
+                None => return true,
+            },
+            _ => {
+                if input.buffer.handle_key(key) {
+                    input.overwrite = false;
                 }
-                input.buffer.push(c);
             }
-            _ => {}
         }
         false
     }
 
-    fn apply_input(&mut self, input: &InputMode) {
+    // Returns whether the input modal should close.
+    fn apply_input(&mut self, input: &mut InputMode) -> bool {
         match input.kind {
             InputKind::Name => {
-                if let Some(Node::Bite { name, .. }) =
-                    crate::layout::find_bite_mut(&mut self.template.layout, self.selected_id)
-                {
+                // A blank entry leaves the name alone; whitespace alone would fail validation.
+                if !input.buffer.is_empty() && input.buffer.as_str().trim().is_empty() {
+                    self.message = "空白だけの名前は使えません".to_string();
+                    return false;
+                }
+                if let Some(Node::Bite { name, .. }) = crate::layout::find_bite_mut(
+                    self.template.active_layout_mut(),
+                    self.selected_id,
+                ) {
                     if !input.buffer.is_empty() {
-                        *name = input.buffer.clone();
+                        *name = input.buffer.as_str().to_string();
+                        self.dirty = true;
                     }
                 }
             }
             InputKind::Command => {
-                if let Some(Node::Bite { command, .. }) =
-                    crate::layout::find_bite_mut(&mut self.template.layout, self.selected_id)
-                {
+                self.history.record(input.buffer.as_str());
+                if let Some(Node::Bite { command, .. }) = crate::layout::find_bite_mut(
+                    self.template.active_layout_mut(),
+                    self.selected_id,
+                ) {
                     if !input.buffer.is_empty() {
-                        *command = input.buffer.clone();
+                        *command = input.buffer.as_str().to_string();
+                        self.dirty = true;
                     }
                 }
             }
+            InputKind::Cwd => {
+                let text = input.buffer.as_str().trim();
+                if !text.is_empty() && !cwd_is_rooted(text) {
+                    self.message = "絶対パスか ~/ で始まるパスを入力してください".to_string();
+                    return false;
+                }
+                if let Some(Node::Bite { cwd, .. }) = crate::layout::find_bite_mut(
+                    self.template.active_layout_mut(),
+                    self.selected_id,
+                ) {
+                    *cwd = (!text.is_empty()).then(|| text.to_string());
+                    self.dirty = true;
+                }
+            }
+            InputKind::SaveAs => return self.save_as(input),
+            InputKind::Ratio => {
+                let ratio = match parse_ratio(input.buffer.as_str()) {
+                    Ok(ratio) => ratio,
+                    Err(err) => {
+                        self.message = err;
+                        return false;
+                    }
+                };
+                if let Some(slot) =
+                    parent_ratio_mut(self.template.active_layout_mut(), self.selected_id)
+                {
+                    *slot = ratio;
+                    self.dirty = true;
+                    self.message = format!("比率を {ratio:.2} にしました");
+                }
+            }
+        }
+        true
+    }
+
+    fn open_cwd_input(&mut self) {
+        let mut input = InputMode::new(InputKind::Cwd);
+        if let Some(Node::Bite { cwd: Some(cwd), .. }) =
+            crate::layout::find_bite(self.template.active_layout(), self.selected_id)
+        {
+            input.buffer = LineEdit::with_text(cwd.clone());
+        }
+        self.input = Some(input);
+    }
+
+    fn open_ratio_input(&mut self) {
+        let Some(ratio) = parent_ratio_mut(self.template.active_layout_mut(), self.selected_id)
+        else {
+            self.message = "分割されていないペインです".to_string();
+            return;
+        };
+        let mut input = InputMode::new(InputKind::Ratio);
+        input.buffer = LineEdit::with_text(format!("{ratio:.2}"));
+        self.input = Some(input);
+    }
+
+    // Invalid names and the first attempt at an existing name keep the modal open.
+    fn save_as(&mut self, input: &mut InputMode) -> bool {
+        let name = input.buffer.as_str().to_string();
+        if let Err(err) = validate_store_name(&name) {
+            self.message = format!("保存名が不正です: {err}");
+            return false;
+        }
+        if name != self.template.name && !input.overwrite && template_path(&name).exists() {
+            input.overwrite = true;
+            self.message = format!("{name} は既にあります。もう一度 Enter で上書き");
+            return false;
+        }
+        let mut template = self.template.clone();
+        template.name = name.clone();
+        match save_template(&template) {
+            Ok(_) => {
+                self.template.name = name.clone();
+                self.dirty = false;
+                self.message = format!("{name} として保存しました");
+                true
+            }
+            Err(err) => {
+                self.message = format!("保存に失敗しました: {err}");
+                false
+            }
         }
     }
 
@@ -274,7 +546,7 @@ impl EditorApp {
         let target_id = self.selected_id;
         let rects = {
             let mut rects = Vec::new();
-            layout_rects(&self.template.layout, main, &mut rects);
+            layout_rects(self.template.active_layout(), main, &mut rects);
             rects
         };
         let rect = rects
@@ -300,25 +572,804 @@ impl EditorApp {
                     }
                 }
             };
-            let new_id = next_id(&self.template.layout);
-            let default_command = match crate::config::Config::load() {
-                Ok(cfg) => cfg.default_command,
-                Err(err) => {
-                    self.message = format!("設定読込に失敗: {err}");
-                    return;
-                }
-            };
+            let new_id = next_id(self.template.active_layout());
             let did = split_bite(
-                &mut self.template.layout,
+                self.template.active_layout_mut(),
                 target_id,
                 orientation,
                 ratio,
                 new_id,
-                &default_command,
+                &self.config.default_command,
             );
             if did {
+                self.dirty = true;
                 self.message = "分割しました".to_string();
             }
         }
     }
+
+    // Wraps around like the runtime's next/prev tab. The tab shown when saving becomes
+    // the template's `active_tab`.
+    fn switch_tab(&mut self, forward: bool) {
+        let count = self.template.tab_count();
+        if count < 2 {
+            self.message = "タブは1つだけです".to_string();
+            return;
+        }
+        let current = self.template.active_tab;
+        let next = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+        self.select_tab(next);
+        self.message = format!(
+            "タブ {}/{}: {}",
+            next + 1,
+            count,
+            self.template.tab_name(next)
+        );
+    }
+
+    fn select_tab(&mut self, index: usize) {
+        self.template.active_tab = index;
+        self.selected_id = initial_focus(self.template.active_layout());
+    }
+
+    // The new tab goes right after the active one and starts as a single default pane.
+    fn add_tab(&mut self) {
+        let name = match new_tab_name(&self.template, &self.config.tab_name_format) {
+            Ok(name) => name,
+            Err(err) => {
+                self.message = format!("タブを追加できません: {err}");
+                return;
+            }
+        };
+        let index = self.template.active_tab;
+        self.template.tabs.insert(
+            index,
+            Tab {
+                name: name.clone(),
+                layout: Node::default_bite(1, "main", &self.config.default_command),
+            },
+        );
+        self.select_tab(index + 1);
+        self.dirty = true;
+        self.message = format!("タブを追加しました: {name}");
+    }
+
+    // Stops at the ends rather than wrapping. The first tab is the template's own `layout`,
+    // so it stays first and the others move around it.
+    fn move_tab(&mut self, forward: bool) {
+        let index = self.template.active_tab;
+        if index == 0 {
+            self.message = "1つ目のタブは移動できません".to_string();
+            return;
+        }
+        let target = if forward { index + 1 } else { index - 1 };
+        if target == 0 || target > self.template.tabs.len() {
+            self.message = "これ以上移動できません".to_string();
+            return;
+        }
+        self.template.tabs.swap(index - 1, target - 1);
+        self.template.active_tab = target;
+        self.dirty = true;
+        self.message = format!(
+            "タブを移動しました: {} ({}/{})",
+            self.template.tab_name(target),
+            target + 1,
+            self.template.tab_count()
+        );
+    }
+
+    // The next tab takes the deleted one's place, or the previous one at the end. Deleting
+    // the first tab promotes the second to `layout`, where it is named after the template.
+    fn delete_active_tab(&mut self) {
+        let index = self.template.active_tab;
+        let name = self.template.tab_name(index).to_string();
+        match index {
+            0 if !self.template.tabs.is_empty() => {
+                self.template.layout = self.template.tabs.remove(0).layout;
+            }
+            n if n >= 1 && n <= self.template.tabs.len() => {
+                self.template.tabs.remove(n - 1);
+            }
+            _ => return,
+        }
+        self.select_tab(index.min(self.template.tab_count() - 1));
+        self.dirty = true;
+        self.message = format!("タブを削除しました: {name}");
+    }
+
+    fn handle_move_key(&mut self, target: usize, key: KeyEvent, main: Rect) {
+        let count = self.template.tab_count();
+        match key.code {
+            KeyCode::Enter => self.move_selected_to_tab(target, main),
+            KeyCode::Esc => self.message = "ペインの移動を取り消しました".to_string(),
+            KeyCode::Right | KeyCode::Tab => self.move_target = Some((target + 1) % count),
+            KeyCode::Left | KeyCode::BackTab => {
+                self.move_target = Some((target + count - 1) % count)
+            }
+            _ => self.move_target = Some(target),
+        }
+    }
+
+    // The pane keeps its command but gets a fresh id in the target tab; the editor stays put.
+    fn move_selected_to_tab(&mut self, target: usize, main: Rect) {
+        let tab = self.template.tab_name(target).to_string();
+        if target == self.template.active_tab {
+            self.message = format!("ペインは既にタブ「{tab}」にあります");
+            return;
+        }
+        let (bite, focus) = match take_bite(self.template.active_layout_mut(), self.selected_id) {
+            Ok(taken) => taken,
+            Err(_) => {
+                self.message = "最後のペインは移動できません".to_string();
+                return;
+            }
+        };
+        let Node::Bite { name, .. } = &bite else {
+            return;
+        };
+        self.message = format!("ペイン「{name}」をタブ「{tab}」へ移動しました");
+        if let Some(layout) = self.template.tab_layout_mut(target) {
+            attach_beside_root(layout, bite);
+        }
+        self.selected_id = find_bite_at(
+            self.template.active_layout(),
+            main,
+            self.cursor_x,
+            self.cursor_y,
+        )
+        .unwrap_or(focus);
+        self.dirty = true;
+    }
+
+    fn request_quit(&mut self) -> bool {
+        if self.dirty {
+            self.confirm_quit = true;
+            return false;
+        }
+        true
+    }
+
+    // The pane under the cursor after the collapse wins; the survivor's first bite is the fallback.
+    fn delete_selected(&mut self, main: Rect) {
+        match delete_bite(self.template.active_layout_mut(), self.selected_id) {
+            Ok(focus) => {
+                self.selected_id = find_bite_at(
+                    self.template.active_layout(),
+                    main,
+                    self.cursor_x,
+                    self.cursor_y,
+                )
+                .unwrap_or(focus);
+                self.dirty = true;
+                self.message = "削除しました".to_string();
+            }
+            Err(_) => self.message = "最後のペインは削除できません".to_string(),
+        }
+    }
+
+    // The copy goes to the right of the selection, which stays on the original.
+    fn duplicate_selected(&mut self) {
+        if duplicate_node(
+            self.template.active_layout_mut(),
+            self.selected_id,
+            Orientation::Vertical,
+        )
+        .is_some()
+        {
+            self.dirty = true;
+            self.message = "複製しました".to_string();
+        }
+    }
+
+    // Jumps the cursor to the center of the adjacent pane so the selection follows it.
+    fn select_toward(&mut self, main: Rect, direction: PaneDirection) {
+        let Some(id) = adjacent_bite(
+            self.template.active_layout(),
+            main,
+            self.selected_id,
+            direction,
+        ) else {
+            self.message = "その方向にペインはありません".to_string();
+            return;
+        };
+        let mut rects = Vec::new();
+        layout_rects(self.template.active_layout(), main, &mut rects);
+        if let Some((_, rect)) = rects.into_iter().find(|(rect_id, _)| *rect_id == id) {
+            self.cursor_x = rect.x + rect.width / 2;
+            self.cursor_y = rect.y + rect.height / 2;
+            self.selected_id = id;
+        }
+    }
+
+    fn resize_selected(&mut self, orientation: Orientation, direction: f32) {
+        let delta = direction * self.config.resize_step;
+        if resize_from_bite(
+            self.template.active_layout_mut(),
+            self.selected_id,
+            orientation,
+            delta,
+        ) {
+            self.dirty = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{backend::TestBackend, layout::Rect, style::Color, Terminal};
+
+    use super::{EditorApp, InputKind, InputMode};
+    use crate::config::Config;
+    use crate::line_edit::LineEdit;
+    use crate::model::{default_template, Node, Orientation, Tab, Template};
+    use crate::terminal::ScriptedTerminal;
+
+    #[test]
+    fn scripted_keys_split_and_rename_through_the_loop() {
+        let key = |code| ScriptedTerminal::key(code, KeyModifiers::NONE);
+        let mut events = vec![key(KeyCode::Right); 9];
+        events.push(key(KeyCode::Char('v')));
+        events.push(key(KeyCode::Left));
+        events.push(key(KeyCode::Char('n')));
+        events.extend("left".chars().map(|c| key(KeyCode::Char(c))));
+        events.push(key(KeyCode::Enter));
+        events.push(key(KeyCode::Char('q')));
+        events.push(key(KeyCode::Char('y')));
+        let mut terminal = ScriptedTerminal::new(40, 12, events);
+
+        let app = EditorApp::new(default_template(), Config::default());
+        let template = app.run_with(&mut terminal).unwrap();
+
+        let Node::Spoon {
+            orientation,
+            ratio,
+            first,
+            ..
+        } = &template.layout
+        else {
+            panic!("expected a split layout");
+        };
+        assert_eq!(*orientation, Orientation::Vertical);
+        assert!((ratio - 0.25).abs() < 0.01);
+        assert!(matches!(first.as_ref(), Node::Bite { name, .. } if name == "left"));
+    }
+
+    #[test]
+    fn rename_rejects_whitespace_only_names() {
+        let mut app = EditorApp::new(default_template(), Config::default());
+        let mut input = InputMode::new(InputKind::Name);
+        input.buffer = LineEdit::with_text("  ");
+        assert!(!app.apply_input(&mut input));
+        assert_eq!(app.message, "空白だけの名前は使えません");
+        assert!(!app.dirty);
+        let Node::Bite { name, .. } = &default_template().layout else {
+            panic!("expected a single bite");
+        };
+        assert!(matches!(&app.template.layout, Node::Bite { name: kept, .. } if kept == name));
+    }
+
+    #[test]
+    fn cwd_input_accepts_rooted_paths_and_clears_on_empty() {
+        let mut app = EditorApp::new(default_template(), Config::default());
+        let cwd_of = |app: &EditorApp| match &app.template.layout {
+            Node::Bite { cwd, .. } => cwd.clone(),
+            Node::Spoon { .. } => panic!("expected a single bite"),
+        };
+        let mut input = InputMode::new(InputKind::Cwd);
+        input.buffer = LineEdit::with_text("src");
+        assert!(!app.apply_input(&mut input));
+        assert_eq!(cwd_of(&app), None);
+
+        input.buffer = LineEdit::with_text("~/work");
+        assert!(app.apply_input(&mut input));
+        assert_eq!(cwd_of(&app).as_deref(), Some("~/work"));
+
+        app.handle_key(
+            KeyEvent::new(KeyCode::Char('w'), KeyModifiers::NONE),
+            Rect::new(0, 0, 40, 12),
+        )
+        .unwrap();
+        assert_eq!(
+            app.input.as_ref().map(|input| input.buffer.as_str()),
+            Some("~/work")
+        );
+
+        input.buffer = LineEdit::new();
+        assert!(app.apply_input(&mut input));
+        assert_eq!(cwd_of(&app), None);
+    }
+
+    #[test]
+    fn cursor_cell_uses_configured_symbol_and_color() {
+        let config = Config {
+            editor_cursor_symbol: "█".to_string(),
+            editor_cursor_color: "#ff8800".to_string(),
+            ..Config::default()
+        };
+        let mut app = EditorApp::new(default_template(), config);
+        let mut terminal = Terminal::new(TestBackend::new(20, 8)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let cell = terminal.backend().buffer().get(1, 1).clone();
+        assert_eq!(cell.symbol(), "█");
+        assert_eq!(cell.fg, Color::Rgb(0xff, 0x88, 0x00));
+
+        app.config.editor_cursor_color = "no-such-color".to_string();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        assert_eq!(terminal.backend().buffer().get(1, 1).fg, Color::Cyan);
+    }
+
+    #[test]
+    fn shift_keys_resize_the_selected_split() {
+        let template = Template {
+            name: "t".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.5,
+                first: Box::new(Node::bite(2, "left", "sh")),
+                second: Box::new(Node::bite(3, "right", "sh")),
+            },
+        };
+        let config = Config {
+            resize_step: 0.1,
+            ..Config::default()
+        };
+        let mut app = EditorApp::new(template, config);
+        app.selected_id = 2;
+        let area = Rect::new(0, 0, 40, 12);
+        for c in ['L', 'J'] {
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT);
+            app.handle_key(key, area).unwrap();
+        }
+        assert!(matches!(
+            app.template.layout,
+            Node::Spoon { ratio, .. } if (ratio - 0.6).abs() < 1e-6
+        ));
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn save_as_keeps_modal_open_on_invalid_name() {
+        let mut app = EditorApp::new(default_template(), Config::default());
+        let area = Rect::new(0, 0, 40, 12);
+        for c in "S bad name".chars() {
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+            app.handle_key(key, area).unwrap();
+        }
+        app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), area)
+            .unwrap();
+
+        assert!(app.input.is_some());
+        assert!(app.message.starts_with("保存名が不正です"));
+        assert_eq!(app.template.name, default_template().name);
+    }
+
+    #[test]
+    fn shift_arrows_jump_to_the_adjacent_pane() {
+        let template = Template {
+            name: "t".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.5,
+                first: Box::new(Node::bite(2, "left", "sh")),
+                second: Box::new(Node::bite(3, "right", "sh")),
+            },
+        };
+        let mut app = EditorApp::new(template, Config::default());
+        let area = Rect::new(0, 0, 40, 12);
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE), area)
+            .unwrap();
+        assert_eq!(app.selected_id, 2);
+
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT), area)
+            .unwrap();
+        assert_eq!(app.selected_id, 3);
+        assert!(app.cursor_x >= 20);
+
+        app.handle_key(KeyEvent::new(KeyCode::Right, KeyModifiers::SHIFT), area)
+            .unwrap();
+        assert_eq!(app.selected_id, 3);
+        assert_eq!(app.message, "その方向にペインはありません");
+    }
+
+    #[test]
+    fn ratio_modal_is_prefilled_and_rejects_out_of_range_values() {
+        let template = Template {
+            name: "t".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.5,
+                first: Box::new(Node::bite(2, "left", "sh")),
+                second: Box::new(Node::bite(3, "right", "sh")),
+            },
+        };
+        let mut app = EditorApp::new(template, Config::default());
+        app.selected_id = 3;
+        let area = Rect::new(0, 0, 40, 12);
+        let press = |app: &mut EditorApp, code| {
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), area)
+                .unwrap();
+        };
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(app.input.as_ref().unwrap().buffer.as_str(), "0.50");
+        for _ in 0..4 {
+            press(&mut app, KeyCode::Backspace);
+        }
+        for c in "5%".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert!(app.input.is_some());
+        assert!(!app.dirty);
+
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Backspace);
+        for c in "1/3".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert!(app.input.is_none());
+        assert!(matches!(
+            app.template.layout,
+            Node::Spoon { ratio, .. } if (ratio - 1.0 / 3.0).abs() < 1e-6
+        ));
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn command_modal_recalls_history_and_esc_restores_the_draft() {
+        let mut app = EditorApp::new(default_template(), Config::default());
+        let area = Rect::new(0, 0, 40, 12);
+        let press = |app: &mut EditorApp, code| {
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), area)
+                .unwrap();
+        };
+        let type_str = |app: &mut EditorApp, text: &str| {
+            for c in text.chars() {
+                press(app, KeyCode::Char(c));
+            }
+        };
+        press(&mut app, KeyCode::Char('c'));
+        type_str(&mut app, "htop");
+        press(&mut app, KeyCode::Enter);
+
+        press(&mut app, KeyCode::Char('c'));
+        type_str(&mut app, "ls");
+        press(&mut app, KeyCode::Up);
+        assert_eq!(app.input.as_ref().unwrap().buffer.as_str(), "htop");
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.input.as_ref().unwrap().buffer.as_str(), "ls");
+        press(&mut app, KeyCode::Esc);
+        assert!(app.input.is_none());
+    }
+
+    #[test]
+    fn ctrl_d_duplicates_the_selected_pane_side_by_side() {
+        let template = Template {
+            name: "t".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::bite(1, "logs", "tail"),
+        };
+        let mut app = EditorApp::new(template, Config::default());
+        let ctrl_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        app.handle_key(ctrl_d, Rect::new(0, 0, 40, 12)).unwrap();
+
+        let Node::Spoon {
+            orientation,
+            first,
+            second,
+            ..
+        } = &app.template.layout
+        else {
+            panic!("expected a split layout");
+        };
+        assert_eq!(*orientation, Orientation::Vertical);
+        assert!(matches!(**first, Node::Bite { id: 1, .. }));
+        assert!(matches!(
+            &**second,
+            Node::Bite { id: 3, name, command, .. } if name == "logs" && command == "tail"
+        ));
+        assert_eq!(app.selected_id, 1);
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn ctrl_c_quits_like_q_and_confirms_unsaved_edits() {
+        let area = Rect::new(0, 0, 40, 12);
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        let mut clean = EditorApp::new(default_template(), Config::default());
+        assert!(clean.handle_key(ctrl_c, area).unwrap());
+        assert!(clean.input.is_none());
+
+        let mut app = EditorApp::new(default_template(), Config::default());
+        app.dirty = true;
+        assert!(!app.handle_key(ctrl_c, area).unwrap());
+        assert!(app.confirm_quit);
+        assert!(!app.handle_key(key('n'), area).unwrap());
+        assert!(!app.confirm_quit);
+        assert!(!app.handle_key(key('q'), area).unwrap());
+        assert!(app.handle_key(key('y'), area).unwrap());
+    }
+
+    #[test]
+    fn d_deletes_the_selection_but_keeps_the_last_pane() {
+        let template = Template {
+            name: "t".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.5,
+                first: Box::new(Node::bite(2, "left", "sh")),
+                second: Box::new(Node::bite(3, "right", "sh")),
+            },
+        };
+        let mut app = EditorApp::new(template, Config::default());
+        let area = Rect::new(0, 0, 40, 12);
+        app.cursor_x = 30;
+        app.cursor_y = 3;
+        app.selected_id = 3;
+        let d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE);
+
+        app.handle_key(d, area).unwrap();
+        assert!(matches!(app.template.layout, Node::Bite { id: 2, .. }));
+        assert_eq!(app.selected_id, 2);
+        assert!(app.dirty);
+
+        app.handle_key(d, area).unwrap();
+        assert!(matches!(app.template.layout, Node::Bite { id: 2, .. }));
+        assert_eq!(app.message, "最後のペインは削除できません");
+    }
+
+    #[test]
+    fn equals_key_gives_every_column_the_same_width() {
+        let template = Template {
+            name: "t".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.8,
+                first: Box::new(Node::bite(2, "a", "sh")),
+                second: Box::new(Node::Spoon {
+                    id: 3,
+                    orientation: Orientation::Vertical,
+                    ratio: 0.2,
+                    first: Box::new(Node::bite(4, "b", "sh")),
+                    second: Box::new(Node::bite(5, "c", "sh")),
+                }),
+            },
+        };
+        let mut app = EditorApp::new(template, Config::default());
+        let equals = KeyEvent::new(KeyCode::Char('='), KeyModifiers::NONE);
+        app.handle_key(equals, Rect::new(0, 0, 40, 12)).unwrap();
+
+        let Node::Spoon { ratio, second, .. } = &app.template.layout else {
+            panic!("expected a split layout");
+        };
+        assert!((ratio - 1.0 / 3.0).abs() < 1e-6);
+        assert!(matches!(**second, Node::Spoon { ratio, .. } if (ratio - 0.5).abs() < 1e-6));
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn tab_keys_switch_edit_and_add_tabs() {
+        let template = Template {
+            name: "dev".to_string(),
+            tabs: vec![Tab {
+                name: "logs".to_string(),
+                layout: Node::bite(1, "tail", "tail"),
+            }],
+            active_tab: 0,
+            layout: Node::bite(1, "shell", "sh"),
+        };
+        let mut app = EditorApp::new(template, Config::default());
+        let area = Rect::new(0, 0, 40, 12);
+        let press = |app: &mut EditorApp, code| {
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), area)
+                .unwrap();
+        };
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Char('v'));
+        let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let status: String = (0..40)
+            .map(|x| terminal.backend().buffer().get(x, 10).symbol().to_string())
+            .collect();
+
+        assert!(status.contains("[tab 2/2 logs]"), "{status}");
+        assert!(matches!(app.template.layout, Node::Bite { .. }));
+        assert!(matches!(app.template.tabs[0].layout, Node::Spoon { .. }));
+
+        press(&mut app, KeyCode::BackTab);
+        assert_eq!(app.template.active_tab, 0);
+        press(&mut app, KeyCode::Char('T'));
+        let names: Vec<&str> = (0..3).map(|i| app.template.tab_name(i)).collect();
+        assert_eq!(names, vec!["dev", "tab-3", "logs"]);
+        assert_eq!(app.template.active_tab, 1);
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn x_deletes_the_active_tab_after_confirming_but_keeps_the_last() {
+        let tab = |name: &str, command: &str| Tab {
+            name: name.to_string(),
+            layout: Node::bite(1, "main", command),
+        };
+        let template = Template {
+            name: "dev".to_string(),
+            tabs: vec![tab("logs", "tail"), tab("build", "make")],
+            active_tab: 2,
+            layout: Node::bite(1, "main", "sh"),
+        };
+        let mut app = EditorApp::new(template, Config::default());
+        let area = Rect::new(0, 0, 40, 12);
+        let press = |app: &mut EditorApp, c| {
+            app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), area)
+                .unwrap();
+        };
+        press(&mut app, 'X');
+        press(&mut app, 'n');
+        assert_eq!(app.template.tab_count(), 3);
+        assert!(!app.dirty);
+
+        press(&mut app, 'X');
+        press(&mut app, 'y');
+        assert_eq!(app.template.tab_count(), 2);
+        assert_eq!(app.template.active_tab, 1);
+        assert_eq!(app.message, "タブを削除しました: build");
+
+        app.template.active_tab = 0;
+        press(&mut app, 'X');
+        press(&mut app, 'y');
+        assert!(app.template.tabs.is_empty());
+        assert_eq!(app.template.active_tab, 0);
+        assert!(matches!(&app.template.layout, Node::Bite { command, .. } if command == "tail"));
+
+        press(&mut app, 'X');
+        assert!(!app.confirm_delete_tab);
+        assert_eq!(app.message, "最後のタブは削除できません");
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn angle_brackets_reorder_tabs_and_stop_at_the_ends() {
+        let tab = |name: &str| Tab {
+            name: name.to_string(),
+            layout: Node::bite(1, "main", "sh"),
+        };
+        let template = Template {
+            name: "dev".to_string(),
+            tabs: vec![tab("logs"), tab("build")],
+            active_tab: 1,
+            layout: Node::bite(1, "main", "sh"),
+        };
+        let mut app = EditorApp::new(template, Config::default());
+        let area = Rect::new(0, 0, 40, 12);
+        let press = |app: &mut EditorApp, c| {
+            app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), area)
+                .unwrap();
+        };
+        let names = |app: &EditorApp| -> Vec<String> {
+            (0..app.template.tab_count())
+                .map(|i| app.template.tab_name(i).to_string())
+                .collect()
+        };
+        press(&mut app, '>');
+        assert_eq!(names(&app), vec!["dev", "build", "logs"]);
+        assert_eq!(app.template.active_tab, 2);
+        assert!(app.dirty);
+
+        press(&mut app, '>');
+        assert_eq!(app.message, "これ以上移動できません");
+        press(&mut app, '<');
+        press(&mut app, '<');
+        assert_eq!(names(&app), vec!["dev", "logs", "build"]);
+        assert_eq!(app.message, "これ以上移動できません");
+
+        app.template.active_tab = 0;
+        press(&mut app, '>');
+        assert_eq!(names(&app), vec!["dev", "logs", "build"]);
+        assert_eq!(app.message, "1つ目のタブは移動できません");
+    }
+
+    #[test]
+    fn m_moves_the_selected_pane_into_the_chosen_tab() {
+        let template = Template {
+            name: "dev".to_string(),
+            tabs: vec![Tab {
+                name: "logs".to_string(),
+                layout: Node::bite(1, "tail", "tail -f log"),
+            }],
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.5,
+                first: Box::new(Node::bite(2, "server", "cargo run")),
+                second: Box::new(Node::bite(3, "watch", "cargo watch")),
+            },
+        };
+        let mut app = EditorApp::new(template, Config::default());
+        app.cursor_x = 30;
+        app.cursor_y = 5;
+        let area = Rect::new(0, 0, 40, 12);
+        let press = |app: &mut EditorApp, code| {
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE), area)
+                .unwrap();
+        };
+
+        press(&mut app, KeyCode::Char('m'));
+        assert_eq!(app.move_target, Some(1));
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.message, "ペインは既にタブ「dev」にあります");
+        assert!(!app.dirty);
+
+        press(&mut app, KeyCode::Char('m'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.message, "ペイン「watch」をタブ「logs」へ移動しました");
+        assert!(matches!(app.template.layout, Node::Bite { id: 2, .. }));
+        assert_eq!(app.selected_id, 2);
+        let mut ids = Vec::new();
+        crate::layout::walk(&app.template.tabs[0].layout, &mut |n| ids.push(n.id()));
+        assert_eq!(ids, vec![3, 1, 2]);
+        assert!(matches!(
+            crate::layout::find_bite(&app.template.tabs[0].layout, 2),
+            Some(Node::Bite { command, .. }) if command == "cargo watch"
+        ));
+
+        press(&mut app, KeyCode::Char('m'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.message, "最後のペインは移動できません");
+        press(&mut app, KeyCode::Char('m'));
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.message, "ペインの移動を取り消しました");
+        assert_eq!(app.move_target, None);
+    }
+
+    #[test]
+    fn help_overlay_lists_keys_and_any_key_dismisses_it() {
+        let mut app = EditorApp::new(default_template(), Config::default());
+        let area = Rect::new(0, 0, 40, 20);
+        app.handle_key(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE), area)
+            .unwrap();
+        assert!(app.help);
+
+        let mut terminal = Terminal::new(TestBackend::new(40, 20)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("Help"));
+        assert!(screen.contains("Shift+Arrows"));
+
+        app.handle_key(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::NONE), area)
+            .unwrap();
+        assert!(!app.help);
+        assert!(matches!(app.template.layout, Node::Bite { .. }));
+    }
 }
@@ -0,0 +1,254 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::{
+    layout::PaneDirection::{self, Down, Left, Right, Up},
+    model::Orientation::{self, Horizontal, Vertical},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum EditorCommand {
+    Cursor(PaneDirection),
+    Select(PaneDirection),
+    Split(Orientation),
+    Duplicate,
+    Delete,
+    Resize(Orientation, f32),
+    EditRatio,
+    Balance,
+    ToggleOrientation,
+    EditName,
+    EditCommand,
+    EditCwd,
+    SetPrimary,
+    SwitchTab(bool),
+    AddTab,
+    DeleteTab,
+    MoveTab(bool),
+    MovePaneToTab,
+    Save,
+    SaveAs,
+    Help,
+    Quit,
+}
+
+pub(super) struct EditorKey {
+    pub(super) label: &'static str,
+    pub(super) description: &'static str,
+    bindings: &'static [(KeyCode, KeyModifiers, EditorCommand)],
+}
+
+const fn key(
+    label: &'static str,
+    description: &'static str,
+    bindings: &'static [(KeyCode, KeyModifiers, EditorCommand)],
+) -> EditorKey {
+    EditorKey {
+        label,
+        description,
+        bindings,
+    }
+}
+
+const NONE: KeyModifiers = KeyModifiers::NONE;
+const SHIFT: KeyModifiers = KeyModifiers::SHIFT;
+const CONTROL: KeyModifiers = KeyModifiers::CONTROL;
+
+// Drives both `handle_key` and the `?` overlay, in the order the overlay lists them.
+pub(super) const EDITOR_KEYS: &[EditorKey] = &[
+    key(
+        "Arrows",
+        "カーソル移動",
+        &[
+            (KeyCode::Left, NONE, EditorCommand::Cursor(Left)),
+            (KeyCode::Right, NONE, EditorCommand::Cursor(Right)),
+            (KeyCode::Up, NONE, EditorCommand::Cursor(Up)),
+            (KeyCode::Down, NONE, EditorCommand::Cursor(Down)),
+        ],
+    ),
+    key(
+        "Shift+Arrows",
+        "隣のペインを選択",
+        &[
+            (KeyCode::Left, SHIFT, EditorCommand::Select(Left)),
+            (KeyCode::Right, SHIFT, EditorCommand::Select(Right)),
+            (KeyCode::Up, SHIFT, EditorCommand::Select(Up)),
+            (KeyCode::Down, SHIFT, EditorCommand::Select(Down)),
+        ],
+    ),
+    key(
+        "v / h",
+        "縦 / 横に分割",
+        &[
+            (KeyCode::Char('v'), NONE, EditorCommand::Split(Vertical)),
+            (KeyCode::Char('h'), NONE, EditorCommand::Split(Horizontal)),
+        ],
+    ),
+    key(
+        "Ctrl+d",
+        "ペインを複製",
+        &[(KeyCode::Char('d'), CONTROL, EditorCommand::Duplicate)],
+    ),
+    key(
+        "d",
+        "ペインを削除",
+        &[(KeyCode::Char('d'), NONE, EditorCommand::Delete)],
+    ),
+    key(
+        "H / L / K / J",
+        "比率を左 / 右 / 上 / 下へ",
+        &[
+            (
+                KeyCode::Char('H'),
+                NONE,
+                EditorCommand::Resize(Vertical, -1.0),
+            ),
+            (
+                KeyCode::Char('L'),
+                NONE,
+                EditorCommand::Resize(Vertical, 1.0),
+            ),
+            (
+                KeyCode::Char('K'),
+                NONE,
+                EditorCommand::Resize(Horizontal, -1.0),
+            ),
+            (
+                KeyCode::Char('J'),
+                NONE,
+                EditorCommand::Resize(Horizontal, 1.0),
+            ),
+        ],
+    ),
+    key(
+        "r",
+        "比率を数値で入力",
+        &[(KeyCode::Char('r'), NONE, EditorCommand::EditRatio)],
+    ),
+    key(
+        "=",
+        "分割比率を均等に",
+        &[(KeyCode::Char('='), NONE, EditorCommand::Balance)],
+    ),
+    key(
+        "o",
+        "親の分割方向を切り替え",
+        &[(KeyCode::Char('o'), NONE, EditorCommand::ToggleOrientation)],
+    ),
+    key(
+        "n",
+        "ペイン名を編集",
+        &[(KeyCode::Char('n'), NONE, EditorCommand::EditName)],
+    ),
+    key(
+        "c",
+        "コマンドを編集",
+        &[(KeyCode::Char('c'), NONE, EditorCommand::EditCommand)],
+    ),
+    key(
+        "w",
+        "作業ディレクトリを編集",
+        &[(KeyCode::Char('w'), NONE, EditorCommand::EditCwd)],
+    ),
+    key(
+        "p",
+        "初期フォーカスに設定",
+        &[(KeyCode::Char('p'), NONE, EditorCommand::SetPrimary)],
+    ),
+    key(
+        "Tab / Shift+Tab",
+        "次 / 前のタブ",
+        &[
+            (KeyCode::Tab, NONE, EditorCommand::SwitchTab(true)),
+            (KeyCode::BackTab, NONE, EditorCommand::SwitchTab(false)),
+        ],
+    ),
+    key(
+        "T",
+        "タブを追加",
+        &[(KeyCode::Char('T'), NONE, EditorCommand::AddTab)],
+    ),
+    key(
+        "X",
+        "タブを削除（確認あり）",
+        &[(KeyCode::Char('X'), NONE, EditorCommand::DeleteTab)],
+    ),
+    key(
+        "< / >",
+        "タブを前 / 後ろへ移動",
+        &[
+            (KeyCode::Char('<'), NONE, EditorCommand::MoveTab(false)),
+            (KeyCode::Char('>'), NONE, EditorCommand::MoveTab(true)),
+        ],
+    ),
+    key(
+        "m",
+        "ペインを別のタブへ移動",
+        &[(KeyCode::Char('m'), NONE, EditorCommand::MovePaneToTab)],
+    ),
+    key(
+        "s",
+        "保存",
+        &[(KeyCode::Char('s'), NONE, EditorCommand::Save)],
+    ),
+    key(
+        "S",
+        "別名で保存",
+        &[(KeyCode::Char('S'), NONE, EditorCommand::SaveAs)],
+    ),
+    key(
+        "?",
+        "このヘルプ",
+        &[(KeyCode::Char('?'), NONE, EditorCommand::Help)],
+    ),
+    key(
+        "q / Ctrl+c",
+        "終了（未保存なら確認）",
+        &[
+            (KeyCode::Char('q'), NONE, EditorCommand::Quit),
+            (KeyCode::Char('c'), CONTROL, EditorCommand::Quit),
+        ],
+    ),
+];
+
+// Extra modifiers are ignored (terminals report Shift on `H` or `?`), so when several
+// bindings match, the one asking for the most modifiers wins: Ctrl+c quits, c edits.
+pub(super) fn editor_command(key: KeyEvent) -> Option<EditorCommand> {
+    EDITOR_KEYS
+        .iter()
+        .flat_map(|entry| entry.bindings)
+        .filter(|(code, modifiers, _)| *code == key.code && key.modifiers.contains(*modifiers))
+        .max_by_key(|(_, modifiers, _)| modifiers.bits().count_ones())
+        .map(|(_, _, command)| *command)
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{editor_command, EditorCommand, EDITOR_KEYS};
+
+    #[test]
+    fn every_listed_binding_reaches_its_command() {
+        for entry in EDITOR_KEYS {
+            for (code, modifiers, command) in entry.bindings {
+                let resolved = editor_command(KeyEvent::new(*code, *modifiers));
+                assert_eq!(resolved, Some(*command), "{} ({code:?})", entry.label);
+            }
+        }
+    }
+
+    #[test]
+    fn modifiers_pick_the_more_specific_binding() {
+        let press = |c, modifiers| editor_command(KeyEvent::new(KeyCode::Char(c), modifiers));
+        assert_eq!(press('c', KeyModifiers::CONTROL), Some(EditorCommand::Quit));
+        assert_eq!(
+            press('c', KeyModifiers::NONE),
+            Some(EditorCommand::EditCommand)
+        );
+        assert_eq!(
+            press('H', KeyModifiers::SHIFT),
+            press('H', KeyModifiers::NONE)
+        );
+        assert_eq!(press('z', KeyModifiers::NONE), None);
+    }
+}
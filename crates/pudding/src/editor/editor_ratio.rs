@@ -0,0 +1,51 @@
+use crate::layout::{MAX_RATIO, MIN_RATIO};
+
+// Accepts `0.25`, `25%` or `1/3`; out-of-range values are rejected rather than clamped.
+pub(super) fn parse_ratio(text: &str) -> Result<f32, String> {
+    let text = text.trim();
+    let number = |s: &str| {
+        s.trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("数値として読めません: {text}"))
+    };
+    let ratio = if let Some(percent) = text.strip_suffix('%') {
+        number(percent)? / 100.0
+    } else if let Some((num, den)) = text.split_once('/') {
+        let den = number(den)?;
+        if den == 0.0 {
+            return Err("分母が 0 です".to_string());
+        }
+        number(num)? / den
+    } else {
+        number(text)?
+    };
+    if !(MIN_RATIO..=MAX_RATIO).contains(&ratio) {
+        return Err(format!(
+            "比率は {MIN_RATIO}〜{MAX_RATIO} の範囲で指定してください"
+        ));
+    }
+    Ok(ratio)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_ratio;
+
+    #[test]
+    fn accepts_decimal_percent_and_fraction() {
+        assert_eq!(parse_ratio("0.25"), Ok(0.25));
+        assert_eq!(parse_ratio(" 30% "), Ok(0.3));
+        assert!((parse_ratio("1/3").unwrap() - 1.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rejects_garbage_and_out_of_range() {
+        assert!(parse_ratio("abc").is_err());
+        assert!(parse_ratio("1/0").is_err());
+        assert!(parse_ratio("0.05").is_err());
+        assert!(parse_ratio("95%").is_err());
+        assert!(parse_ratio("NaN").is_err());
+    }
+}
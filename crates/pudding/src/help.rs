@@ -0,0 +1,59 @@
+use ratatui::{
+    layout::Rect,
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+// Size that fits every line plus the border, capped to `area`; longer lists are truncated.
+pub fn help_size(lines: &[String], area: Rect) -> (u16, u16) {
+    let widest = lines
+        .iter()
+        .map(|line| Line::from(line.as_str()).width())
+        .max()
+        .unwrap_or(0);
+    let width = (widest + 2).min(area.width as usize) as u16;
+    let height = (lines.len() + 2).min(area.height as usize) as u16;
+    (width, height)
+}
+
+pub fn draw_help(f: &mut Frame, rect: Rect, lines: &[String]) {
+    let hidden = lines
+        .len()
+        .saturating_sub(rect.height.saturating_sub(2) as usize);
+    let title = if hidden > 0 {
+        format!("Help (+{hidden})")
+    } else {
+        "Help".to_string()
+    };
+    let text: Vec<Line> = lines.iter().map(|line| Line::from(line.as_str())).collect();
+    f.render_widget(Clear, rect);
+    f.render_widget(
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(title)),
+        rect,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+
+    use super::{draw_help, help_size};
+
+    #[test]
+    fn fits_lines_and_truncates_on_small_terminals() {
+        let lines: Vec<String> = (0..20).map(|i| format!("key-{i}  action")).collect();
+        assert_eq!(help_size(&lines, Rect::new(0, 0, 80, 40)), (16, 22));
+        assert_eq!(help_size(&lines, Rect::new(0, 0, 6, 3)), (6, 3));
+
+        for (w, h) in [(6, 3), (1, 1), (0, 0)] {
+            let mut terminal = Terminal::new(TestBackend::new(w, h)).unwrap();
+            terminal
+                .draw(|f| {
+                    let (width, height) = help_size(&lines, f.size());
+                    draw_help(f, Rect::new(0, 0, width, height), &lines);
+                })
+                .unwrap();
+        }
+    }
+}
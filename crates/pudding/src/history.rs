@@ -0,0 +1,149 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    paths::{config_dir, data_dir},
+    template::{ensure_dir_secure, write_private_file},
+};
+
+pub const HISTORY_LIMIT: usize = 100;
+
+pub fn history_path() -> PathBuf {
+    config_dir().join("pudding").join("history.json")
+}
+
+// Commands entered in the editor and runtime command prompts, apart from other prompt input.
+pub fn command_history_path() -> PathBuf {
+    data_dir().join("pudding").join("command_history.json")
+}
+
+// Entries submitted through input prompts, oldest first. Without a path the
+// history only lives for the session (tests, or when the file is unusable).
+#[derive(Debug, Default)]
+pub struct History {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+    // Index of the recalled entry and the buffer it replaced.
+    browsing: Option<(usize, String)>,
+}
+
+impl History {
+    // A missing or corrupt file starts an empty history rather than failing the UI.
+    pub fn load(path: PathBuf) -> Self {
+        let mut entries: Vec<String> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        let excess = entries.len().saturating_sub(HISTORY_LIMIT);
+        entries.drain(..excess);
+        Self {
+            entries,
+            path: Some(path),
+            browsing: None,
+        }
+    }
+
+    // Saving is best-effort: a read-only config dir should not block the prompt.
+    pub fn record(&mut self, entry: &str) {
+        self.browsing = None;
+        if entry.is_empty() || self.entries.last().is_some_and(|last| last == entry) {
+            return;
+        }
+        self.entries.push(entry.to_string());
+        let excess = self.entries.len().saturating_sub(HISTORY_LIMIT);
+        self.entries.drain(..excess);
+        if let Some(path) = &self.path {
+            let _ = save_entries(path, &self.entries);
+        }
+    }
+
+    // Steps back one entry, remembering `current` the first time.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let index = match &self.browsing {
+            Some((0, _)) => return None,
+            Some((index, _)) => index - 1,
+            None => self.entries.len().checked_sub(1)?,
+        };
+        let draft = match self.browsing.take() {
+            Some((_, draft)) => draft,
+            None => current.to_string(),
+        };
+        self.browsing = Some((index, draft));
+        Some(&self.entries[index])
+    }
+
+    // Steps forward; past the newest entry the remembered draft comes back.
+    pub fn newer(&mut self) -> Option<String> {
+        let (index, draft) = self.browsing.take()?;
+        if index + 1 < self.entries.len() {
+            self.browsing = Some((index + 1, draft));
+            return Some(self.entries[index + 1].clone());
+        }
+        Some(draft)
+    }
+
+    // Leaves browsing and hands back the buffer that was being typed, if any.
+    pub fn restore(&mut self) -> Option<String> {
+        self.browsing.take().map(|(_, draft)| draft)
+    }
+}
+
+fn save_entries(path: &Path, entries: &[String]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        ensure_dir_secure(parent)?;
+    }
+    let data = serde_json::to_string_pretty(entries).map_err(std::io::Error::other)?;
+    write_private_file(path, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{History, HISTORY_LIMIT};
+
+    #[test]
+    fn browses_back_and_forward_to_the_draft() {
+        let mut history = History::default();
+        history.record("ls");
+        history.record("top");
+        assert_eq!(history.older("tai"), Some("top"));
+        assert_eq!(history.older("top"), Some("ls"));
+        assert_eq!(history.older("ls"), None);
+        assert_eq!(history.newer().as_deref(), Some("top"));
+        assert_eq!(history.newer().as_deref(), Some("tai"));
+        assert_eq!(history.newer(), None);
+        history.older("x");
+        assert_eq!(history.restore().as_deref(), Some("x"));
+        assert_eq!(history.restore(), None);
+    }
+
+    #[test]
+    fn skips_empty_and_repeated_entries_and_stays_bounded() {
+        let mut history = History::default();
+        history.record("");
+        history.record("a");
+        history.record("a");
+        assert_eq!(history.entries, vec!["a"]);
+        for i in 0..HISTORY_LIMIT + 5 {
+            history.record(&i.to_string());
+        }
+        assert_eq!(history.entries.len(), HISTORY_LIMIT);
+        assert_eq!(history.entries[0], "5");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn persists_to_a_private_file() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("pudding-history-{}", std::process::id()));
+        let path = dir.join("history.json");
+        let mut history = History::load(path.clone());
+        history.record("cargo test");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        let reloaded = History::load(path.clone()).entries;
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(mode, 0o600);
+        assert_eq!(reloaded, vec!["cargo test"]);
+    }
+}
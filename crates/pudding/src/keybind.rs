@@ -31,9 +31,15 @@ pub fn parse_keybinding(input: &str) -> Option<KeyBinding> {
     if key_part.len() == 1 {
         let ch = key_part.chars().next().unwrap();
         let mut mods = modifiers;
-        if ch.is_uppercase() && !mods.contains(KeyModifiers::SHIFT) {
-            mods |= KeyModifiers::SHIFT;
-        }
+        // Terminals report Ctrl+letter as the lowercase letter, so "Ctrl+S" means Ctrl+s.
+        let ch = if mods.contains(KeyModifiers::CONTROL) {
+            ch.to_ascii_lowercase()
+        } else {
+            if ch.is_uppercase() && !mods.contains(KeyModifiers::SHIFT) {
+                mods |= KeyModifiers::SHIFT;
+            }
+            ch
+        };
         let code = KeyCode::Char(ch);
         return Some(KeyBinding {
             code,
@@ -51,6 +57,8 @@ pub fn parse_keybinding(input: &str) -> Option<KeyBinding> {
         "esc" => KeyCode::Esc,
         "tab" => KeyCode::Tab,
         "backspace" => KeyCode::Backspace,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
         _ => {
             if lower.starts_with('f') {
                 let num = lower.trim_start_matches('f').parse::<u8>().ok()?;
@@ -63,3 +71,35 @@ pub fn parse_keybinding(input: &str) -> Option<KeyBinding> {
 
     Some(KeyBinding { code, modifiers })
 }
+
+// Inverse of `parse_keybinding`, for display; the implied Shift of uppercase letters is omitted.
+pub fn format_keybinding(binding: &KeyBinding) -> String {
+    let mut parts = Vec::new();
+    let implied_shift = matches!(binding.code, KeyCode::Char(ch) if ch.is_uppercase());
+    if binding.modifiers.contains(KeyModifiers::CONTROL) {
+        parts.push("Ctrl".to_string());
+    }
+    if binding.modifiers.contains(KeyModifiers::ALT) {
+        parts.push("Alt".to_string());
+    }
+    if binding.modifiers.contains(KeyModifiers::SHIFT) && !implied_shift {
+        parts.push("Shift".to_string());
+    }
+    let key = match binding.code {
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        other => format!("{other:?}"),
+    };
+    parts.push(key);
+    parts.join("+")
+}
@@ -2,8 +2,19 @@ use ratatui::layout::Rect;
 
 use crate::model::{Node, Orientation};
 
-const MIN_RATIO: f32 = 0.1;
-const MAX_RATIO: f32 = 0.9;
+mod focus;
+mod manipulation;
+
+pub use self::focus::{initial_focus, set_primary};
+use self::manipulation::swap_toward;
+pub use self::manipulation::{
+    adjacent_bite, attach_beside_root, balance_spoons, delete_bite, duplicate_node,
+    flip_orientations, move_bite, move_bite_toward, open_below, override_commands, preset,
+    renumber_ids, split_grid, take_bite, toggle_orientation, PaneDirection, PRESET_NAMES,
+};
+
+pub const MIN_RATIO: f32 = 0.1;
+pub const MAX_RATIO: f32 = 0.9;
 
 pub fn next_id(node: &Node) -> u64 {
     let mut max_id = 0;
@@ -66,17 +77,9 @@ pub fn split_bite(
     default_command: &str,
 ) -> bool {
     match node {
-        Node::Bite { id, name, command } if *id == target_id => {
-            let original = Node::Bite {
-                id: *id,
-                name: name.clone(),
-                command: command.clone(),
-            };
-            let new_bite = Node::Bite {
-                id: new_id,
-                name: format!("bite-{}", new_id),
-                command: default_command.to_string(),
-            };
+        Node::Bite { id, .. } if *id == target_id => {
+            let original = node.clone();
+            let new_bite = Node::default_bite(new_id, format!("bite-{}", new_id), default_command);
             *node = Node::Spoon {
                 id: new_id + 1,
                 orientation,
@@ -147,7 +150,21 @@ fn resize_from_bite_inner(
     }
 }
 
+// Swaps with the sibling bite when there is one, otherwise with the neighbor sharing an edge
+// along `orientation` (right/below first), so `A | (B / C)` can still swap A and B.
+// Ids travel with their bites, so running panes stay keyed correctly.
 pub fn swap_adjacent_bites(node: &mut Node, target_id: u64, orientation: Orientation) -> bool {
+    if swap_sibling_bites(node, target_id, orientation) {
+        return true;
+    }
+    let (forward, backward) = match orientation {
+        Orientation::Vertical => (PaneDirection::Right, PaneDirection::Left),
+        Orientation::Horizontal => (PaneDirection::Down, PaneDirection::Up),
+    };
+    swap_toward(node, target_id, forward) || swap_toward(node, target_id, backward)
+}
+
+fn swap_sibling_bites(node: &mut Node, target_id: u64, orientation: Orientation) -> bool {
     match node {
         Node::Spoon {
             orientation: o,
@@ -165,17 +182,34 @@ pub fn swap_adjacent_bites(node: &mut Node, target_id: u64, orientation: Orienta
                     return true;
                 }
             }
-            swap_adjacent_bites(first, target_id, orientation)
-                || swap_adjacent_bites(second, target_id, orientation)
+            swap_sibling_bites(first, target_id, orientation)
+                || swap_sibling_bites(second, target_id, orientation)
         }
         Node::Spoon { first, second, .. } => {
-            swap_adjacent_bites(first, target_id, orientation)
-                || swap_adjacent_bites(second, target_id, orientation)
+            swap_sibling_bites(first, target_id, orientation)
+                || swap_sibling_bites(second, target_id, orientation)
         }
         Node::Bite { .. } => false,
     }
 }
 
+// The ratio of the spoon directly holding the bite, i.e. the split its border belongs to.
+pub fn parent_ratio_mut(node: &mut Node, target_id: u64) -> Option<&mut f32> {
+    let Node::Spoon {
+        ratio,
+        first,
+        second,
+        ..
+    } = node
+    else {
+        return None;
+    };
+    if first.id() == target_id || second.id() == target_id {
+        return Some(ratio);
+    }
+    parent_ratio_mut(first, target_id).or_else(|| parent_ratio_mut(second, target_id))
+}
+
 pub fn layout_rects(node: &Node, rect: Rect, out: &mut Vec<(u64, Rect)>) {
     match node {
         Node::Bite { id, .. } => out.push((*id, rect)),
@@ -318,7 +352,7 @@ fn point_in_rect(rect: Rect, x: u16, y: u16) -> bool {
 mod tests {
     use ratatui::layout::Rect;
 
-    use crate::layout::{clamp_ratio, next_id, split_rect};
+    use crate::layout::{clamp_ratio, collect_bites, next_id, split_rect, swap_adjacent_bites};
     use crate::model::{Node, Orientation};
 
     #[test]
@@ -386,27 +420,43 @@ mod tests {
             id: 10,
             orientation: Orientation::Vertical,
             ratio: 0.5,
-            first: Box::new(Node::Bite {
+            first: Box::new(Node::bite(3, "a", "sh")),
+            second: Box::new(Node::Spoon {
                 id: 3,
-                name: "a".to_string(),
-                command: "sh".to_string(),
+                orientation: Orientation::Horizontal,
+                ratio: 0.5,
+                first: Box::new(Node::bite(9, "b", "sh")),
+                second: Box::new(Node::bite(9, "c", "sh")),
             }),
+        };
+        assert_eq!(next_id(&node), 11);
+    }
+
+    #[test]
+    fn swaps_visually_adjacent_bites_across_spoons() {
+        let mut layout = Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(Node::bite(2, "a", "sh")),
             second: Box::new(Node::Spoon {
                 id: 3,
                 orientation: Orientation::Horizontal,
                 ratio: 0.5,
-                first: Box::new(Node::Bite {
-                    id: 9,
-                    name: "b".to_string(),
-                    command: "sh".to_string(),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 9,
-                    name: "c".to_string(),
-                    command: "sh".to_string(),
-                }),
+                first: Box::new(Node::bite(4, "b", "sh")),
+                second: Box::new(Node::bite(5, "c", "sh")),
             }),
         };
-        assert_eq!(next_id(&node), 11);
+        let order = |node: &Node| {
+            let mut ids = Vec::new();
+            collect_bites(node, &mut ids);
+            ids
+        };
+        assert!(swap_adjacent_bites(&mut layout, 2, Orientation::Vertical));
+        assert_eq!(order(&layout), vec![4, 2, 5]);
+        assert!(swap_adjacent_bites(&mut layout, 5, Orientation::Vertical));
+        assert_eq!(order(&layout), vec![5, 2, 4]);
+        assert!(swap_adjacent_bites(&mut layout, 2, Orientation::Horizontal));
+        assert_eq!(order(&layout), vec![5, 4, 2]);
     }
 }
@@ -0,0 +1,75 @@
+use crate::model::Node;
+
+use super::{collect_bites, walk};
+
+pub fn initial_focus(node: &Node) -> u64 {
+    let mut primary = None;
+    walk(node, &mut |n| {
+        if let Node::Bite {
+            id, primary: true, ..
+        } = n
+        {
+            primary.get_or_insert(*id);
+        }
+    });
+    if let Some(id) = primary {
+        return id;
+    }
+    let mut ids = Vec::new();
+    collect_bites(node, &mut ids);
+    ids.first().copied().unwrap_or_else(|| node.id())
+}
+
+pub fn set_primary(node: &mut Node, target_id: u64) {
+    match node {
+        Node::Bite { id, primary, .. } => *primary = *id == target_id,
+        Node::Spoon { first, second, .. } => {
+            set_primary(first, target_id);
+            set_primary(second, target_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{initial_focus, set_primary};
+    use crate::model::{Node, Orientation};
+
+    fn tree(primary_second: bool) -> Node {
+        let mut second = Node::bite(3, "b", "sh");
+        if let Node::Bite { primary, .. } = &mut second {
+            *primary = primary_second;
+        }
+        Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(Node::bite(2, "a", "sh")),
+            second: Box::new(second),
+        }
+    }
+
+    #[test]
+    fn focuses_primary_bite() {
+        assert_eq!(initial_focus(&tree(true)), 3);
+    }
+
+    #[test]
+    fn set_primary_moves_marker() {
+        let mut node = tree(true);
+        set_primary(&mut node, 2);
+        assert_eq!(initial_focus(&node), 2);
+        let mut count = 0;
+        crate::layout::walk(&node, &mut |n| {
+            if matches!(n, Node::Bite { primary: true, .. }) {
+                count += 1;
+            }
+        });
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn falls_back_to_first_bite_not_root_spoon() {
+        assert_eq!(initial_focus(&tree(false)), 2);
+    }
+}
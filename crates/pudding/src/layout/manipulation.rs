@@ -0,0 +1,1024 @@
+use anyhow::{bail, Result};
+use ratatui::layout::Rect;
+
+use crate::model::{Node, Orientation};
+
+use super::{
+    clamp_ratio, collect_bites, find_bite, find_bite_mut, layout_rects, next_id, split_bite,
+};
+
+pub fn flip_orientations(node: &mut Node) {
+    if let Node::Spoon {
+        orientation,
+        first,
+        second,
+        ..
+    } = node
+    {
+        *orientation = match orientation {
+            Orientation::Vertical => Orientation::Horizontal,
+            Orientation::Horizontal => Orientation::Vertical,
+        };
+        flip_orientations(first);
+        flip_orientations(second);
+    }
+}
+
+// Flips only the spoon directly holding the pane; the ratio stays as it was.
+pub fn toggle_orientation(node: &mut Node, target_id: u64) -> bool {
+    let Node::Spoon {
+        orientation,
+        first,
+        second,
+        ..
+    } = node
+    else {
+        return false;
+    };
+    if first.id() == target_id || second.id() == target_id {
+        *orientation = match orientation {
+            Orientation::Vertical => Orientation::Horizontal,
+            Orientation::Horizontal => Orientation::Vertical,
+        };
+        return true;
+    }
+    toggle_orientation(first, target_id) || toggle_orientation(second, target_id)
+}
+
+pub fn open_below(
+    node: &mut Node,
+    target_id: u64,
+    default_command: &str,
+    live_cwd: Option<String>,
+) -> Option<u64> {
+    let Some(Node::Bite { cwd, .. }) = find_bite(node, target_id) else {
+        return None;
+    };
+    let cwd = live_cwd.or_else(|| cwd.clone());
+    let new_id = next_id(node);
+    if !split_bite(
+        node,
+        target_id,
+        Orientation::Horizontal,
+        0.5,
+        new_id,
+        default_command,
+    ) {
+        return None;
+    }
+    if let Some(Node::Bite { cwd: slot, .. }) = find_bite_mut(node, new_id) {
+        *slot = cwd;
+    }
+    Some(new_id)
+}
+
+pub fn delete_bite(node: &mut Node, target_id: u64) -> Result<u64> {
+    if let Node::Bite { id, .. } = node {
+        if *id == target_id {
+            bail!("cannot delete the last bite");
+        }
+        bail!("bite {target_id} not found");
+    }
+    match remove_bite(node, target_id) {
+        Some(focus) => Ok(focus),
+        None => bail!("bite {target_id} not found"),
+    }
+}
+
+// Like `delete_bite`, but hands back the removed bite along with the focus fallback.
+pub fn take_bite(node: &mut Node, target_id: u64) -> Result<(Node, u64)> {
+    if let Node::Bite { id, .. } = node {
+        if *id == target_id {
+            bail!("cannot move the last bite");
+        }
+        bail!("bite {target_id} not found");
+    }
+    match detach_bite(node, target_id) {
+        Some(taken) => Ok(taken),
+        None => bail!("bite {target_id} not found"),
+    }
+}
+
+// Splits the root to put `bite` on the right. The bite is renumbered past the layout's ids
+// and loses `primary`, since the layout may already have its own.
+pub fn attach_beside_root(node: &mut Node, mut bite: Node) -> u64 {
+    let bite_id = next_id(node);
+    if let Node::Bite { id, primary, .. } = &mut bite {
+        *id = bite_id;
+        *primary = false;
+    }
+    let root = std::mem::replace(node, Node::bite(0, "", ""));
+    *node = Node::Spoon {
+        id: bite_id + 1,
+        orientation: Orientation::Vertical,
+        ratio: 0.5,
+        first: Box::new(root),
+        second: Box::new(bite),
+    };
+    bite_id
+}
+
+fn remove_bite(node: &mut Node, target_id: u64) -> Option<u64> {
+    detach_bite(node, target_id).map(|(_, focus)| focus)
+}
+
+// Removes the bite and collapses its parent spoon; returns the bite and the survivor's first bite.
+fn detach_bite(node: &mut Node, target_id: u64) -> Option<(Node, u64)> {
+    let Node::Spoon { first, second, .. } = node else {
+        return None;
+    };
+    let is_target = |child: &Node| matches!(child, Node::Bite { id, .. } if *id == target_id);
+    let (taken, survivor) = if is_target(first) {
+        (first, second)
+    } else if is_target(second) {
+        (second, first)
+    } else {
+        return detach_bite(first, target_id).or_else(|| detach_bite(second, target_id));
+    };
+    let taken = std::mem::replace(taken.as_mut(), Node::bite(0, "", ""));
+    let survivor = std::mem::replace(survivor.as_mut(), Node::bite(0, "", ""));
+    let mut ids = Vec::new();
+    collect_bites(&survivor, &mut ids);
+    *node = survivor;
+    Some((taken, ids.first().copied()?))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaneDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+// Detaches the bite and re-inserts it on the far side of its neighbor in `direction`,
+// splitting the neighbor's space in half. Ids are kept, so running panes stay attached.
+pub fn move_bite_toward(node: &mut Node, target_id: u64, direction: PaneDirection) -> bool {
+    let Some(neighbor_id) = neighbor(node, target_id, direction) else {
+        return false;
+    };
+    let Some((bite, _)) = detach_bite(node, target_id) else {
+        return false;
+    };
+    let spoon_id = next_id(node);
+    let Some(slot) = find_bite_mut(node, neighbor_id) else {
+        return false;
+    };
+    let neighbor = std::mem::replace(slot, Node::bite(0, "", ""));
+    let (orientation, first, second) = match direction {
+        PaneDirection::Left => (Orientation::Vertical, bite, neighbor),
+        PaneDirection::Right => (Orientation::Vertical, neighbor, bite),
+        PaneDirection::Up => (Orientation::Horizontal, bite, neighbor),
+        PaneDirection::Down => (Orientation::Horizontal, neighbor, bite),
+    };
+    *slot = Node::Spoon {
+        id: spoon_id,
+        orientation,
+        ratio: 0.5,
+        first: Box::new(first),
+        second: Box::new(second),
+    };
+    true
+}
+
+fn neighbor(node: &Node, target_id: u64, direction: PaneDirection) -> Option<u64> {
+    adjacent_bite(node, Rect::new(0, 0, 4000, 4000), target_id, direction)
+}
+
+// Picks the bite sharing the edge in `direction`, preferring the one level with the
+// target's center, then the most overlap. Corner-only contact does not count.
+pub fn adjacent_bite(
+    node: &Node,
+    area: Rect,
+    target_id: u64,
+    direction: PaneDirection,
+) -> Option<u64> {
+    let mut rects = Vec::new();
+    layout_rects(node, area, &mut rects);
+    let (_, from) = *rects.iter().find(|(id, _)| *id == target_id)?;
+    let overlap = |a0: u16, a1: u16, b0: u16, b1: u16| a1.min(b1).saturating_sub(a0.max(b0));
+    let center_x = from.x + from.width / 2;
+    let center_y = from.y + from.height / 2;
+    rects
+        .iter()
+        .filter(|(id, _)| *id != target_id)
+        .filter_map(|(id, rect)| {
+            let touching = match direction {
+                PaneDirection::Left => rect.right() == from.x,
+                PaneDirection::Right => rect.x == from.right(),
+                PaneDirection::Up => rect.bottom() == from.y,
+                PaneDirection::Down => rect.y == from.bottom(),
+            };
+            let shared = match direction {
+                PaneDirection::Left | PaneDirection::Right => {
+                    overlap(rect.y, rect.bottom(), from.y, from.bottom())
+                }
+                PaneDirection::Up | PaneDirection::Down => {
+                    overlap(rect.x, rect.right(), from.x, from.right())
+                }
+            };
+            let level = match direction {
+                PaneDirection::Left | PaneDirection::Right => {
+                    (rect.y..rect.bottom()).contains(&center_y)
+                }
+                PaneDirection::Up | PaneDirection::Down => {
+                    (rect.x..rect.right()).contains(&center_x)
+                }
+            };
+            (touching && shared > 0).then_some((*id, (level, shared)))
+        })
+        .max_by_key(|(_, key)| *key)
+        .map(|(id, _)| id)
+}
+
+pub fn swap_toward(node: &mut Node, target_id: u64, direction: PaneDirection) -> bool {
+    neighbor(node, target_id, direction).is_some_and(|other| swap_nodes(node, target_id, other))
+}
+
+pub fn swap_nodes(node: &mut Node, a: u64, b: u64) -> bool {
+    let (Some(node_a), Some(node_b)) = (find_node(node, a), find_node(node, b)) else {
+        return false;
+    };
+    if a == b || find_node(node_a, b).is_some() || find_node(node_b, a).is_some() {
+        return false;
+    }
+    let placeholder = Node::bite(u64::MAX, "", "");
+    let Some(taken_a) = replace_node(node, a, placeholder) else {
+        return false;
+    };
+    let Some(taken_b) = replace_node(node, b, taken_a) else {
+        return false;
+    };
+    replace_node(node, u64::MAX, taken_b).is_some()
+}
+
+// Siblings are the children of the same-orientation spoon chain around the bite,
+// so `a | b | c` cycles through three positions even though spoons are binary.
+pub fn move_bite(node: &mut Node, target_id: u64, forward: bool) -> bool {
+    let Some(run) = sibling_run(node, target_id) else {
+        return false;
+    };
+    let Some(pos) = run.iter().position(|id| *id == target_id) else {
+        return false;
+    };
+    let len = run.len();
+    let other = if forward {
+        run[(pos + 1) % len]
+    } else {
+        run[(pos + len - 1) % len]
+    };
+    swap_nodes(node, target_id, other)
+}
+
+fn sibling_run(node: &Node, target_id: u64) -> Option<Vec<u64>> {
+    fn spoon_path<'a>(node: &'a Node, target_id: u64, path: &mut Vec<&'a Node>) -> bool {
+        match node {
+            Node::Bite { id, .. } => *id == target_id,
+            Node::Spoon { first, second, .. } => {
+                path.push(node);
+                if spoon_path(first, target_id, path) || spoon_path(second, target_id, path) {
+                    return true;
+                }
+                path.pop();
+                false
+            }
+        }
+    }
+    fn flatten(node: &Node, orientation: Orientation, out: &mut Vec<u64>) {
+        match node {
+            Node::Spoon {
+                orientation: inner,
+                first,
+                second,
+                ..
+            } if *inner == orientation => {
+                flatten(first, orientation, out);
+                flatten(second, orientation, out);
+            }
+            _ => out.push(node.id()),
+        }
+    }
+    let orientation_of = |node: &Node| match node {
+        Node::Spoon { orientation, .. } => Some(*orientation),
+        Node::Bite { .. } => None,
+    };
+    let mut path = Vec::new();
+    if !spoon_path(node, target_id, &mut path) {
+        return None;
+    }
+    let orientation = orientation_of(path.last()?)?;
+    let root = path
+        .iter()
+        .rev()
+        .take_while(|spoon| orientation_of(spoon) == Some(orientation))
+        .last()?;
+    let mut run = Vec::new();
+    flatten(root, orientation, &mut run);
+    Some(run)
+}
+
+fn find_node(node: &Node, target_id: u64) -> Option<&Node> {
+    if node.id() == target_id {
+        return Some(node);
+    }
+    match node {
+        Node::Spoon { first, second, .. } => {
+            find_node(first, target_id).or_else(|| find_node(second, target_id))
+        }
+        Node::Bite { .. } => None,
+    }
+}
+
+fn replace_node(node: &mut Node, target_id: u64, replacement: Node) -> Option<Node> {
+    if node.id() == target_id {
+        return Some(std::mem::replace(node, replacement));
+    }
+    let Node::Spoon { first, second, .. } = node else {
+        return None;
+    };
+    if find_node(first, target_id).is_some() {
+        replace_node(first, target_id, replacement)
+    } else {
+        replace_node(second, target_id, replacement)
+    }
+}
+
+pub fn renumber_ids(node: &mut Node) {
+    let mut next = 1;
+    assign_ids(node, &mut next);
+}
+
+fn assign_ids(node: &mut Node, next: &mut u64) {
+    match node {
+        Node::Bite { id, .. } => {
+            *id = *next;
+            *next += 1;
+        }
+        Node::Spoon {
+            id, first, second, ..
+        } => {
+            *id = *next;
+            *next += 1;
+            assign_ids(first, next);
+            assign_ids(second, next);
+        }
+    }
+}
+
+// Like `split_bite`, but the new half is a copy of the target subtree (commands and names
+// included) with fresh ids past `next_id`. Returns the id of the copy's root.
+pub fn duplicate_node(node: &mut Node, target_id: u64, orientation: Orientation) -> Option<u64> {
+    let original = find_node(node, target_id)?.clone();
+    let mut copy = original.clone();
+    let spoon_id = next_id(node);
+    let mut next = spoon_id + 1;
+    assign_ids(&mut copy, &mut next);
+    clear_primary(&mut copy);
+    let copy_id = copy.id();
+    replace_node(
+        node,
+        target_id,
+        Node::Spoon {
+            id: spoon_id,
+            orientation,
+            ratio: 0.5,
+            first: Box::new(original),
+            second: Box::new(copy),
+        },
+    )?;
+    Some(copy_id)
+}
+
+// Only one bite may be primary, so copies never carry the flag.
+fn clear_primary(node: &mut Node) {
+    match node {
+        Node::Bite { primary, .. } => *primary = false,
+        Node::Spoon { first, second, .. } => {
+            clear_primary(first);
+            clear_primary(second);
+        }
+    }
+}
+
+pub fn override_commands(node: &mut Node, command: &str, only: Option<&str>) {
+    match node {
+        Node::Bite {
+            command: slot,
+            from_default,
+            ..
+        } => {
+            if only.is_none_or(|matching| slot == matching) {
+                *slot = command.to_string();
+                *from_default = false;
+            }
+        }
+        Node::Spoon { first, second, .. } => {
+            override_commands(first, command, only);
+            override_commands(second, command, only);
+        }
+    }
+}
+
+pub const PRESET_NAMES: &[&str] = &["single", "two-column", "three-column", "main-stack"];
+
+// Built with the same splits the runtime uses, so ids come from `next_id` and never collide.
+pub fn preset(name: &str, default_command: &str) -> Option<Node> {
+    let mut layout = Node::default_bite(1, "main", default_command);
+    let split = |layout: &mut Node, target, orientation, ratio| {
+        let new_id = next_id(layout);
+        split_bite(layout, target, orientation, ratio, new_id, default_command);
+        new_id
+    };
+    match name {
+        "single" => {}
+        "two-column" => {
+            split(&mut layout, 1, Orientation::Vertical, 0.5);
+        }
+        "three-column" => {
+            let second = split(&mut layout, 1, Orientation::Vertical, 0.5);
+            split(&mut layout, second, Orientation::Vertical, 0.5);
+            balance_spoons(&mut layout);
+        }
+        "main-stack" => {
+            let stack = split(&mut layout, 1, Orientation::Vertical, 0.6);
+            split(&mut layout, stack, Orientation::Horizontal, 0.5);
+        }
+        _ => return None,
+    }
+    Some(layout)
+}
+
+pub fn split_grid(
+    node: &mut Node,
+    target_id: u64,
+    rows: usize,
+    cols: usize,
+    default_command: &str,
+) -> Option<Vec<u64>> {
+    if rows == 0 || cols == 0 {
+        return None;
+    }
+    let mut grid = find_bite(node, target_id)?.clone();
+    let mut new_id = next_id(node);
+    let mut row_heads = vec![target_id];
+    let mut created = Vec::new();
+    for _ in 1..rows {
+        let last = *row_heads.last()?;
+        split_bite(
+            &mut grid,
+            last,
+            Orientation::Horizontal,
+            0.5,
+            new_id,
+            default_command,
+        );
+        row_heads.push(new_id);
+        created.push(new_id);
+        new_id += 2;
+    }
+    for head in row_heads {
+        let mut last = head;
+        for _ in 1..cols {
+            split_bite(
+                &mut grid,
+                last,
+                Orientation::Vertical,
+                0.5,
+                new_id,
+                default_command,
+            );
+            created.push(new_id);
+            last = new_id;
+            new_id += 2;
+        }
+    }
+    balance_spoons(&mut grid);
+    *find_bite_mut(node, target_id)? = grid;
+    Some(created)
+}
+
+// A child spoon of the other orientation counts as one slot, so each run of
+// same-orientation spoons is balanced on its own.
+pub fn balance_spoons(node: &mut Node) {
+    if let Node::Spoon {
+        orientation,
+        ratio,
+        first,
+        second,
+        ..
+    } = node
+    {
+        let before = slots(first, *orientation) as f32;
+        let after = slots(second, *orientation) as f32;
+        *ratio = clamp_ratio(before / (before + after));
+        balance_spoons(first);
+        balance_spoons(second);
+    }
+}
+
+fn slots(node: &Node, orientation: Orientation) -> usize {
+    match node {
+        Node::Spoon {
+            orientation: inner,
+            first,
+            second,
+            ..
+        } if *inner == orientation => slots(first, orientation) + slots(second, orientation),
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+
+    use super::{
+        adjacent_bite, attach_beside_root, balance_spoons, delete_bite, duplicate_node,
+        flip_orientations, move_bite, move_bite_toward, open_below, override_commands, preset,
+        renumber_ids, split_grid, swap_nodes, take_bite, toggle_orientation, PaneDirection,
+        PRESET_NAMES,
+    };
+    use crate::layout::{collect_bites, layout_rects, walk, MIN_RATIO};
+    use crate::model::{Node, Orientation};
+
+    fn sample() -> Node {
+        Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(Node::bite(2, "a", "sh")),
+            second: Box::new(Node::Spoon {
+                id: 3,
+                orientation: Orientation::Horizontal,
+                ratio: 0.5,
+                first: Box::new(Node::bite(4, "b", "sh")),
+                second: Box::new(Node::bite(5, "c", "sh")),
+            }),
+        }
+    }
+
+    fn orientations(node: &Node) -> Vec<Orientation> {
+        let mut out = Vec::new();
+        walk(node, &mut |n| {
+            if let Node::Spoon { orientation, .. } = n {
+                out.push(*orientation);
+            }
+        });
+        out
+    }
+
+    #[test]
+    fn flips_every_spoon() {
+        let mut node = sample();
+        flip_orientations(&mut node);
+        assert_eq!(
+            orientations(&node),
+            vec![Orientation::Horizontal, Orientation::Vertical]
+        );
+    }
+
+    #[test]
+    fn toggles_only_the_nearest_spoon() {
+        let mut node = sample();
+        assert!(toggle_orientation(&mut node, 4));
+        assert_eq!(
+            orientations(&node),
+            vec![Orientation::Vertical, Orientation::Vertical]
+        );
+        assert!(toggle_orientation(&mut node, 2));
+        assert_eq!(
+            orientations(&node),
+            vec![Orientation::Horizontal, Orientation::Vertical]
+        );
+        let Node::Spoon { ratio, .. } = &node else {
+            panic!("root should stay a spoon");
+        };
+        assert_eq!(*ratio, 0.5);
+    }
+
+    #[test]
+    fn toggle_orientation_is_a_no_op_for_the_root() {
+        let mut single = Node::bite(1, "a", "sh");
+        assert!(!toggle_orientation(&mut single, 1));
+        let mut node = sample();
+        assert!(!toggle_orientation(&mut node, 1));
+        assert!(!toggle_orientation(&mut node, 99));
+        assert_eq!(
+            orientations(&node),
+            vec![Orientation::Vertical, Orientation::Horizontal]
+        );
+    }
+
+    #[test]
+    fn open_below_inherits_cwd_and_returns_new_focus() {
+        let mut node = Node::bite(1, "main", "zsh");
+        if let Node::Bite { cwd, .. } = &mut node {
+            *cwd = Some("/srv/app".to_string());
+        }
+        let focus = open_below(&mut node, 1, "bash", None).unwrap();
+        let Node::Spoon {
+            orientation,
+            second,
+            ..
+        } = &node
+        else {
+            panic!("expected spoon");
+        };
+        assert_eq!(*orientation, Orientation::Horizontal);
+        assert_eq!(second.id(), focus);
+        assert!(matches!(
+            second.as_ref(),
+            Node::Bite { command, cwd: Some(cwd), .. } if command == "bash" && cwd == "/srv/app"
+        ));
+    }
+
+    #[test]
+    fn open_below_prefers_live_cwd_and_rejects_unknown_target() {
+        let mut node = Node::bite(1, "main", "zsh");
+        let focus = open_below(&mut node, 1, "bash", Some("/tmp".to_string())).unwrap();
+        assert!(matches!(
+            crate::layout::find_bite(&node, focus),
+            Some(Node::Bite { cwd: Some(cwd), .. }) if cwd == "/tmp"
+        ));
+        assert_eq!(open_below(&mut node, 99, "bash", None), None);
+    }
+
+    #[test]
+    fn rects_follow_flipped_layout() {
+        let mut node = sample();
+        flip_orientations(&mut node);
+        let mut rects = Vec::new();
+        layout_rects(&node, Rect::new(0, 0, 40, 20), &mut rects);
+        assert_eq!(
+            rects,
+            vec![
+                (2, Rect::new(0, 0, 40, 10)),
+                (4, Rect::new(0, 10, 20, 10)),
+                (5, Rect::new(20, 10, 20, 10)),
+            ]
+        );
+    }
+
+    #[test]
+    fn grid_2x2_yields_four_equal_rects() {
+        let mut node = Node::bite(1, "main", "zsh");
+        let created = split_grid(&mut node, 1, 2, 2, "bash").unwrap();
+        assert_eq!(created.len(), 3);
+        let mut ids = Vec::new();
+        collect_bites(&node, &mut ids);
+        assert_eq!(ids.len(), 4);
+        let mut rects = Vec::new();
+        layout_rects(&node, Rect::new(0, 0, 80, 24), &mut rects);
+        for (_, rect) in &rects {
+            assert_eq!((rect.width, rect.height), (40, 12));
+        }
+        assert!(split_grid(&mut node, 99, 2, 2, "bash").is_none());
+        assert!(split_grid(&mut node, 1, 0, 2, "bash").is_none());
+    }
+
+    #[test]
+    fn grid_keeps_rest_of_tree_and_balances_rows() {
+        let mut node = sample();
+        split_grid(&mut node, 2, 3, 1, "bash").unwrap();
+        let mut rects = Vec::new();
+        layout_rects(&node, Rect::new(0, 0, 40, 30), &mut rects);
+        let heights: Vec<u16> = rects
+            .iter()
+            .filter(|(_, r)| r.x == 0)
+            .map(|(_, r)| r.height)
+            .collect();
+        assert_eq!(heights, vec![10, 10, 10]);
+        assert!(matches!(&node, Node::Spoon { ratio, .. } if *ratio == 0.5));
+    }
+
+    #[test]
+    fn balance_counts_same_orientation_runs() {
+        let mut node = Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.8,
+            first: Box::new(Node::bite(2, "a", "sh")),
+            second: Box::new(Node::Spoon {
+                id: 3,
+                orientation: Orientation::Vertical,
+                ratio: 0.2,
+                first: Box::new(Node::bite(4, "b", "sh")),
+                second: Box::new(Node::bite(5, "c", "sh")),
+            }),
+        };
+        balance_spoons(&mut node);
+        let Node::Spoon { ratio, second, .. } = &node else {
+            panic!("expected spoon");
+        };
+        assert!((ratio - 1.0 / 3.0).abs() < 1e-6);
+        assert!(matches!(second.as_ref(), Node::Spoon { ratio, .. } if *ratio == 0.5));
+    }
+
+    #[test]
+    fn balance_treats_mixed_orientation_child_as_one_slot() {
+        let mut node = sample();
+        if let Node::Spoon { ratio, .. } = &mut node {
+            *ratio = 0.9;
+        }
+        balance_spoons(&mut node);
+        assert!(matches!(&node, Node::Spoon { ratio, .. } if *ratio == 0.5));
+
+        let mut run = Node::bite(1, "p1", "sh");
+        for id in 2..=12 {
+            run = Node::Spoon {
+                id: id * 100,
+                orientation: Orientation::Horizontal,
+                ratio: 0.5,
+                first: Box::new(Node::bite(id, "p", "sh")),
+                second: Box::new(run),
+            };
+        }
+        balance_spoons(&mut run);
+        assert!(matches!(&run, Node::Spoon { ratio, .. } if *ratio == MIN_RATIO));
+    }
+
+    #[test]
+    fn override_commands_can_target_default_only() {
+        let mut node = sample();
+        if let Some(Node::Bite { command, .. }) = crate::layout::find_bite_mut(&mut node, 4) {
+            *command = "htop".to_string();
+        }
+        let mut all = node.clone();
+        override_commands(&mut all, "zsh", None);
+        override_commands(&mut node, "zsh", Some("sh"));
+        let commands = |node: &Node| {
+            let mut out = Vec::new();
+            walk(node, &mut |n| {
+                if let Node::Bite { command, .. } = n {
+                    out.push(command.clone());
+                }
+            });
+            out
+        };
+        assert_eq!(commands(&all), vec!["zsh", "zsh", "zsh"]);
+        assert_eq!(commands(&node), vec!["zsh", "htop", "zsh"]);
+    }
+
+    #[test]
+    fn delete_bite_promotes_sibling_and_returns_focus() {
+        let mut node = sample();
+        assert_eq!(delete_bite(&mut node, 2).unwrap(), 4);
+        assert!(matches!(&node, Node::Spoon { id: 3, .. }));
+        assert_eq!(delete_bite(&mut node, 5).unwrap(), 4);
+        assert!(matches!(&node, Node::Bite { id: 4, .. }));
+    }
+
+    #[test]
+    fn delete_bite_refuses_last_or_unknown_bite() {
+        let mut node = sample();
+        assert!(delete_bite(&mut node, 99).is_err());
+        let mut single = Node::bite(1, "only", "sh");
+        let err = delete_bite(&mut single, 1).unwrap_err();
+        assert!(err.to_string().contains("last bite"));
+        assert!(matches!(single, Node::Bite { id: 1, .. }));
+    }
+
+    #[test]
+    fn taken_bite_joins_another_layout_with_fresh_ids() {
+        let mut from = sample();
+        if let Some(Node::Bite { primary, .. }) = crate::layout::find_bite_mut(&mut from, 4) {
+            *primary = true;
+        }
+        let (bite, focus) = take_bite(&mut from, 4).unwrap();
+        let mut to = Node::bite(1, "tail", "tail -f log");
+        let id = attach_beside_root(&mut to, bite);
+
+        assert_eq!(focus, 5);
+        assert!(matches!(&from, Node::Spoon { second, .. } if second.id() == 5));
+        assert_eq!(id, 2);
+        let mut ids = Vec::new();
+        walk(&to, &mut |n| ids.push(n.id()));
+        assert_eq!(ids, vec![3, 1, 2]);
+        assert!(matches!(
+            &to,
+            Node::Spoon { second, .. }
+                if matches!(second.as_ref(), Node::Bite { name, primary: false, .. } if name == "b")
+        ));
+        let mut single = Node::bite(1, "only", "sh");
+        assert!(take_bite(&mut single, 1).is_err());
+    }
+
+    #[test]
+    fn renumbered_layout_is_a_valid_standalone_template() {
+        let mut node = sample();
+        split_grid(&mut node, 5, 2, 2, "bash").unwrap();
+        renumber_ids(&mut node);
+        let mut ids = Vec::new();
+        walk(&node, &mut |n| ids.push(n.id()));
+        assert_eq!(ids, (1..=ids.len() as u64).collect::<Vec<_>>());
+        let template = crate::model::Template {
+            name: "captured".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: node,
+        };
+        assert!(crate::template::template_problems(&template).is_empty());
+    }
+
+    #[test]
+    fn duplicate_copies_subtree_with_fresh_ids_and_can_be_deleted() {
+        let mut node = sample();
+        if let Some(Node::Bite { primary, .. }) = crate::layout::find_bite_mut(&mut node, 4) {
+            *primary = true;
+        }
+        let copy_id = duplicate_node(&mut node, 3, Orientation::Vertical).unwrap();
+        assert_eq!(copy_id, 7);
+        let template = crate::model::Template {
+            name: "dup".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: node.clone(),
+        };
+        assert!(crate::template::template_problems(&template).is_empty());
+        let mut bites = Vec::new();
+        collect_bites(&node, &mut bites);
+        assert_eq!(bites, vec![2, 4, 5, 8, 9]);
+        assert!(matches!(
+            crate::layout::find_bite(&node, 8),
+            Some(Node::Bite { name, command, primary: false, .. }) if name == "b" && command == "sh"
+        ));
+
+        delete_bite(&mut node, 8).unwrap();
+        delete_bite(&mut node, 9).unwrap();
+        let mut ids = Vec::new();
+        walk(&node, &mut |n| ids.push(n.id()));
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+        assert!(duplicate_node(&mut node, 99, Orientation::Vertical).is_none());
+    }
+
+    fn bite_order(node: &Node) -> Vec<u64> {
+        let mut ids = Vec::new();
+        collect_bites(node, &mut ids);
+        ids
+    }
+
+    fn row_of_three() -> Node {
+        Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(Node::bite(2, "a", "sh")),
+            second: Box::new(Node::Spoon {
+                id: 3,
+                orientation: Orientation::Vertical,
+                ratio: 0.5,
+                first: Box::new(Node::bite(4, "b", "sh")),
+                second: Box::new(Node::bite(5, "c", "sh")),
+            }),
+        }
+    }
+
+    #[test]
+    fn repeated_moves_cycle_position_among_siblings() {
+        let mut layout = row_of_three();
+        let mut positions = Vec::new();
+        for _ in 0..3 {
+            assert!(move_bite(&mut layout, 2, true));
+            positions.push(bite_order(&layout).iter().position(|id| *id == 2).unwrap());
+        }
+        assert_eq!(positions, vec![1, 2, 0]);
+        for _ in 0..3 {
+            assert!(move_bite(&mut layout, 2, false));
+            positions.push(bite_order(&layout).iter().position(|id| *id == 2).unwrap());
+        }
+        assert_eq!(&positions[3..], &[2, 1, 0]);
+    }
+
+    #[test]
+    fn move_swaps_with_a_whole_sibling_subtree() {
+        let mut layout = sample();
+        assert!(move_bite(&mut layout, 2, true));
+        let Node::Spoon { first, second, .. } = &layout else {
+            panic!("root should stay a spoon");
+        };
+        assert_eq!(first.id(), 3);
+        assert_eq!(second.id(), 2);
+        assert!(!move_bite(&mut Node::bite(1, "solo", "sh"), 1, true));
+    }
+
+    #[test]
+    fn swap_nodes_rejects_nested_or_missing_ids() {
+        let mut layout = row_of_three();
+        assert!(!swap_nodes(&mut layout, 3, 4));
+        assert!(!swap_nodes(&mut layout, 2, 99));
+        assert!(swap_nodes(&mut layout, 2, 5));
+        assert_eq!(bite_order(&layout), vec![5, 4, 2]);
+    }
+
+    #[test]
+    fn move_toward_crosses_spoon_boundaries() {
+        // sample(): a | (b / c)
+        let mut layout = sample();
+        assert!(move_bite_toward(&mut layout, 4, PaneDirection::Left));
+        let mut rects = Vec::new();
+        layout_rects(&layout, Rect::new(0, 0, 80, 20), &mut rects);
+        let x_of = |id: u64| rects.iter().find(|(i, _)| *i == id).unwrap().1.x;
+        assert!(x_of(4) < x_of(2));
+        assert_eq!(bite_order(&layout), vec![4, 2, 5]);
+
+        let mut ids = Vec::new();
+        walk(&layout, &mut |node| ids.push(node.id()));
+        let count = ids.len();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), count);
+    }
+
+    #[test]
+    fn move_toward_without_neighbor_leaves_layout_alone() {
+        let mut layout = sample();
+        assert!(!move_bite_toward(&mut layout, 2, PaneDirection::Left));
+        assert!(!move_bite_toward(&mut layout, 4, PaneDirection::Up));
+        assert_eq!(bite_order(&layout), vec![2, 4, 5]);
+        assert!(move_bite_toward(&mut layout, 5, PaneDirection::Up));
+        assert_eq!(bite_order(&layout), vec![2, 5, 4]);
+    }
+
+    #[test]
+    fn adjacent_bite_ignores_corner_contact() {
+        let column = |id, top, bottom| Node::Spoon {
+            id,
+            orientation: Orientation::Horizontal,
+            ratio: 0.5,
+            first: Box::new(Node::bite(top, "top", "sh")),
+            second: Box::new(Node::bite(bottom, "bottom", "sh")),
+        };
+        let layout = Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(column(2, 3, 4)),
+            second: Box::new(column(5, 6, 7)),
+        };
+        let area = ratatui::layout::Rect::new(0, 0, 80, 24);
+        assert_eq!(
+            adjacent_bite(&layout, area, 3, PaneDirection::Right),
+            Some(6)
+        );
+        assert_eq!(
+            adjacent_bite(&layout, area, 7, PaneDirection::Left),
+            Some(4)
+        );
+        assert_eq!(
+            adjacent_bite(&layout, area, 3, PaneDirection::Down),
+            Some(4)
+        );
+        assert_eq!(adjacent_bite(&layout, area, 3, PaneDirection::Up), None);
+    }
+
+    #[test]
+    fn presets_have_expected_shape_and_unique_ids() {
+        let expected = [
+            ("single", 1, vec![]),
+            ("two-column", 2, vec![Orientation::Vertical]),
+            (
+                "three-column",
+                3,
+                vec![Orientation::Vertical, Orientation::Vertical],
+            ),
+            (
+                "main-stack",
+                3,
+                vec![Orientation::Vertical, Orientation::Horizontal],
+            ),
+        ];
+        assert_eq!(PRESET_NAMES.len(), expected.len());
+        for (name, panes, spoons) in expected {
+            let layout = preset(name, "zsh").unwrap();
+            let mut bites = Vec::new();
+            collect_bites(&layout, &mut bites);
+            assert_eq!(bites.len(), panes, "{name}");
+            assert_eq!(orientations(&layout), spoons, "{name}");
+
+            let mut ids = Vec::new();
+            walk(&layout, &mut |node| {
+                ids.push(node.id());
+                if let Node::Bite { command, .. } = node {
+                    assert_eq!(command, "zsh");
+                }
+            });
+            let count = ids.len();
+            ids.sort_unstable();
+            ids.dedup();
+            assert_eq!(ids.len(), count, "{name}");
+        }
+        assert!(preset("nope", "zsh").is_none());
+    }
+
+    #[test]
+    fn three_column_preset_has_equal_widths() {
+        let layout = preset("three-column", "sh").unwrap();
+        let mut rects = Vec::new();
+        layout_rects(
+            &layout,
+            ratatui::layout::Rect::new(0, 0, 90, 10),
+            &mut rects,
+        );
+        assert!(rects.iter().all(|(_, rect)| rect.width == 30));
+    }
+}
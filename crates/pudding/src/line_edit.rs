@@ -0,0 +1,135 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// Single-line text buffer shared by the editor and runtime prompts. `cursor` is a
+// byte offset that always sits on a grapheme boundary.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LineEdit {
+    text: String,
+    cursor: usize,
+}
+
+impl LineEdit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_text(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let cursor = text.len();
+        Self { text, cursor }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    // Display columns left of the cursor, for placing the terminal cursor.
+    pub fn cursor_width(&self) -> u16 {
+        self.text[..self.cursor].width() as u16
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        self.text.insert_str(self.cursor, s);
+        self.cursor += s.len();
+    }
+
+    pub fn backspace(&mut self) {
+        let start = self.prev_boundary();
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn delete(&mut self) {
+        let end = self.next_boundary();
+        self.text.replace_range(self.cursor..end, "");
+    }
+
+    // Applies a text-editing key and reports whether it was one.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Left => self.cursor = self.prev_boundary(),
+            KeyCode::Right => self.cursor = self.next_boundary(),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.len(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.insert_str(c.encode_utf8(&mut [0; 4]));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(index, _)| index)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |g| self.cursor + g.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::LineEdit;
+
+    fn press(line: &mut LineEdit, code: KeyCode) {
+        assert!(line.handle_key(KeyEvent::new(code, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn inserts_and_deletes_at_the_cursor() {
+        let mut line = LineEdit::with_text("cho hi");
+        press(&mut line, KeyCode::Home);
+        press(&mut line, KeyCode::Char('e'));
+        assert_eq!(line.as_str(), "echo hi");
+        press(&mut line, KeyCode::End);
+        press(&mut line, KeyCode::Left);
+        press(&mut line, KeyCode::Delete);
+        press(&mut line, KeyCode::Backspace);
+        assert_eq!(line.as_str(), "echo ");
+        press(&mut line, KeyCode::Right);
+        assert_eq!(line.cursor_width(), 5);
+    }
+
+    #[test]
+    fn moves_over_multibyte_graphemes_as_a_unit() {
+        let mut line = LineEdit::with_text("aé日\u{1f44d}\u{1f3fd}");
+        press(&mut line, KeyCode::Left);
+        assert_eq!(line.cursor_width(), 4);
+        press(&mut line, KeyCode::Left);
+        press(&mut line, KeyCode::Backspace);
+        assert_eq!(line.as_str(), "a日\u{1f44d}\u{1f3fd}");
+        assert_eq!(line.cursor_width(), 1);
+        press(&mut line, KeyCode::Delete);
+        assert_eq!(line.as_str(), "a\u{1f44d}\u{1f3fd}");
+        press(&mut line, KeyCode::Home);
+        press(&mut line, KeyCode::Left);
+        press(&mut line, KeyCode::Backspace);
+        assert_eq!(line.as_str(), "a\u{1f44d}\u{1f3fd}");
+    }
+
+    #[test]
+    fn leaves_control_chords_and_other_keys_alone() {
+        let mut line = LineEdit::new();
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        assert!(!line.handle_key(ctrl_a));
+        assert!(!line.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)));
+        assert!(line.is_empty());
+    }
+}
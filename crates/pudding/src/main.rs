@@ -1,47 +1,191 @@
 mod action;
 mod cli;
+mod commands;
 mod config;
 mod editor;
+mod help;
+mod history;
 mod keybind;
 mod layout;
+mod line_edit;
 mod model;
 mod paths;
 mod runtime;
+mod shell_words;
 mod template;
+mod terminal;
+mod theme;
+mod timestamp;
+mod zellij;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 
 use crate::{
-    cli::{Cli, Command, TemplateCommand},
-    config::Config,
+    cli::{Cli, Command, ConfigCommand, StandaloneCommand, StateCommand, TemplateCommand},
+    commands::{
+        copy_template_command, delete_state_command, diff_templates_command, export_template,
+        import_template, list_states_command, list_templates_command, print_completions,
+        print_config, print_dry_run, rename_template_command, run_doctor, show_state,
+        show_template, validate_template_target,
+    },
+    config::{config_file_path, Config},
     editor::EditorApp,
+    layout::{override_commands, preset},
+    model::Template,
+    paths::resolve_user_path,
     runtime::RuntimeApp,
-    template::load_template,
+    template::{
+        load_state, load_template, load_template_file, record_last_used, resolve_last_used,
+    },
 };
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    let config = Config::load()?;
+    let command = match cli.command {
+        Some(Command::Standalone(command)) => return run_standalone(command, cli.config),
+        Some(Command::WithConfig(command)) => Some(command),
+        None => None,
+    };
+    let config_path = match &cli.config {
+        Some(path) => resolve_user_path(path),
+        None => config_file_path(),
+    };
+    let config = match &cli.config {
+        Some(_) => Config::load_from(&config_path)?,
+        None => Config::load()?,
+    };
+    if cli.print_config {
+        return print_config(&config, &config_path);
+    }
 
-    match cli.command {
+    match command {
         None => run_template("default", &config),
-        Some(Command::Run { template }) => run_template(&template, &config),
-        Some(Command::Template { command }) => match command {
-            TemplateCommand::Edit { name } => {
-                let mut template = load_template(&name)?;
-                template.name = name;
-                let _ = EditorApp::new(template).run()?;
-                Ok(())
+        Some(ConfigCommand::Run {
+            template,
+            file,
+            state,
+            preset,
+            command,
+            only_default,
+            dry_run,
+        }) => {
+            let mut template = match (file, state, preset) {
+                (_, _, Some(preset)) => preset_template(&preset, &config)?,
+                (Some(path), _, _) => load_file_for_run(&path, &config)?,
+                (None, Some(state), _) => {
+                    if let Some(template) = &template {
+                        eprintln!("warning: --state {state} overrides --template {template}");
+                    }
+                    load_state(&state).with_context(|| format!("failed to load state: {state}"))?
+                }
+                (None, None, None) => load_for_run(template.as_deref().unwrap_or("default"))?,
+            };
+            if let Some(command) = &command {
+                let only = only_default.then_some(config.default_command.as_str());
+                for layout in template.layouts_mut() {
+                    override_commands(layout, command, only);
+                }
+            }
+            if dry_run {
+                print_dry_run(&template);
+                return Ok(());
             }
+            launch(template, &config)
+        }
+        Some(ConfigCommand::Edit { last: true, .. }) => {
+            edit_template(&resolve_last_used()?, &config)
+        }
+        Some(ConfigCommand::Edit { name, .. }) => edit_template(&name, &config),
+        Some(ConfigCommand::State { command }) => match command {
+            StateCommand::List { json } => list_states_command(json),
+            StateCommand::Show { name } => show_state(&name),
+            StateCommand::Delete { name, force } => delete_state_command(&name, force),
+        },
+        Some(ConfigCommand::Template { command }) => match command {
+            TemplateCommand::Edit { name } => edit_template(&name, &config),
             TemplateCommand::Apply { name } => run_template(&name, &config),
+            TemplateCommand::Show { name, format } => show_template(&name, format),
+            TemplateCommand::Export {
+                name,
+                format,
+                output,
+                stdout,
+            } => export_template(&name, format, output.as_deref(), stdout),
+            TemplateCommand::Copy { from, to, force } => copy_template_command(&from, &to, force),
+            TemplateCommand::Rename { from, to, force } => {
+                rename_template_command(&from, &to, force)
+            }
+            TemplateCommand::Diff { a, b, kdl, ids } => diff_templates_command(&a, &b, kdl, ids),
+            TemplateCommand::Import { path, name } => import_template(&path, &name, &config),
+            TemplateCommand::List { plain, json } => list_templates_command(plain, json),
+            TemplateCommand::Validate { name_or_path } => {
+                std::process::exit(validate_template_target(&name_or_path))
+            }
         },
     }
 }
 
+fn run_standalone(command: StandaloneCommand, config: Option<String>) -> Result<()> {
+    match command {
+        StandaloneCommand::Doctor { json } => {
+            let path = config.as_deref().map(resolve_user_path);
+            std::process::exit(run_doctor(path.as_deref(), json))
+        }
+        StandaloneCommand::Completions { shell } => {
+            print_completions(shell);
+            Ok(())
+        }
+    }
+}
+
+fn edit_template(name: &str, config: &Config) -> Result<()> {
+    let mut template = load_template(name)?;
+    template.name = name.to_string();
+    note_last_used(name);
+    let _ = EditorApp::new(template, config.clone()).run()?;
+    Ok(())
+}
+
 fn run_template(name: &str, config: &Config) -> Result<()> {
+    launch(load_for_run(name)?, config)
+}
+
+fn load_for_run(name: &str) -> Result<Template> {
     let mut template = load_template(name)?;
     template.name = name.to_string();
+    note_last_used(name);
+    Ok(template)
+}
+
+// The marker only feeds `--last`, so failing to write it must not stop the session.
+fn note_last_used(name: &str) {
+    if let Err(err) = record_last_used(name) {
+        eprintln!("warning: could not record {name} as the last used template: {err:#}");
+    }
+}
+
+fn load_file_for_run(path: &str, config: &Config) -> Result<Template> {
+    let path = resolve_user_path(path);
+    let (template, warnings) = load_template_file(&path, &config.default_command)?;
+    for warning in &warnings {
+        eprintln!("warning: {warning}");
+    }
+    Ok(template)
+}
+
+fn preset_template(name: &str, config: &Config) -> Result<Template> {
+    let layout =
+        preset(name, &config.default_command).with_context(|| format!("unknown preset: {name}"))?;
+    Ok(Template {
+        name: name.to_string(),
+        tabs: Vec::new(),
+        active_tab: 0,
+        layout,
+    })
+}
+
+fn launch(template: Template, config: &Config) -> Result<()> {
     let app = RuntimeApp::new(template, config.clone())?;
     app.run()
 }
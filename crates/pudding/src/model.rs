@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -14,6 +16,19 @@ pub enum Node {
         id: u64,
         name: String,
         command: String,
+        #[serde(default, skip_serializing_if = "is_false")]
+        primary: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cwd: Option<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        env: BTreeMap<String, String>,
+        // Fixed header row drawn inside the pane, separate from the title.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        // Set when pudding filled in `default_command` itself rather than a template naming
+        // a command; only these panes get `shell_args`. Never saved.
+        #[serde(skip)]
+        from_default: bool,
     },
     Spoon {
         id: u64,
@@ -26,11 +41,92 @@ pub enum Node {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Template {
+    pub name: String,
+    // The first tab; a template without `tabs` has only this one.
+    pub layout: Node,
+    // Tabs after the first, in display order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tabs: Vec<Tab>,
+    // 0 is `layout`, n is `tabs[n - 1]`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub active_tab: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tab {
     pub name: String,
     pub layout: Node,
 }
 
+impl Template {
+    pub fn tab_count(&self) -> usize {
+        self.tabs.len() + 1
+    }
+
+    // The first tab is named after the template itself.
+    pub fn tab_name(&self, index: usize) -> &str {
+        match index {
+            0 => &self.name,
+            n => self.tabs.get(n - 1).map_or("", |tab| tab.name.as_str()),
+        }
+    }
+
+    pub fn tab_layout(&self, index: usize) -> Option<&Node> {
+        match index {
+            0 => Some(&self.layout),
+            n => self.tabs.get(n - 1).map(|tab| &tab.layout),
+        }
+    }
+
+    pub fn tab_layout_mut(&mut self, index: usize) -> Option<&mut Node> {
+        match index {
+            0 => Some(&mut self.layout),
+            n => self.tabs.get_mut(n - 1).map(|tab| &mut tab.layout),
+        }
+    }
+
+    pub fn active_layout(&self) -> &Node {
+        self.tab_layout(self.active_tab).unwrap_or(&self.layout)
+    }
+
+    pub fn active_layout_mut(&mut self) -> &mut Node {
+        match self.active_tab.checked_sub(1) {
+            Some(n) if n < self.tabs.len() => &mut self.tabs[n].layout,
+            _ => &mut self.layout,
+        }
+    }
+
+    pub fn layouts(&self) -> impl Iterator<Item = &Node> {
+        std::iter::once(&self.layout).chain(self.tabs.iter().map(|tab| &tab.layout))
+    }
+
+    pub fn layouts_mut(&mut self) -> impl Iterator<Item = &mut Node> {
+        std::iter::once(&mut self.layout).chain(self.tabs.iter_mut().map(|tab| &mut tab.layout))
+    }
+}
+
 impl Node {
+    pub fn bite(id: u64, name: impl Into<String>, command: impl Into<String>) -> Self {
+        Node::Bite {
+            id,
+            name: name.into(),
+            command: command.into(),
+            primary: false,
+            cwd: None,
+            env: BTreeMap::new(),
+            label: None,
+            from_default: false,
+        }
+    }
+
+    pub fn default_bite(id: u64, name: impl Into<String>, default_command: &str) -> Self {
+        let mut bite = Node::bite(id, name, default_command);
+        if let Node::Bite { from_default, .. } = &mut bite {
+            *from_default = true;
+        }
+        bite
+    }
+
     pub fn id(&self) -> u64 {
         match self {
             Node::Bite { id, .. } => *id,
@@ -39,13 +135,19 @@ impl Node {
     }
 }
 
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
 pub fn default_template() -> Template {
     Template {
         name: "default".to_string(),
-        layout: Node::Bite {
-            id: 1,
-            name: "main".to_string(),
-            command: "bash".to_string(),
-        },
+        tabs: Vec::new(),
+        active_tab: 0,
+        layout: Node::bite(1, "main", "bash"),
     }
 }
@@ -10,10 +10,103 @@ pub fn config_dir() -> PathBuf {
     dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
 }
 
+pub fn data_dir() -> PathBuf {
+    if let Ok(dir) = env::var("XDG_DATA_HOME") {
+        return PathBuf::from(dir);
+    }
+    if let Ok(home) = env::var("HOME") {
+        return PathBuf::from(home).join(".local").join("share");
+    }
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+// Precedence: $XDG_STATE_HOME, then $HOME/.local/state, then the home dir from the OS.
+pub fn state_dir() -> PathBuf {
+    state_dir_from(|key| env::var(key).ok())
+}
+
+fn state_dir_from(var: impl Fn(&str) -> Option<String>) -> PathBuf {
+    if let Some(dir) = var("XDG_STATE_HOME") {
+        return PathBuf::from(dir);
+    }
+    if let Some(home) = var("HOME") {
+        return PathBuf::from(home).join(".local").join("state");
+    }
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+// Templates are written by the user, so they stay in the config tree.
 pub fn templates_dir() -> PathBuf {
     config_dir().join("pudding").join("templates")
 }
 
+// Saved runtime states are machine-written, so they live under `state_dir()`
+// (~/.local/state/pudding/states by default) rather than next to the templates.
 pub fn states_dir() -> PathBuf {
-    config_dir().join("pudding").join("states")
+    state_dir().join("pudding").join("states")
+}
+
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = || {
+        env::var("HOME")
+            .map(PathBuf::from)
+            .ok()
+            .or_else(dirs::home_dir)
+    };
+    if path == "~" {
+        return home().unwrap_or_else(|| PathBuf::from(path));
+    }
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = home() {
+            return home.join(rest);
+        }
+    }
+    PathBuf::from(path)
+}
+
+pub fn resolve_user_path(path: &str) -> PathBuf {
+    let expanded = expand_home(path);
+    if expanded.is_absolute() {
+        return expanded;
+    }
+    env::current_dir()
+        .map(|cwd| cwd.join(&expanded))
+        .unwrap_or(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{expand_home, resolve_user_path, state_dir_from};
+
+    #[test]
+    fn expands_tilde_prefix_only() {
+        let home = expand_home("~");
+        assert!(home.is_absolute());
+        assert_eq!(expand_home("~/a/b.kdl"), home.join("a/b.kdl"));
+        assert_eq!(expand_home("a/~/b").to_str(), Some("a/~/b"));
+    }
+
+    #[test]
+    fn xdg_state_home_takes_precedence_over_home() {
+        let both = |key: &str| match key {
+            "XDG_STATE_HOME" => Some("/xdg/state".to_string()),
+            "HOME" => Some("/home/u".to_string()),
+            _ => None,
+        };
+        assert_eq!(state_dir_from(both), PathBuf::from("/xdg/state"));
+        let home_only = |key: &str| (key == "HOME").then(|| "/home/u".to_string());
+        assert_eq!(
+            state_dir_from(home_only),
+            PathBuf::from("/home/u/.local/state")
+        );
+    }
+
+    #[test]
+    fn resolves_relative_against_current_dir() {
+        let resolved = resolve_user_path("layouts/x.kdl");
+        assert!(resolved.is_absolute());
+        assert!(resolved.ends_with("layouts/x.kdl"));
+    }
 }
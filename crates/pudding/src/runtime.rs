@@ -1,133 +1,244 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::HashMap,
+    fs::OpenOptions,
     io::{self, Read, Write},
-    sync::{Arc, Mutex},
-    thread,
-    time::Duration,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
-use anyhow::Result;
-use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+use anyhow::{bail, Result};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
 use portable_pty::{CommandBuilder, PtySize};
 use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Paragraph},
-    Terminal,
 };
 
 use crate::{
-    action::{actions_from_config, Action},
+    action::{help_lines, runtime_actions_from_config, Action},
     config::Config,
+    help::{draw_help, help_size},
+    history::{command_history_path, history_path, History},
     keybind::KeyBinding,
     layout::{
-        collect_bites, layout_rects, next_id, resize_from_bite, split_bite, swap_adjacent_bites,
+        adjacent_bite, balance_spoons, collect_bites, delete_bite, flip_orientations,
+        initial_focus, layout_rects, move_bite, move_bite_toward, next_id, open_below,
+        renumber_ids, resize_from_bite, split_bite, split_grid, swap_adjacent_bites, PaneDirection,
+    },
+    line_edit::LineEdit,
+    model::{Node, Orientation, Tab, Template},
+    paths::{expand_home, resolve_user_path},
+    shell_words::command_argv,
+    template::{
+        ensure_dir_secure, load_state_with_focus, new_tab_name, save_state, save_template,
+        template_path,
     },
-    model::{Node, Orientation, Template},
-    template::{load_state, save_state},
+    terminal::{with_terminal, CrosstermTerminal, TerminalBackend},
+    timestamp::{now_unix, utc_clock},
 };
 
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
 #[path = "runtime_centered_rect.rs"]
 mod runtime_centered_rect;
+#[path = "runtime_click_target.rs"]
+mod runtime_click_target;
+#[path = "runtime_clipboard.rs"]
+mod runtime_clipboard;
+#[path = "runtime_grid_size.rs"]
+mod runtime_grid_size;
+#[path = "runtime_idle.rs"]
+mod runtime_idle;
+#[path = "runtime_key_route.rs"]
+mod runtime_key_route;
 #[path = "runtime_key_to_bytes.rs"]
 mod runtime_key_to_bytes;
+#[path = "runtime_label_rows.rs"]
+mod runtime_label_rows;
 #[path = "runtime_main_area.rs"]
 mod runtime_main_area;
+#[path = "runtime_output_buffer.rs"]
+mod runtime_output_buffer;
+#[path = "runtime_pty_size.rs"]
+mod runtime_pty_size;
+#[path = "runtime_search.rs"]
+mod runtime_search;
+#[path = "runtime_session_name.rs"]
+mod runtime_session_name;
 #[path = "runtime_terminal_size.rs"]
 mod runtime_terminal_size;
+#[path = "runtime_visible_rects.rs"]
+mod runtime_visible_rects;
+#[path = "runtime_wrapped_rows.rs"]
+mod runtime_wrapped_rows;
 
 use runtime_centered_rect::centered_rect;
-use runtime_key_to_bytes::key_to_bytes;
+use runtime_click_target::click_target;
+use runtime_clipboard::copy_to_clipboard;
+use runtime_grid_size::parse_grid_size;
+use runtime_idle::idle_expired;
+use runtime_key_route::{route_key, KeyRoute};
+use runtime_label_rows::{content_rect, label_rows};
 use runtime_main_area::main_area;
+use runtime_output_buffer::{OutputBuffer, OutputLine};
+use runtime_pty_size::{pty_size, screen_pixels, ScreenPixels};
+use runtime_search::Search;
+use runtime_session_name::session_name;
 use runtime_terminal_size::terminal_size;
+use runtime_visible_rects::visible_rects;
+use runtime_wrapped_rows::{clip_for_wrap, wrapped_rows, PANE_WRAP};
+
+// RIS (full reset); the same bytes `tput reset` starts with.
+const TERMINAL_RESET: &[u8] = b"\x1bc";
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+const READER_JOIN_TIMEOUT: Duration = Duration::from_millis(200);
 
-const OUTPUT_LIMIT: usize = 2000;
-const PENDING_CHAR_LIMIT: usize = 8192;
-const RESIZE_STEP_RATIO: f32 = 0.20;
+// How a pane's process has ended, as far as pudding can tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PaneExit {
+    // The child was reaped with this exit code.
+    Code(u32),
+    // The PTY hit EOF (every holder of the terminal closed it) but the child is not
+    // reaped yet, e.g. it closed its output and kept running.
+    Closed,
+}
 
 struct PaneProcess {
     master: Box<dyn portable_pty::MasterPty + Send>,
     writer: Box<dyn Write + Send>,
-    _child: Box<dyn portable_pty::Child + Send>,
-    output: Arc<Mutex<VecDeque<String>>>,
+    child: Box<dyn portable_pty::Child + Send>,
+    output: Arc<Mutex<OutputBuffer>>,
+    // Lines scrolled up from the bottom; kept on the pane so it survives focus changes.
+    scroll: usize,
+    // Long lines wrap onto the next row unless toggled off, in which case they are clipped.
+    wrap: bool,
+    reader: Option<JoinHandle<()>>,
+    // Set by the reader thread when the PTY reaches EOF.
+    eof: Arc<AtomicBool>,
+    // Kept once seen, so the code stays available after the child is reaped.
+    exit_code: Option<u32>,
+    // Left running on drop instead of being killed; see `detach_on_quit`.
+    detached: bool,
 }
 
 impl PaneProcess {
-    fn spawn(command: String, size: PtySize) -> Result<Self> {
+    fn spawn(
+        bite: &Node,
+        size: PtySize,
+        config: &Config,
+        log: Option<Box<dyn Write + Send>>,
+    ) -> Result<Self> {
+        let cmd = pane_command(bite, config)?;
+        let scrollback = config.scrollback_lines;
         let pty_system = portable_pty::native_pty_system();
         let pair = pty_system.openpty(size)?;
-        let mut cmd = CommandBuilder::new(&command);
-        cmd.env("TERM", "xterm-256color");
         let child = pair.slave.spawn_command(cmd)?;
         drop(pair.slave);
 
         let reader = pair.master.try_clone_reader()?;
         let writer = pair.master.take_writer()?;
 
-        let output = Arc::new(Mutex::new(VecDeque::new()));
+        let output = Arc::new(Mutex::new(OutputBuffer::new(scrollback.max(1), log)));
         let output_clone = output.clone();
+        let eof = Arc::new(AtomicBool::new(false));
+        let eof_clone = eof.clone();
 
-        thread::spawn(move || {
+        let reader = thread::spawn(move || {
             let mut reader = reader;
             let mut buf = [0u8; 4096];
-            let mut pending = String::new();
             loop {
                 match reader.read(&mut buf) {
                     Ok(0) => break,
-                    Ok(n) => {
-                        let chunk = String::from_utf8_lossy(&buf[..n]);
-                        let stripped = strip_ansi_escapes::strip(chunk.as_bytes());
-                        let text = String::from_utf8_lossy(&stripped).replace('\r', "");
-                        let mut combined = String::new();
-                        std::mem::swap(&mut combined, &mut pending);
-                        combined.push_str(&text);
-                        let mut lines: Vec<&str> = combined.split('\n').collect();
-                        let last = lines.pop().unwrap_or("");
-                        pending = last.to_string();
-                        if pending.chars().count() > PENDING_CHAR_LIMIT {
-                            pending = pending
-                                .chars()
-                                .rev()
-                                .take(PENDING_CHAR_LIMIT)
-                                .collect::<Vec<_>>()
-                                .into_iter()
-                                .rev()
-                                .collect();
-                        }
-                        let mut guard = output_clone.lock().unwrap();
-                        for line in lines {
-                            guard.push_back(line.to_string());
-                            if guard.len() > OUTPUT_LIMIT {
-                                guard.pop_front();
-                            }
-                        }
-                    }
+                    Ok(n) => output_clone.lock().unwrap().push_bytes(&buf[..n]),
                     Err(_) => break,
                 }
             }
+            eof_clone.store(true, Ordering::Release);
         });
 
         Ok(Self {
             master: pair.master,
             writer,
-            _child: child,
+            child,
             output,
+            scroll: 0,
+            wrap: true,
+            reader: Some(reader),
+            eof,
+            exit_code: None,
+            detached: false,
         })
     }
 
-    fn resize(&mut self, rows: u16, cols: u16) {
-        let _ = self.master.resize(PtySize {
-            rows,
-            cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        });
+    // Read from procfs, so only Linux can follow a `cd` inside the pane.
+    #[cfg(target_os = "linux")]
+    fn current_dir(&self) -> Option<String> {
+        let pid = self.child.process_id()?;
+        let link = std::fs::read_link(format!("/proc/{pid}/cwd")).ok()?;
+        link.to_str().map(str::to_string)
+    }
+
+    // Without procfs, `open_below` falls back to the pane's configured `cwd`.
+    #[cfg(not(target_os = "linux"))]
+    fn current_dir(&self) -> Option<String> {
+        None
+    }
+
+    fn is_running(&mut self) -> bool {
+        self.exit_state().is_none()
+    }
+
+    // Non-blocking; `None` while the process is still attached to its terminal.
+    fn exit_state(&mut self) -> Option<PaneExit> {
+        if self.exit_code.is_none() {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                self.exit_code = Some(status.exit_code());
+            }
+        }
+        match self.exit_code {
+            Some(code) => Some(PaneExit::Code(code)),
+            None if self.eof.load(Ordering::Acquire) => Some(PaneExit::Closed),
+            None => None,
+        }
+    }
+
+    fn kill(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    // The reader ends on EOF once the child is gone, but a grandchild still holding the PTY
+    // can keep it blocked, so it is only joined if it finishes promptly.
+    fn shutdown(&mut self) {
+        if self.detached {
+            return;
+        }
+        self.kill();
+        let Some(reader) = self.reader.take() else {
+            return;
+        };
+        let deadline = Instant::now() + READER_JOIN_TIMEOUT;
+        while !reader.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        if reader.is_finished() {
+            let _ = reader.join();
+        }
+    }
+
+    fn resize(&mut self, size: PtySize) {
+        let _ = self.master.resize(size);
     }
 
     fn write_bytes(&mut self, bytes: &[u8]) {
@@ -135,17 +246,100 @@ impl PaneProcess {
         let _ = self.writer.flush();
     }
 
-    fn lines_for_height(&self, height: usize) -> Vec<String> {
+    // Returns the index of the first line too; `anchor` centers the view on that line.
+    // With wrapping on, a line counts for as many rows as it wraps onto at `width`.
+    fn lines_for_height(
+        &self,
+        height: usize,
+        width: u16,
+        anchor: Option<usize>,
+    ) -> (usize, Vec<OutputLine>) {
+        let guard = self.output.lock().unwrap();
+        let lines = guard.lines();
+        let rows = |line: &OutputLine| {
+            if self.wrap {
+                wrapped_rows(&line.text, width, height)
+            } else {
+                1
+            }
+        };
+        // The earliest start whose remaining lines still fit, i.e. the view when following.
+        let mut bottom = lines.len();
+        let mut used = 0;
+        while bottom > 0 && used + rows(&lines[bottom - 1]) <= height {
+            used += rows(&lines[bottom - 1]);
+            bottom -= 1;
+        }
+        let start = anchor
+            .map(|line| line.saturating_sub(height / 2).min(bottom))
+            .unwrap_or_else(|| bottom.saturating_sub(self.scroll));
+        let mut visible = Vec::new();
+        let mut used = 0;
+        for line in lines.iter().skip(start) {
+            if used >= height {
+                break;
+            }
+            used += rows(line);
+            let mut line = line.clone();
+            if self.wrap {
+                line.text = clip_for_wrap(&line.text, width, height).to_string();
+            }
+            visible.push(line);
+        }
+        (start, visible)
+    }
+
+    // Pasted text goes through as one bracketed write so the child can tell it from typing.
+    fn paste(&mut self, text: &str) {
+        let mut bytes = Vec::with_capacity(PASTE_START.len() + text.len() + PASTE_END.len());
+        bytes.extend_from_slice(PASTE_START);
+        bytes.extend_from_slice(text.as_bytes());
+        bytes.extend_from_slice(PASTE_END);
+        self.write_bytes(&bytes);
+    }
+
+    fn reset_terminal(&mut self) {
+        self.write_bytes(TERMINAL_RESET);
+        self.output.lock().unwrap().clear();
+        self.scroll = 0;
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        let total = self.output.lock().unwrap().lines().len();
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(delta)
+            .min(total.saturating_sub(1));
+    }
+
+    fn scrollback_text(&self) -> (String, usize) {
         let guard = self.output.lock().unwrap();
-        let total = guard.len();
-        let start = total.saturating_sub(height);
-        guard.iter().skip(start).cloned().collect()
+        let lines: Vec<&str> = guard
+            .lines()
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect();
+        (lines.join("\n"), lines.len())
+    }
+
+    fn find_matches(&self, query: &str, case_sensitive: bool) -> Vec<usize> {
+        self.output
+            .lock()
+            .unwrap()
+            .find_matches(query, case_sensitive)
+    }
+}
+
+// Closed and restarted panes are dropped from the map, so the child is reaped here.
+impl Drop for PaneProcess {
+    fn drop(&mut self) {
+        self.shutdown();
     }
 }
 
 struct InputPrompt {
     label: String,
-    buffer: String,
+    buffer: LineEdit,
     mode: PromptMode,
 }
 
@@ -153,6 +347,21 @@ struct InputPrompt {
 enum PromptMode {
     Save,
     Restore,
+    Grid,
+    ConfirmDelete,
+    ConfirmQuit,
+    SaveTemplate,
+    EditCommand,
+    SavePaneOutput,
+    Search { case_sensitive: bool },
+}
+
+// Where each bite would be drawn on the current terminal, without spawning anything.
+pub fn planned_rects(layout: &Node) -> (Rect, Vec<(u64, Rect)>) {
+    let main = main_area(terminal_size());
+    let mut rects = Vec::new();
+    layout_rects(layout, main, &mut rects);
+    (main, rects)
 }
 
 pub struct RuntimeApp {
@@ -160,82 +369,210 @@ pub struct RuntimeApp {
     config: Config,
     actions: HashMap<KeyBinding, Action>,
     panes: HashMap<u64, PaneProcess>,
+    // Panes of the tabs not on screen, with the focus each tab had. They keep running and
+    // buffering output while hidden. A tab that was never shown has no entry here and no
+    // panes yet; it is spawned the first time it is switched to.
+    background: HashMap<usize, (HashMap<u64, PaneProcess>, u64)>,
     active_id: u64,
+    zoomed: bool,
+    search: Option<Search>,
     prompt: Option<InputPrompt>,
+    history: History,
+    command_history: History,
+    help: bool,
     status: String,
 }
 
 impl RuntimeApp {
     pub fn new(template: Template, config: Config) -> Result<Self> {
-        let actions = actions_from_config(&config.keybinds);
+        let actions = runtime_actions_from_config(&config.keybinds);
         let mut app = Self {
-            active_id: template.layout.id(),
+            active_id: initial_focus(template.active_layout()),
             template,
             config,
             actions,
             panes: HashMap::new(),
+            background: HashMap::new(),
+            zoomed: false,
+            search: None,
             prompt: None,
+            history: History::default(),
+            command_history: History::default(),
+            help: false,
             status: "".to_string(),
         };
         app.spawn_all()?;
         Ok(app)
     }
 
+    // Only the tab on screen; the others wait for `switch_tab`.
     fn spawn_all(&mut self) -> Result<()> {
-        let full = terminal_size();
-        let main = main_area(full);
+        self.panes = self.spawn_tab(self.template.active_tab)?;
+        Ok(())
+    }
+
+    // A bite whose cwd is missing stays empty with a status message, and one whose log
+    // cannot be opened runs without it; other spawn failures still abort.
+    fn spawn_tab(&mut self, tab: usize) -> Result<HashMap<u64, PaneProcess>> {
+        let mut panes = HashMap::new();
+        let Some(layout) = self.template.tab_layout(tab) else {
+            return Ok(panes);
+        };
+        let main = main_area(terminal_size());
         let mut rects = Vec::new();
-        layout_rects(&self.template.layout, main, &mut rects);
+        layout_rects(layout, main, &mut rects);
+        let screen = screen_pixels();
+        let mut notice = None;
 
         for (id, rect) in rects {
-            if let Some(Node::Bite { command, .. }) =
-                crate::layout::find_bite(&self.template.layout, id)
-            {
-                let pty_size = PtySize {
-                    rows: rect.height.saturating_sub(2),
-                    cols: rect.width.saturating_sub(2),
-                    pixel_width: 0,
-                    pixel_height: 0,
-                };
-                let pane = PaneProcess::spawn(command.clone(), pty_size)?;
-                self.panes.insert(id, pane);
+            if let Some(bite) = crate::layout::find_bite(layout, id) {
+                if let Err(err) = bite_cwd(bite) {
+                    notice = Some(format!("{err:#}"));
+                    continue;
+                }
+                let log = self.pane_log(tab, id).unwrap_or_else(|message| {
+                    notice = Some(message);
+                    None
+                });
+                let pane =
+                    PaneProcess::spawn(bite, bite_pty_size(bite, rect, screen), &self.config, log)?;
+                panes.insert(id, pane);
             }
         }
-        Ok(())
+        if let Some(message) = notice {
+            self.status = message;
+        }
+        Ok(panes)
     }
 
-    pub fn run(mut self) -> Result<()> {
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen)?;
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
+    // Hidden tabs included, for quitting.
+    fn all_panes_mut(&mut self) -> impl Iterator<Item = &mut PaneProcess> {
+        self.panes.values_mut().chain(
+            self.background
+                .values_mut()
+                .flat_map(|(panes, _)| panes.values_mut()),
+        )
+    }
+
+    fn switch_tab(&mut self, forward: bool) {
+        let count = self.template.tab_count();
+        if count < 2 {
+            self.status = "タブは1つだけです".to_string();
+            return;
+        }
+        let current = self.template.active_tab;
+        let next = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+        self.show_tab(next);
+    }
+
+    // Appended rather than inserted, so hidden tabs keep their indices (and log names).
+    fn new_tab(&mut self) {
+        let name = match new_tab_name(&self.template, &self.config.tab_name_format) {
+            Ok(name) => name,
+            Err(err) => {
+                self.status = format!("タブを追加できません: {err}");
+                return;
+            }
+        };
+        self.template.tabs.push(Tab {
+            name,
+            layout: Node::default_bite(1, "main", &self.config.default_command),
+        });
+        self.show_tab(self.template.tab_count() - 1);
+    }
+
+    // Parks the visible tab's panes and brings tab `next` on screen, spawning it on first view.
+    fn show_tab(&mut self, next: usize) {
+        let count = self.template.tab_count();
+        let current = self.template.active_tab;
+        let panes = std::mem::take(&mut self.panes);
+        self.background.insert(current, (panes, self.active_id));
+        self.template.active_tab = next;
+        self.status = format!(
+            "タブ {}/{}: {}",
+            next + 1,
+            count,
+            self.template.tab_name(next)
+        );
+        let (panes, focus) = match self.background.remove(&next) {
+            Some(parked) => parked,
+            // First view of this tab. A spawn problem replaces the tab status.
+            None => {
+                let focus = initial_focus(self.template.active_layout());
+                let panes = self.spawn_tab(next).unwrap_or_else(|err| {
+                    self.status = format!("起動に失敗: {err}");
+                    HashMap::new()
+                });
+                (panes, focus)
+            }
+        };
+        self.panes = panes;
+        self.active_id = focus;
+        self.zoomed = false;
+        self.search = None;
+        self.resize_all(terminal_size());
+    }
+
+    // The completed lines a bite's pane holds, as the draw sees them (ANSI stripped).
+    #[cfg(test)]
+    pub(crate) fn pane_text(&self, id: u64) -> Vec<String> {
+        self.panes
+            .get(&id)
+            .map(|pane| {
+                let guard = pane.output.lock().unwrap();
+                guard.lines().iter().map(|line| line.text.clone()).collect()
+            })
+            .unwrap_or_default()
+    }
 
-        let res = self.loop_ui(&mut terminal);
+    pub fn run(mut self) -> Result<()> {
+        self.history = History::load(history_path());
+        self.command_history = History::load(command_history_path());
+        self.run_with(&mut CrosstermTerminal::new(true))
+    }
 
-        disable_raw_mode()?;
-        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-        terminal.show_cursor()?;
-        res
+    fn run_with<T: TerminalBackend>(mut self, terminal: &mut T) -> Result<()> {
+        with_terminal(terminal, |terminal| {
+            let res = self.loop_ui(terminal);
+            let detach = self.config.detach_on_quit;
+            for pane in self.all_panes_mut() {
+                if detach {
+                    pane.detached = true;
+                } else {
+                    pane.kill();
+                }
+            }
+            res
+        })
     }
 
-    fn loop_ui(&mut self, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
+    fn loop_ui<T: TerminalBackend>(&mut self, terminal: &mut T) -> Result<()> {
+        let mut last_input = Instant::now();
         loop {
             terminal.draw(|f| self.draw(f))?;
 
-            if event::poll(Duration::from_millis(30))? {
-                match event::read()? {
-                    Event::Key(key) => {
-                        if self.handle_key(key)? {
-                            break;
-                        }
-                    }
+            if terminal.poll(Duration::from_millis(30))? {
+                let event = terminal.read()?;
+                if matches!(event, Event::Key(_) | Event::Mouse(_) | Event::Paste(_)) {
+                    last_input = Instant::now();
+                }
+                match event {
+                    Event::Key(key) if self.handle_key(key)? => break,
+                    Event::Mouse(mouse) => self.handle_mouse(mouse, terminal.size()?),
+                    Event::Paste(text) => self.handle_paste(&text),
                     Event::Resize(_, _) => {
                         self.resize_all(terminal.size()?);
                     }
                     _ => {}
                 }
             }
+            if idle_expired(last_input, Instant::now(), self.config.idle_quit_secs) {
+                break;
+            }
         }
         Ok(())
     }
@@ -249,40 +586,112 @@ impl RuntimeApp {
         let main = chunks[0];
         let status = chunks[1];
 
-        let mut rects = Vec::new();
-        layout_rects(&self.template.layout, main, &mut rects);
+        let rects = visible_rects(self.template.active_layout(), main, self.zoom_target());
 
         for (id, rect) in rects.iter() {
-            let (title, highlight) = match crate::layout::find_bite(&self.template.layout, *id) {
+            let bite = crate::layout::find_bite(self.template.active_layout(), *id);
+            let (mut title, highlight) = match bite {
                 Some(Node::Bite { name, .. }) => (name.clone(), *id == self.active_id),
                 _ => ("".to_string(), *id == self.active_id),
             };
-            let style = if highlight {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
+            let label = bite.and_then(bite_label);
+            let content = content_rect(*rect, label.is_some());
+            let height = content.height.saturating_sub(2) as usize;
+            let width = content.width.saturating_sub(2);
+            let search = self.search.as_ref().filter(|search| search.pane == *id);
+            let (start, lines) = self
+                .panes
+                .get(id)
+                .map(|pane| {
+                    pane.lines_for_height(height, width, search.and_then(Search::current_line))
+                })
+                .unwrap_or_default();
+            let wrap = self.panes.get(id).is_some_and(|pane| pane.wrap);
+            if lines.iter().any(|line| line.lossy) {
+                title.push_str(" [非UTF-8]");
+            }
+            if let Some(pane) = self.panes.get(id).filter(|pane| pane.scroll > 0) {
+                title.push_str(&format!(" [+{}]", pane.scroll));
+            }
+            match self.panes.get_mut(id).and_then(PaneProcess::exit_state) {
+                Some(PaneExit::Code(code)) => title.push_str(&format!(" [終了 (コード {code})]")),
+                Some(PaneExit::Closed) => title.push_str(" [終了]"),
+                None => {}
+            }
+            let block = self.config.theme.pane_block(title, highlight);
+            let inner = block.inner(content);
+            let label_row = Rect {
+                height: 1,
+                ..block.inner(*rect)
             };
-            let block = Block::default()
-                .title(title)
-                .borders(Borders::ALL)
-                .border_style(style);
-            let inner = block.inner(*rect);
             f.render_widget(block, *rect);
+            if let Some(label) = label.filter(|_| label_rows(*rect, true) > 0) {
+                let style = Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED);
+                f.render_widget(Paragraph::new(Line::styled(label, style)), label_row);
+            }
 
-            if let Some(pane) = self.panes.get(id) {
-                let height = rect.height.saturating_sub(2) as usize;
-                let lines = pane.lines_for_height(height);
-                let text = Text::from(lines.into_iter().map(Line::from).collect::<Vec<_>>());
-                f.render_widget(Paragraph::new(text), inner);
+            let text = Text::from(
+                lines
+                    .into_iter()
+                    .enumerate()
+                    .map(|(offset, line)| {
+                        let index = start + offset;
+                        let style = match search {
+                            Some(search) if search.current_line() == Some(index) => {
+                                Style::default().fg(Color::Black).bg(Color::Yellow)
+                            }
+                            Some(search) if search.is_match(index) => {
+                                Style::default().fg(Color::Yellow)
+                            }
+                            _ => Style::default(),
+                        };
+                        Line::styled(line.text, style)
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            let paragraph = Paragraph::new(text);
+            if wrap {
+                f.render_widget(paragraph.wrap(PANE_WRAP), inner);
+            } else {
+                f.render_widget(paragraph, inner);
             }
         }
 
+        // Fixed-width fields come first so a narrow terminal cuts the free-form status instead.
         let mut status_line = vec![Span::raw("[pudding] ")];
+        let tabs = self.template.tab_count();
+        if tabs > 1 {
+            let active = self.template.active_tab;
+            status_line.push(Span::styled(
+                format!(
+                    "[tab {}/{} {}]  ",
+                    active + 1,
+                    tabs,
+                    self.template.tab_name(active)
+                ),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        if self.config.show_clock {
+            status_line.push(Span::raw(format!("{}  ", utc_clock(now_unix()))));
+        }
+        let mut bites = Vec::new();
+        collect_bites(self.template.active_layout(), &mut bites);
+        status_line.push(Span::raw(format!("panes: {}  ", bites.len())));
         if let Some(Node::Bite { name, .. }) =
-            crate::layout::find_bite(&self.template.layout, self.active_id)
+            crate::layout::find_bite(self.template.active_layout(), self.active_id)
         {
             status_line.push(Span::raw(format!("active: {}  ", name)));
         }
+        if self.zoomed {
+            status_line.push(Span::styled("[zoom]  ", Style::default().fg(Color::Yellow)));
+        }
+        if let Some(search) = &self.search {
+            status_line.push(Span::styled(
+                format!("/{} {}  ", search.query, search.counter()),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
         if !self.status.is_empty() {
             status_line.push(Span::raw(self.status.clone()));
         }
@@ -291,13 +700,13 @@ impl RuntimeApp {
 
         if let Some(prompt) = &self.prompt {
             let label = format!("{}: ", prompt.label);
-            let line = Line::from(vec![Span::raw(&label), Span::raw(&prompt.buffer)]);
+            let line = Line::from(vec![Span::raw(&label), Span::raw(prompt.buffer.as_str())]);
             let area = centered_rect(80, 3, area);
             let block = Block::default().borders(Borders::ALL).title("Input");
             f.render_widget(block, area);
             f.render_widget(
                 Paragraph::new(line),
-                ratatui::layout::Rect {
+                Rect {
                     x: area.x + 1,
                     y: area.y + 1,
                     width: area.width - 2,
@@ -305,35 +714,131 @@ impl RuntimeApp {
                 },
             );
             f.set_cursor(
-                area.x + 1 + label.len() as u16 + prompt.buffer.len() as u16,
+                area.x + 1 + Span::raw(&label).width() as u16 + prompt.buffer.cursor_width(),
                 area.y + 1,
             );
         }
+
+        if self.help {
+            let lines = help_lines(&self.actions);
+            let (width, height) = help_size(&lines, area);
+            draw_help(f, centered_rect(width, height, area), &lines);
+        }
     }
 
+    // Help, search and prompts get the key first; with none of them open, every
+    // unbound key (bare Esc included) goes to the active pane.
     fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
-        if let Some(mut prompt) = self.prompt.take() {
-            let close = self.handle_prompt_key(&mut prompt, key);
-            if !close {
-                self.prompt = Some(prompt);
-            }
+        // The overlay swallows whichever key dismisses it.
+        if self.help {
+            self.help = false;
             return Ok(false);
         }
-
-        for (binding, action) in self.actions.iter() {
-            if binding.matches(key) {
-                return Ok(self.handle_action(*action));
+        if self.prompt.is_none() && self.search.is_some() && self.handle_search_key(key) {
+            return Ok(false);
+        }
+        match route_key(key, self.prompt.is_some(), &self.actions) {
+            KeyRoute::Prompt => {
+                if let Some(mut prompt) = self.prompt.take() {
+                    // Any key but y dismisses the confirm and keeps running.
+                    if let PromptMode::ConfirmQuit = prompt.mode {
+                        return Ok(matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')));
+                    }
+                    let close = self.handle_prompt_key(&mut prompt, key);
+                    if !close {
+                        self.prompt = Some(prompt);
+                    }
+                }
+            }
+            KeyRoute::Action(action) => return Ok(self.handle_action(action)),
+            // A finished pane has nobody reading its input, so keys stop there.
+            KeyRoute::Pane(bytes) => {
+                if let Some(pane) = self.panes.get_mut(&self.active_id) {
+                    if pane.is_running() {
+                        pane.scroll = 0;
+                        pane.write_bytes(&bytes);
+                    } else {
+                        self.status = "このペインのプロセスは終了しています".to_string();
+                    }
+                }
             }
+            KeyRoute::Ignore => {}
         }
+        Ok(false)
+    }
 
-        // send to active pane
+    // Prompts take the first pasted line; otherwise the paste goes to the active pane.
+    fn handle_paste(&mut self, text: &str) {
+        if self.help {
+            return;
+        }
+        if let Some(prompt) = self.prompt.as_mut() {
+            if !matches!(
+                prompt.mode,
+                PromptMode::ConfirmDelete | PromptMode::ConfirmQuit
+            ) {
+                let line = text.lines().next().unwrap_or_default();
+                prompt.buffer.insert_str(line);
+            }
+            return;
+        }
         if let Some(pane) = self.panes.get_mut(&self.active_id) {
-            if let Some(bytes) = key_to_bytes(key) {
-                pane.write_bytes(&bytes);
+            if pane.is_running() {
+                pane.scroll = 0;
+                pane.paste(text);
             }
         }
+    }
 
-        Ok(false)
+    // While a search is shown, n/N step through matches and Esc clears it.
+    fn handle_search_key(&mut self, key: KeyEvent) -> bool {
+        let forward = match (key.code, key.modifiers) {
+            (KeyCode::Char('n'), KeyModifiers::NONE) => true,
+            (KeyCode::Char('N'), KeyModifiers::SHIFT) => false,
+            (KeyCode::Esc, _) => {
+                self.search = None;
+                return true;
+            }
+            _ => return false,
+        };
+        if let Some(search) = self.search.as_mut() {
+            if let Some(pane) = self.panes.get(&search.pane) {
+                search.refresh(pane.find_matches(&search.query, search.case_sensitive));
+            }
+            search.step(forward);
+        }
+        true
+    }
+
+    fn start_search(&mut self, query: &str, case_sensitive: bool) {
+        let matches = self
+            .panes
+            .get(&self.active_id)
+            .map(|pane| pane.find_matches(query, case_sensitive))
+            .unwrap_or_default();
+        if matches.is_empty() {
+            self.status = format!("見つかりません: {query}");
+        }
+        self.search = Some(Search::new(
+            self.active_id,
+            query.to_string(),
+            case_sensitive,
+            matches,
+        ));
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent, area: Rect) {
+        if self.prompt.is_some()
+            || self.zoomed
+            || mouse.kind != MouseEventKind::Down(MouseButton::Left)
+        {
+            return;
+        }
+        let main = main_area(area);
+        if let Some(id) = click_target(self.template.active_layout(), main, mouse.column, mouse.row)
+        {
+            self.active_id = id;
+        }
     }
 
     fn handle_action(&mut self, action: Action) -> bool {
@@ -346,99 +851,258 @@ impl RuntimeApp {
             }
             Action::ResizeLeft => {
                 let _ = resize_from_bite(
-                    &mut self.template.layout,
+                    self.template.active_layout_mut(),
                     self.active_id,
                     Orientation::Vertical,
-                    -RESIZE_STEP_RATIO,
+                    -self.config.resize_step,
                 );
                 self.resize_all(terminal_size());
             }
             Action::ResizeRight => {
                 let _ = resize_from_bite(
-                    &mut self.template.layout,
+                    self.template.active_layout_mut(),
                     self.active_id,
                     Orientation::Vertical,
-                    RESIZE_STEP_RATIO,
+                    self.config.resize_step,
                 );
                 self.resize_all(terminal_size());
             }
             Action::ResizeUp => {
                 let _ = resize_from_bite(
-                    &mut self.template.layout,
+                    self.template.active_layout_mut(),
                     self.active_id,
                     Orientation::Horizontal,
-                    -RESIZE_STEP_RATIO,
+                    -self.config.resize_step,
                 );
                 self.resize_all(terminal_size());
             }
             Action::ResizeDown => {
                 let _ = resize_from_bite(
-                    &mut self.template.layout,
+                    self.template.active_layout_mut(),
                     self.active_id,
                     Orientation::Horizontal,
-                    RESIZE_STEP_RATIO,
+                    self.config.resize_step,
                 );
                 self.resize_all(terminal_size());
             }
-            Action::SwapVertical => {
-                let _ = swap_adjacent_bites(
-                    &mut self.template.layout,
+            Action::SwapVertical | Action::SwapHorizontal => {
+                let orientation = match action {
+                    Action::SwapVertical => Orientation::Vertical,
+                    _ => Orientation::Horizontal,
+                };
+                if swap_adjacent_bites(
+                    self.template.active_layout_mut(),
                     self.active_id,
-                    Orientation::Vertical,
-                );
+                    orientation,
+                ) {
+                    self.resize_all(terminal_size());
+                }
             }
-            Action::SwapHorizontal => {
-                let _ = swap_adjacent_bites(
-                    &mut self.template.layout,
-                    self.active_id,
-                    Orientation::Horizontal,
-                );
+            Action::MovePaneForward | Action::MovePaneBackward => {
+                let forward = action == Action::MovePaneForward;
+                if move_bite(self.template.active_layout_mut(), self.active_id, forward) {
+                    self.resize_all(terminal_size());
+                }
+            }
+            Action::MovePaneLeft
+            | Action::MovePaneRight
+            | Action::MovePaneUp
+            | Action::MovePaneDown => {
+                let direction = match action {
+                    Action::MovePaneLeft => PaneDirection::Left,
+                    Action::MovePaneRight => PaneDirection::Right,
+                    Action::MovePaneUp => PaneDirection::Up,
+                    _ => PaneDirection::Down,
+                };
+                if move_bite_toward(self.template.active_layout_mut(), self.active_id, direction) {
+                    self.resize_all(terminal_size());
+                }
+            }
+            Action::BalancePanes => {
+                balance_spoons(self.template.active_layout_mut());
+                self.resize_all(terminal_size());
             }
             Action::SaveState => {
                 self.prompt = Some(InputPrompt {
-                    label: "保存名".to_string(),
-                    buffer: String::new(),
+                    label: "保存名（空なら日時から自動）".to_string(),
+                    buffer: LineEdit::new(),
                     mode: PromptMode::Save,
                 });
             }
+            Action::Search => {
+                self.prompt = Some(InputPrompt {
+                    label: search_label(false),
+                    buffer: LineEdit::new(),
+                    mode: PromptMode::Search {
+                        case_sensitive: false,
+                    },
+                });
+            }
+            Action::SaveAsTemplate => {
+                self.prompt = Some(InputPrompt {
+                    label: "テンプレート名".to_string(),
+                    buffer: LineEdit::new(),
+                    mode: PromptMode::SaveTemplate,
+                });
+            }
             Action::RestoreState => {
                 self.prompt = Some(InputPrompt {
                     label: "復元名".to_string(),
-                    buffer: String::new(),
+                    buffer: LineEdit::new(),
                     mode: PromptMode::Restore,
                 });
             }
+            Action::SplitGrid => {
+                self.prompt = Some(InputPrompt {
+                    label: "グリッド (例: 2x2)".to_string(),
+                    buffer: LineEdit::new(),
+                    mode: PromptMode::Grid,
+                });
+            }
+            Action::DeletePane => {
+                let running = self
+                    .panes
+                    .get_mut(&self.active_id)
+                    .is_some_and(PaneProcess::is_running);
+                if self.config.confirm_delete.needs_confirm(running) {
+                    self.prompt = Some(InputPrompt {
+                        label: "このペインを削除しますか? (y/N)".to_string(),
+                        buffer: LineEdit::new(),
+                        mode: PromptMode::ConfirmDelete,
+                    });
+                } else {
+                    self.delete_active();
+                }
+            }
             Action::FocusNext => {
                 self.focus_next();
+                // In zoom mode this swaps which pane is zoomed, so its PTY needs the full area.
+                if self.zoomed {
+                    self.resize_all(terminal_size());
+                }
+            }
+            Action::FocusLeft | Action::FocusRight | Action::FocusUp | Action::FocusDown => {
+                let direction = match action {
+                    Action::FocusLeft => PaneDirection::Left,
+                    Action::FocusRight => PaneDirection::Right,
+                    Action::FocusUp => PaneDirection::Up,
+                    _ => PaneDirection::Down,
+                };
+                if self.focus_toward(direction) && self.zoomed {
+                    self.resize_all(terminal_size());
+                }
+            }
+            Action::CopyOutput => self.copy_active_output(),
+            Action::SavePaneOutput => {
+                self.prompt = Some(InputPrompt {
+                    label: "出力の保存先".to_string(),
+                    buffer: LineEdit::new(),
+                    mode: PromptMode::SavePaneOutput,
+                });
+            }
+            Action::ScrollUp => self.scroll_active(true),
+            Action::ScrollDown => self.scroll_active(false),
+            Action::RestartPane => {
+                self.restart_active();
+            }
+            Action::EditCommand => {
+                if let Some(Node::Bite { command, .. }) =
+                    crate::layout::find_bite(self.template.active_layout(), self.active_id)
+                {
+                    self.prompt = Some(InputPrompt {
+                        label: "コマンド".to_string(),
+                        buffer: LineEdit::with_text(command.clone()),
+                        mode: PromptMode::EditCommand,
+                    });
+                }
+            }
+            Action::ToggleWrap => {
+                if let Some(pane) = self.panes.get_mut(&self.active_id) {
+                    pane.wrap = !pane.wrap;
+                    self.status = if pane.wrap {
+                        "折り返し: オン".to_string()
+                    } else {
+                        "折り返し: オフ".to_string()
+                    };
+                }
+            }
+            Action::ResetTerminal => {
+                if let Some(pane) = self.panes.get_mut(&self.active_id) {
+                    pane.reset_terminal();
+                }
+                if self
+                    .search
+                    .as_ref()
+                    .is_some_and(|s| s.pane == self.active_id)
+                {
+                    self.search = None;
+                }
+            }
+            Action::OpenBelow => {
+                self.open_below();
+            }
+            Action::ToggleZoom => {
+                self.zoomed = !self.zoomed;
+                self.resize_all(terminal_size());
+            }
+            Action::FlipTab => {
+                flip_orientations(self.template.active_layout_mut());
+                self.resize_all(terminal_size());
+            }
+            Action::NextTab => self.switch_tab(true),
+            Action::PrevTab => self.switch_tab(false),
+            Action::NewTab => self.new_tab(),
+            Action::Help => self.help = true,
+            Action::Quit => {
+                if !self.config.confirm_quit || !self.all_panes_mut().any(PaneProcess::is_running) {
+                    return true;
+                }
+                self.prompt = Some(InputPrompt {
+                    label: "保存せず終了しますか？ (y/n)".to_string(),
+                    buffer: LineEdit::new(),
+                    mode: PromptMode::ConfirmQuit,
+                });
             }
-            Action::Quit => return true,
         }
         false
     }
 
     fn handle_prompt_key(&mut self, prompt: &mut InputPrompt, key: KeyEvent) -> bool {
+        if let PromptMode::ConfirmDelete = prompt.mode {
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.delete_active();
+            }
+            return true;
+        }
         match key.code {
             KeyCode::Enter => {
-                let name = prompt.buffer.trim().to_string();
+                self.prompt_history(prompt.mode)
+                    .record(prompt.buffer.as_str());
+                let name = prompt.buffer.as_str().trim().to_string();
                 match prompt.mode {
                     PromptMode::Save => {
-                        if !name.is_empty() {
-                            match save_state(&name, &self.template) {
-                                Ok(_) => {
-                                    self.status = format!("保存しました: {}", name);
-                                }
-                                Err(err) => {
-                                    self.status = format!("保存に失敗: {err}");
-                                }
+                        let name = if name.is_empty() {
+                            session_name(now_unix())
+                        } else {
+                            name
+                        };
+                        match save_state(&name, &self.template, self.active_id) {
+                            Ok(_) => {
+                                self.status = format!("保存しました: {}", name);
+                            }
+                            Err(err) => {
+                                self.status = format!("保存に失敗: {err}");
                             }
                         }
                     }
                     PromptMode::Restore => {
                         if !name.is_empty() {
-                            match load_state(&name) {
-                                Ok(tpl) => {
+                            match load_state_with_focus(&name) {
+                                Ok((tpl, focus)) => {
                                     self.template = tpl;
+                                    self.active_id = focus;
                                     self.panes.clear();
+                                    self.background.clear();
                                     match self.spawn_all() {
                                         Ok(_) => {
                                             self.status = format!("復元しました: {}", name);
@@ -454,30 +1118,75 @@ impl RuntimeApp {
                             }
                         }
                     }
+                    PromptMode::ConfirmDelete | PromptMode::ConfirmQuit => {}
+                    PromptMode::SaveTemplate => {
+                        if !name.is_empty() {
+                            self.save_as_template(&name);
+                        }
+                    }
+                    PromptMode::Grid => {
+                        if !name.is_empty() {
+                            self.split_active_grid(&name);
+                        }
+                    }
+                    PromptMode::EditCommand => {
+                        if !name.is_empty() {
+                            self.edit_active_command(&name);
+                        }
+                    }
+                    PromptMode::SavePaneOutput => {
+                        if !name.is_empty() {
+                            self.save_active_output(&name);
+                        }
+                    }
+                    PromptMode::Search { case_sensitive } => {
+                        if !prompt.buffer.is_empty() {
+                            self.start_search(prompt.buffer.as_str(), case_sensitive);
+                        }
+                    }
                 }
                 return true;
             }
-            KeyCode::Esc => {
-                return true;
+            KeyCode::Tab => {
+                if let PromptMode::Search { case_sensitive } = prompt.mode {
+                    prompt.mode = PromptMode::Search {
+                        case_sensitive: !case_sensitive,
+                    };
+                    prompt.label = search_label(!case_sensitive);
+                }
             }
-            KeyCode::Backspace => {
-                prompt.buffer.pop();
+            KeyCode::Up => {
+                if let Some(entry) = self
+                    .prompt_history(prompt.mode)
+                    .older(prompt.buffer.as_str())
+                {
+                    prompt.buffer = LineEdit::with_text(entry);
+                }
             }
-            KeyCode::Char(c) => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    return false;
+            KeyCode::Down => {
+                if let Some(entry) = self.prompt_history(prompt.mode).newer() {
+                    prompt.buffer = LineEdit::with_text(entry);
                 }
-                prompt.buffer.push(c);
             }
-            _ => {}
+            // While recalling history, Esc first brings back what was being typed.
+            KeyCode::Esc => match self.prompt_history(prompt.mode).restore() {
+                Some(draft) => prompt.buffer = LineEdit::with_text(draft),
+                None => return true,
+            },
+            _ => {
+                prompt.buffer.handle_key(key);
+            }
         }
         false
     }
 
     fn split_active(&mut self, orientation: Orientation) {
-        let new_id = next_id(&self.template.layout);
+        if !self.reserve_panes(1) {
+            return;
+        }
+        let new_id = next_id(self.template.active_layout());
         let did = split_bite(
-            &mut self.template.layout,
+            self.template.active_layout_mut(),
             self.active_id,
             orientation,
             0.5,
@@ -485,54 +1194,1218 @@ impl RuntimeApp {
             &self.config.default_command,
         );
         if did {
-            let full = terminal_size();
-            let size = main_area(full);
-            let mut rects = Vec::new();
-            layout_rects(&self.template.layout, size, &mut rects);
-            if let Some((_, rect)) = rects.iter().find(|(id, _)| *id == new_id) {
-                if let Some(Node::Bite { command, .. }) =
-                    crate::layout::find_bite(&self.template.layout, new_id)
-                {
-                    let pane = PaneProcess::spawn(
-                        command.clone(),
-                        PtySize {
-                            rows: rect.height.saturating_sub(2),
-                            cols: rect.width.saturating_sub(2),
-                            pixel_width: 0,
-                            pixel_height: 0,
-                        },
-                    );
-                    if let Ok(pane) = pane {
-                        self.panes.insert(new_id, pane);
-                    }
-                }
-            }
-            self.resize_all(full);
+            self.spawn_new(new_id);
         }
     }
 
-    fn focus_next(&mut self) {
-        let mut ids = Vec::new();
-        collect_bites(&self.template.layout, &mut ids);
-        if ids.is_empty() {
+    fn split_active_grid(&mut self, input: &str) {
+        let (rows, cols) = match parse_grid_size(input) {
+            Ok(size) => size,
+            Err(err) => {
+                self.status = format!("グリッドの指定が不正: {err}");
+                return;
+            }
+        };
+        if !self.reserve_panes(rows * cols - 1) {
             return;
         }
-        if let Some(pos) = ids.iter().position(|id| *id == self.active_id) {
-            let next = (pos + 1) % ids.len();
-            self.active_id = ids[next];
-        } else {
-            self.active_id = ids[0];
+        if let Some(created) = split_grid(
+            self.template.active_layout_mut(),
+            self.active_id,
+            rows,
+            cols,
+            &self.config.default_command,
+        ) {
+            for id in created {
+                self.spawn_new(id);
+            }
         }
     }
 
-    fn resize_all(&mut self, area: ratatui::layout::Rect) {
-        let area = main_area(area);
-        let mut rects = Vec::new();
-        layout_rects(&self.template.layout, area, &mut rects);
-        for (id, rect) in rects {
-            if let Some(pane) = self.panes.get_mut(&id) {
-                pane.resize(rect.height.saturating_sub(2), rect.width.saturating_sub(2));
-            }
+    fn save_as_template(&mut self, name: &str) {
+        if template_path(name).exists() {
+            self.status = format!("同名のテンプレートが既にあります: {name}");
+            return;
         }
+        let mut template = self.template.clone();
+        template.name = name.to_string();
+        template.active_tab = 0;
+        for layout in template.layouts_mut() {
+            renumber_ids(layout);
+        }
+        match save_template(&template) {
+            Ok(_) => self.status = format!("テンプレートとして保存しました: {name}"),
+            Err(err) => self.status = format!("テンプレート保存に失敗: {err}"),
+        }
+    }
+
+    fn delete_active(&mut self) {
+        let closing = self.active_id;
+        match delete_bite(self.template.active_layout_mut(), closing) {
+            Ok(focus) => {
+                if let Some(pane) = self.panes.remove(&closing) {
+                    if let Err(err) = self.archive_pane(closing, &pane) {
+                        self.status = format!("出力の保存に失敗: {err}");
+                    }
+                }
+                self.active_id = focus;
+                self.resize_all(terminal_size());
+            }
+            Err(err) => {
+                self.status = format!("削除できません: {err}");
+            }
+        }
+    }
+
+    fn open_below(&mut self) {
+        if !self.reserve_panes(1) {
+            return;
+        }
+        let live_cwd = self
+            .panes
+            .get(&self.active_id)
+            .and_then(PaneProcess::current_dir);
+        if let Some(new_id) = open_below(
+            self.template.active_layout_mut(),
+            self.active_id,
+            &self.config.default_command,
+            live_cwd,
+        ) {
+            self.spawn_new(new_id);
+            self.active_id = new_id;
+        }
+    }
+
+    // Checked before the layout changes, so a refused split leaves the tree untouched.
+    fn reserve_panes(&mut self, count: usize) -> bool {
+        let mut ids = Vec::new();
+        collect_bites(self.template.active_layout(), &mut ids);
+        if ids.len() + count > self.config.max_panes {
+            self.status = format!("ペイン数の上限（{}）に達しています", self.config.max_panes);
+            return false;
+        }
+        true
+    }
+
+    fn spawn_new(&mut self, new_id: u64) {
+        if let Some(rect) = self.pane_rect(new_id) {
+            if let Some(bite) = crate::layout::find_bite(self.template.active_layout(), new_id) {
+                let log = self
+                    .pane_log(self.template.active_tab, new_id)
+                    .unwrap_or_else(|message| {
+                        self.status = message;
+                        None
+                    });
+                match PaneProcess::spawn(
+                    bite,
+                    bite_pty_size(bite, rect, screen_pixels()),
+                    &self.config,
+                    log,
+                ) {
+                    Ok(pane) => {
+                        self.panes.insert(new_id, pane);
+                    }
+                    Err(err) => {
+                        self.status = format!("起動に失敗: {err}");
+                    }
+                }
+            }
+        }
+        self.resize_all(terminal_size());
+    }
+
+    // Edited commands recall only each other, not state names or search queries.
+    fn prompt_history(&mut self, mode: PromptMode) -> &mut History {
+        match mode {
+            PromptMode::EditCommand => &mut self.command_history,
+            _ => &mut self.history,
+        }
+    }
+
+    fn edit_active_command(&mut self, new_command: &str) {
+        if let Some(Node::Bite {
+            command,
+            from_default,
+            ..
+        }) = crate::layout::find_bite_mut(self.template.active_layout_mut(), self.active_id)
+        {
+            *command = new_command.to_string();
+            *from_default = false;
+            self.restart_active();
+        }
+    }
+
+    // The old child is killed when its pane drops, and the new one starts with an empty
+    // scrollback, so a search over the old output is dropped too.
+    fn restart_active(&mut self) {
+        let id = self.active_id;
+        let Some(bite @ Node::Bite { name, .. }) =
+            crate::layout::find_bite(self.template.active_layout(), id)
+        else {
+            return;
+        };
+        let Some(rect) = self.pane_rect(id) else {
+            return;
+        };
+        let wrap = self.panes.remove(&id).is_none_or(|pane| pane.wrap);
+        if self.search.as_ref().is_some_and(|search| search.pane == id) {
+            self.search = None;
+        }
+        let (log, log_error) = match self.pane_log(self.template.active_tab, id) {
+            Ok(log) => (log, None),
+            Err(message) => (None, Some(message)),
+        };
+        match PaneProcess::spawn(
+            bite,
+            bite_pty_size(bite, rect, screen_pixels()),
+            &self.config,
+            log,
+        ) {
+            Ok(mut pane) => {
+                pane.wrap = wrap;
+                self.panes.insert(id, pane);
+                self.status = log_error.unwrap_or_else(|| format!("再起動しました: {}", name));
+            }
+            Err(err) => {
+                self.status = format!("再起動に失敗: {err}");
+            }
+        }
+    }
+
+    // Ids are only unique within a tab, so panes past the first tab carry its number too.
+    fn pane_file_stem(&self, tab: usize, id: u64) -> String {
+        match tab {
+            0 => format!("{}-{}", self.template.name, id),
+            tab => format!("{}-tab{}-{}", self.template.name, tab + 1, id),
+        }
+    }
+
+    // `Ok(None)` when logging is off. The error is a status message: the terminal is in
+    // raw mode, and the pane still starts without a log.
+    fn pane_log(&self, tab: usize, id: u64) -> Result<Option<Box<dyn Write + Send>>, String> {
+        let Some(dir) = self.config.pane_log_dir.as_deref() else {
+            return Ok(None);
+        };
+        let dir = crate::paths::expand_home(dir);
+        let path = dir.join(format!("{}.log", self.pane_file_stem(tab, id)));
+        match open_log_file(&dir, &path) {
+            Ok(file) => Ok(Some(Box::new(file))),
+            Err(err) => Err(format!("ログを開けません: {}: {err}", path.display())),
+        }
+    }
+
+    // Writes the closed pane's scrollback next to the live logs, as `<template>-<id>-closed.log`.
+    fn archive_pane(&self, id: u64, pane: &PaneProcess) -> io::Result<()> {
+        let Some(dir) = self.config.closed_pane_archive_dir.as_deref() else {
+            return Ok(());
+        };
+        let dir = crate::paths::expand_home(dir);
+        let stem = self.pane_file_stem(self.template.active_tab, id);
+        let path = dir.join(format!("{stem}-closed.log"));
+        let (text, _) = pane.scrollback_text();
+        let mut file = open_log_file(&dir, &path)?;
+        writeln!(file, "{text}")
+    }
+
+    fn pane_rect(&self, target_id: u64) -> Option<Rect> {
+        let area = main_area(terminal_size());
+        visible_rects(self.template.active_layout(), area, self.zoom_target())
+            .into_iter()
+            .find(|(id, _)| *id == target_id)
+            .map(|(_, rect)| rect)
+    }
+
+    fn scroll_active(&mut self, up: bool) {
+        let page = self
+            .pane_rect(self.active_id)
+            .map(|rect| (rect.height.saturating_sub(2) / 2).max(1) as isize)
+            .unwrap_or(1);
+        if let Some(pane) = self.panes.get_mut(&self.active_id) {
+            pane.scroll_by(if up { page } else { -page });
+        }
+    }
+
+    fn copy_active_output(&mut self) {
+        let Some(pane) = self.panes.get(&self.active_id) else {
+            return;
+        };
+        let (text, count) = pane.scrollback_text();
+        self.status = match copy_to_clipboard(&text) {
+            Ok(()) => format!("出力をクリップボードにコピーしました（{count} 行）"),
+            Err(err) => format!("クリップボードにコピーできません: {err}"),
+        };
+    }
+
+    // A one-off dump of the scrollback, unlike `pane_log_dir`. Existing files are left
+    // alone rather than overwritten.
+    fn save_active_output(&mut self, target: &str) {
+        let Some(pane) = self.panes.get(&self.active_id) else {
+            return;
+        };
+        let path = resolve_user_path(target);
+        let (text, count) = pane.scrollback_text();
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let written = options
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "{text}"));
+        self.status = match written {
+            Ok(()) => format!("出力を保存しました: {}（{count} 行）", path.display()),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                format!("既にあるファイルには保存しません: {}", path.display())
+            }
+            Err(err) => format!("出力の保存に失敗: {}: {err}", path.display()),
+        };
+    }
+
+    fn focus_next(&mut self) {
+        let mut ids = Vec::new();
+        collect_bites(self.template.active_layout(), &mut ids);
+        if ids.is_empty() {
+            return;
+        }
+        if let Some(pos) = ids.iter().position(|id| *id == self.active_id) {
+            let next = (pos + 1) % ids.len();
+            self.active_id = ids[next];
+        } else {
+            self.active_id = ids[0];
+        }
+    }
+
+    // Moves focus to the pane on screen next to the active one, judged on the full layout
+    // even while zoomed. Returns false at the edge.
+    fn focus_toward(&mut self, direction: PaneDirection) -> bool {
+        let area = main_area(terminal_size());
+        match adjacent_bite(
+            self.template.active_layout(),
+            area,
+            self.active_id,
+            direction,
+        ) {
+            Some(id) => {
+                self.active_id = id;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn zoom_target(&self) -> Option<u64> {
+        self.zoomed.then_some(self.active_id)
+    }
+
+    fn resize_all(&mut self, area: Rect) {
+        let area = main_area(area);
+        let rects = visible_rects(self.template.active_layout(), area, self.zoom_target());
+        let screen = screen_pixels();
+        for (id, rect) in rects {
+            let bite = crate::layout::find_bite(self.template.active_layout(), id);
+            if let (Some(bite), Some(pane)) = (bite, self.panes.get_mut(&id)) {
+                pane.resize(bite_pty_size(bite, rect, screen));
+            }
+        }
+    }
+}
+
+fn bite_label(bite: &Node) -> Option<&str> {
+    match bite {
+        Node::Bite { label, .. } => label.as_deref(),
+        Node::Spoon { .. } => None,
+    }
+}
+
+fn bite_pty_size(bite: &Node, rect: Rect, screen: ScreenPixels) -> PtySize {
+    pty_size(content_rect(rect, bite_label(bite).is_some()), screen)
+}
+
+// The bite's cwd with `~` expanded. A directory that does not exist is refused up front,
+// so the caller can report it instead of the spawn failing inside the PTY.
+fn bite_cwd(bite: &Node) -> Result<Option<PathBuf>> {
+    let Node::Bite { cwd: Some(cwd), .. } = bite else {
+        return Ok(None);
+    };
+    let path = expand_home(cwd);
+    if !path.is_dir() {
+        bail!("作業ディレクトリがありません: {cwd}");
+    }
+    Ok(Some(path))
+}
+
+// The command is split into argv by `command_argv`. `shell_args` only apply to panes
+// pudding gave the default command, never to commands a template spells out explicitly.
+fn pane_command(bite: &Node, config: &Config) -> Result<CommandBuilder> {
+    let Node::Bite {
+        command,
+        env,
+        from_default,
+        ..
+    } = bite
+    else {
+        bail!("only bites can be spawned");
+    };
+    let mut argv = command_argv(command);
+    if *from_default {
+        argv.extend(config.shell_args.iter().cloned());
+    }
+    let mut cmd = if config.detach_on_quit {
+        // portable-pty already starts each child in its own session; ignoring SIGHUP as
+        // well lets it outlive the PTY closing when pudding exits. The disposition
+        // survives `exec`, though a program may still install its own handler.
+        let mut cmd = CommandBuilder::new("/bin/sh");
+        cmd.args(["-c", "trap '' HUP; exec \"$@\"", "pudding-pane"]);
+        cmd.args(&argv);
+        cmd
+    } else {
+        CommandBuilder::from_argv(argv.into_iter().map(Into::into).collect())
+    };
+    cmd.env("TERM", "xterm-256color");
+    for (key, value) in env {
+        cmd.env(key, value);
+    }
+    if let Some(cwd) = bite_cwd(bite)? {
+        cmd.cwd(cwd);
+    }
+    Ok(cmd)
+}
+
+fn open_log_file(dir: &Path, path: &Path) -> io::Result<std::fs::File> {
+    ensure_dir_secure(dir)?;
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options.open(path)
+}
+
+fn search_label(case_sensitive: bool) -> String {
+    let case = if case_sensitive {
+        "大小区別"
+    } else {
+        "大小無視"
+    };
+    format!("検索（{case}・Tabで切替）")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{self, Write},
+        ops::{Deref, DerefMut},
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::{
+        bite_cwd, pane_command, PaneExit, PaneProcess, RuntimeApp, PASTE_END, PASTE_START,
+        TERMINAL_RESET,
+    };
+    use crate::action::Action;
+    use crate::config::{Config, ConfirmDelete};
+    use crate::layout::collect_bites;
+    use crate::model::{Node, Orientation, Tab, Template};
+    use crate::paths::expand_home;
+    use crate::terminal::ScriptedTerminal;
+
+    // Kills every pane, hidden tabs included, when the test ends, even after a failed
+    // assertion.
+    struct TestApp(RuntimeApp);
+
+    impl Deref for TestApp {
+        type Target = RuntimeApp;
+
+        fn deref(&self) -> &RuntimeApp {
+            &self.0
+        }
+    }
+
+    impl DerefMut for TestApp {
+        fn deref_mut(&mut self) -> &mut RuntimeApp {
+            &mut self.0
+        }
+    }
+
+    impl Drop for TestApp {
+        fn drop(&mut self) {
+            for pane in self.0.all_panes_mut() {
+                pane.kill();
+            }
+        }
+    }
+
+    fn template(layout: Node) -> Template {
+        Template {
+            name: "test".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout,
+        }
+    }
+
+    fn cat_config() -> Config {
+        Config {
+            default_command: "cat".to_string(),
+            ..Config::default()
+        }
+    }
+
+    fn test_app(layout: Node, config: Config) -> TestApp {
+        TestApp(RuntimeApp::new(template(layout), config).unwrap())
+    }
+
+    fn cat_app(layout: Node) -> TestApp {
+        test_app(layout, cat_config())
+    }
+
+    #[test]
+    fn scripted_keys_split_and_quit_through_the_loop() {
+        let mut app = cat_app(Node::bite(1, "main", "cat"));
+        let mut terminal = ScriptedTerminal::new(
+            80,
+            24,
+            vec![
+                ScriptedTerminal::key(KeyCode::Char('v'), KeyModifiers::NONE),
+                ScriptedTerminal::key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+                ScriptedTerminal::key(KeyCode::Char('y'), KeyModifiers::NONE),
+            ],
+        );
+
+        app.loop_ui(&mut terminal).unwrap();
+
+        let mut ids = Vec::new();
+        collect_bites(&app.template.layout, &mut ids);
+        assert_eq!(ids.len(), 2);
+        assert_eq!(app.panes.len(), 2);
+        assert!(matches!(app.template.layout, Node::Spoon { .. }));
+    }
+
+    #[test]
+    fn user_quit_binding_ends_the_loop() {
+        let mut config = cat_config();
+        config.keybinds.insert("quit".to_string(), "q".to_string());
+        let mut app = test_app(Node::bite(1, "main", "cat"), config);
+        let mut terminal = ScriptedTerminal::new(
+            80,
+            24,
+            vec![
+                ScriptedTerminal::key(KeyCode::Char('q'), KeyModifiers::NONE),
+                ScriptedTerminal::key(KeyCode::Char('y'), KeyModifiers::NONE),
+            ],
+        );
+        app.loop_ui(&mut terminal).unwrap();
+    }
+
+    #[test]
+    fn scroll_offset_survives_focus_switching() {
+        let mut app = cat_app(Node::bite(1, "main", "cat"));
+        app.split_active(Orientation::Vertical);
+        let first = app.active_id;
+        let lines: String = (0..200).map(|i| format!("line {i}\n")).collect();
+        app.panes[&first]
+            .output
+            .lock()
+            .unwrap()
+            .push_bytes(lines.as_bytes());
+
+        app.scroll_active(true);
+        let scrolled = app.panes[&first].scroll;
+        app.focus_next();
+        assert_ne!(app.active_id, first);
+        app.scroll_active(true);
+        app.focus_next();
+
+        assert_eq!(app.active_id, first);
+        assert!(scrolled > 0);
+        assert_eq!(app.panes[&first].scroll, scrolled);
+    }
+
+    #[test]
+    fn split_past_max_panes_is_rejected() {
+        let config = Config {
+            default_command: "cat".to_string(),
+            max_panes: 1,
+            ..Config::default()
+        };
+        let mut app = test_app(Node::bite(1, "main", "cat"), config);
+        app.split_active(Orientation::Vertical);
+        app.split_active_grid("2x2");
+        app.open_below();
+
+        assert!(matches!(app.template.layout, Node::Bite { id: 1, .. }));
+        assert_eq!(app.panes.len(), 1);
+        assert!(app.status.contains('1'));
+    }
+
+    #[test]
+    fn copy_output_reports_failure_in_status() {
+        let mut app = cat_app(Node::bite(1, "main", "cat"));
+        app.panes[&1]
+            .output
+            .lock()
+            .unwrap()
+            .push_bytes(b"one\ntwo\n");
+        let (text, count) = app.panes[&1].scrollback_text();
+        let quit = app.handle_action(Action::CopyOutput);
+
+        assert_eq!((text.as_str(), count), ("one\ntwo", 2));
+        assert!(!quit);
+        if cfg!(not(feature = "clipboard")) {
+            assert!(app.status.starts_with("クリップボードにコピーできません"));
+        }
+    }
+
+    #[test]
+    fn focus_next_in_zoom_mode_changes_the_zoomed_pane() {
+        let mut app = cat_app(Node::bite(1, "main", "cat"));
+        app.split_active(Orientation::Vertical);
+        app.handle_action(Action::ToggleZoom);
+        let before = app.zoom_target();
+        app.handle_action(Action::FocusNext);
+        let after = app.zoom_target();
+
+        assert!(app.zoomed);
+        assert!(before.is_some() && after.is_some());
+        assert_ne!(before, after);
+        assert_eq!(after, Some(app.active_id));
+    }
+
+    #[test]
+    fn closing_a_pane_archives_its_scrollback() {
+        let dir = std::env::temp_dir().join(format!("pudding-archive-{}", std::process::id()));
+        let config = Config {
+            default_command: "cat".to_string(),
+            closed_pane_archive_dir: Some(dir.display().to_string()),
+            ..Config::default()
+        };
+        let mut app = test_app(Node::bite(1, "main", "cat"), config);
+        app.split_active(Orientation::Vertical);
+        let closing = app.active_id;
+        app.panes[&closing]
+            .output
+            .lock()
+            .unwrap()
+            .push_bytes(b"build ok\n");
+        app.delete_active();
+
+        let path = dir.join(format!("test-{closing}-closed.log"));
+        let archived = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(archived, "build ok\n");
+        assert_eq!(app.panes.len(), 1);
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reset_terminal_writes_reset_and_clears_view() {
+        let mut app = cat_app(Node::bite(1, "main", "sleep 100"));
+        let written = SharedWriter::default();
+        let pane = app.panes.get_mut(&1).unwrap();
+        pane.writer = Box::new(written.clone());
+        pane.output.lock().unwrap().push_bytes(b"garbage\n");
+        pane.scroll = 1;
+        app.handle_action(Action::ResetTerminal);
+        let (text, count) = app.panes[&1].scrollback_text();
+        let scroll = app.panes[&1].scroll;
+
+        assert_eq!(written.0.lock().unwrap().as_slice(), TERMINAL_RESET);
+        assert_eq!((text.as_str(), count, scroll), ("", 0, 0));
+    }
+
+    #[test]
+    fn paste_event_is_bracketed_and_written_once() {
+        let mut app = cat_app(Node::bite(1, "main", "sleep 100"));
+        let written = SharedWriter::default();
+        // Dropping the PTY writer sends EOF, which would end `cat` and with it key
+        // forwarding; `sleep` ignores its input and keeps the pane running.
+        app.panes.get_mut(&1).unwrap().writer = Box::new(written.clone());
+        let mut terminal = ScriptedTerminal::new(
+            80,
+            24,
+            vec![
+                Event::Paste("echo a\necho b".to_string()),
+                ScriptedTerminal::key(KeyCode::Char('x'), KeyModifiers::NONE),
+            ],
+        );
+        let _ = app.loop_ui(&mut terminal);
+
+        let mut expected = PASTE_START.to_vec();
+        expected.extend_from_slice(b"echo a\necho b");
+        expected.extend_from_slice(PASTE_END);
+        expected.push(b'x');
+        assert_eq!(*written.0.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn bare_esc_reaches_the_pane_only_without_modal_state() {
+        let mut app = cat_app(Node::bite(1, "main", "sleep 100"));
+        let written = SharedWriter::default();
+        app.panes.get_mut(&1).unwrap().writer = Box::new(written.clone());
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+
+        app.handle_key(esc).unwrap();
+        let forwarded = written.0.lock().unwrap().clone();
+        app.handle_action(Action::SaveState);
+        app.handle_key(esc).unwrap();
+        let prompt_closed = app.prompt.is_none();
+        let after_prompt = written.0.lock().unwrap().clone();
+
+        assert_eq!(forwarded, vec![0x1b]);
+        assert!(prompt_closed);
+        assert_eq!(after_prompt, vec![0x1b]);
+    }
+
+    #[test]
+    fn edited_commands_restart_the_pane_and_recall_only_each_other() {
+        let mut app = cat_app(Node::bite(1, "main", "cat"));
+        app.history.record("session-a");
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        app.handle_action(Action::EditCommand);
+        let prefilled = app.prompt.as_ref().unwrap().buffer.as_str().to_string();
+        for _ in 0..3 {
+            app.handle_key(key(KeyCode::Backspace)).unwrap();
+        }
+        for c in "head".chars() {
+            app.handle_key(key(KeyCode::Char(c))).unwrap();
+        }
+        app.handle_key(key(KeyCode::Enter)).unwrap();
+        let edited = matches!(
+            &app.template.layout,
+            Node::Bite { command, .. } if command == "head"
+        );
+
+        app.handle_action(Action::EditCommand);
+        app.handle_key(key(KeyCode::Up)).unwrap();
+        app.handle_key(key(KeyCode::Up)).unwrap();
+        let recalled = app.prompt.as_ref().unwrap().buffer.as_str().to_string();
+
+        assert_eq!(prefilled, "cat");
+        assert!(edited);
+        assert_eq!(app.status, "再起動しました: main");
+        assert_eq!(recalled, "head");
+    }
+
+    #[test]
+    fn tabs_spawn_on_first_view_and_keep_running_hidden() {
+        let template = Template {
+            name: "dev".to_string(),
+            tabs: vec![Tab {
+                name: "logs".to_string(),
+                layout: Node::bite(1, "tail", "cat"),
+            }],
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.5,
+                first: Box::new(Node::bite(2, "left", "cat")),
+                second: Box::new(Node::bite(3, "right", "cat")),
+            },
+        };
+        let mut app = TestApp(RuntimeApp::new(template, cat_config()).unwrap());
+        app.active_id = 3;
+        let unviewed = app.background.is_empty() && !app.panes.contains_key(&1);
+
+        app.handle_action(Action::NextTab);
+        let status = app.status.clone();
+        let shown: Vec<u64> = app.panes.keys().copied().collect();
+        app.handle_action(Action::PrevTab);
+        let back = (app.template.active_tab, app.active_id, app.panes.len());
+        let (hidden, focus) = app.background.get_mut(&1).unwrap();
+        let hidden_focus = *focus;
+        hidden.get_mut(&1).unwrap().write_bytes(b"while hidden\n");
+        app.handle_action(Action::NextTab);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !app.pane_text(1).iter().any(|line| line == "while hidden")
+            && Instant::now() < deadline
+        {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let buffered = app.pane_text(1);
+
+        assert!(unviewed, "the second tab spawned before it was shown");
+        assert_eq!(status, "タブ 2/2: logs");
+        assert_eq!(shown, vec![1]);
+        assert_eq!(back, (0, 3, 2));
+        assert_eq!(hidden_focus, 1);
+        assert!(buffered.iter().any(|line| line == "while hidden"));
+    }
+
+    #[test]
+    fn new_tab_appends_a_tab_named_by_the_configured_format() {
+        let config = Config {
+            tab_name_format: "{template}-{n}".to_string(),
+            ..cat_config()
+        };
+        let mut template = template(Node::bite(1, "main", "cat"));
+        template.name = "dev".to_string();
+        let mut app = TestApp(RuntimeApp::new(template, config).unwrap());
+
+        app.handle_action(Action::NewTab);
+
+        assert_eq!(app.template.tab_name(1), "dev-2");
+        assert_eq!(app.template.active_tab, 1);
+        assert_eq!(app.status, "タブ 2/2: dev-2");
+        assert!(app.background.contains_key(&0));
+        assert_eq!(app.panes.len(), 1);
+    }
+
+    #[test]
+    fn pane_text_snapshots_finished_output() {
+        let app = cat_app(Node::bite(1, "main", "echo pudding-snapshot"));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.pane_text(1).is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let text = app.pane_text(1);
+        let missing = app.pane_text(99);
+
+        assert_eq!(text, vec!["pudding-snapshot"]);
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn long_lines_wrap_until_toggled_off() {
+        let mut app = cat_app(Node::bite(1, "main", "cat"));
+        let long = "x".repeat(30);
+        app.panes[&1]
+            .output
+            .lock()
+            .unwrap()
+            .push_bytes(format!("{long}\ntail\n").as_bytes());
+        let mut terminal = Terminal::new(TestBackend::new(20, 8)).unwrap();
+        let mut screen = |app: &mut RuntimeApp| {
+            terminal.draw(|f| app.draw(f)).unwrap();
+            let cells = terminal.backend().buffer().content();
+            cells.iter().map(|cell| cell.symbol()).collect::<String>()
+        };
+        let wrapped = screen(&mut app);
+        app.handle_action(Action::ToggleWrap);
+        let clipped = screen(&mut app);
+        let wrap = app.panes[&1].wrap;
+
+        assert_eq!(wrapped.matches('x').count(), 30);
+        assert_eq!(clipped.matches('x').count(), 18);
+        assert!(wrapped.contains("tail") && clipped.contains("tail"));
+        assert!(!wrap);
+    }
+
+    #[test]
+    fn status_line_shows_clock_and_pane_count() {
+        let mut app = cat_app(Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(Node::bite(2, "left", "cat")),
+            second: Box::new(Node::bite(3, "right", "cat")),
+        });
+        let mut terminal = Terminal::new(TestBackend::new(60, 8)).unwrap();
+        let mut status_row = |app: &mut RuntimeApp| {
+            terminal.draw(|f| app.draw(f)).unwrap();
+            let buffer = terminal.backend().buffer();
+            (0..60)
+                .map(|x| buffer.get(x, 6).symbol())
+                .collect::<String>()
+        };
+        let with_clock = status_row(&mut app);
+        app.config.show_clock = false;
+        let without_clock = status_row(&mut app);
+
+        let clock: Vec<char> = with_clock["[pudding] ".len()..].chars().take(8).collect();
+        assert!(clock.iter().enumerate().all(|(i, c)| if i % 3 == 2 {
+            *c == ':'
+        } else {
+            c.is_ascii_digit()
+        }));
+        assert!(with_clock.contains("panes: 2  active: left"));
+        assert!(without_clock.starts_with("[pudding] panes: 2  active: left"));
+    }
+
+    #[test]
+    fn shell_args_only_apply_to_default_shell_panes() {
+        let config = Config {
+            default_command: "zsh".to_string(),
+            shell_args: vec!["-l".to_string()],
+            ..Config::default()
+        };
+        let argv = |bite: Node| {
+            pane_command(&bite, &config)
+                .unwrap()
+                .get_argv()
+                .iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        let mut split = Node::bite(1, "top", "htop");
+        crate::layout::split_bite(&mut split, 1, Orientation::Vertical, 0.5, 2, "zsh");
+        let new_pane = crate::layout::find_bite(&split, 2).unwrap().clone();
+
+        assert_eq!(
+            argv(Node::default_bite(1, "shell", "zsh")),
+            vec!["zsh", "-l"]
+        );
+        assert_eq!(argv(new_pane), vec!["zsh", "-l"]);
+        assert_eq!(argv(Node::bite(2, "shell", "zsh")), vec!["zsh"]);
+        assert_eq!(argv(Node::bite(2, "top", "htop")), vec!["htop"]);
+        assert_eq!(
+            argv(Node::bite(3, "logs", "tail -f '/tmp/my logs/app.log'")),
+            vec!["tail", "-f", "/tmp/my logs/app.log"]
+        );
+    }
+
+    #[test]
+    fn commands_with_quoted_arguments_spawn_directly() {
+        let app = cat_app(Node::bite(1, "main", "echo 'two  spaces' \"and quotes\""));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.pane_text(1).is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let text = app.pane_text(1);
+
+        assert_eq!(text, vec!["two  spaces and quotes"]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn open_below_starts_in_the_live_directory_of_the_active_pane() {
+        let mut app = cat_app(Node::bite(1, "main", "sh -c 'cd / && exec cat'"));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.panes[&1].current_dir().as_deref() != Some("/") && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        if let Some(Node::Bite { cwd, .. }) =
+            crate::layout::find_bite_mut(&mut app.template.layout, 1)
+        {
+            *cwd = Some("~".to_string());
+        }
+
+        app.handle_action(Action::OpenBelow);
+
+        let new_id = app.active_id;
+        assert_ne!(new_id, 1);
+        assert!(matches!(
+            crate::layout::find_bite(&app.template.layout, new_id),
+            Some(Node::Bite { cwd: Some(cwd), .. }) if cwd == "/"
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dropping_a_pane_reaps_its_child() {
+        let bite = Node::bite(1, "sleeper", "sleep 100");
+        let size = portable_pty::PtySize::default();
+        let pane = PaneProcess::spawn(&bite, size, &Config::default(), None).unwrap();
+        let pid = pane.child.process_id().unwrap();
+        assert!(std::path::Path::new(&format!("/proc/{pid}")).exists());
+
+        drop(pane);
+        assert!(!std::path::Path::new(&format!("/proc/{pid}")).exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn detached_children_survive_quitting() {
+        let config = Config {
+            detach_on_quit: true,
+            ..Config::default()
+        };
+        // Not a `TestApp`: quitting must leave the child running rather than kill it.
+        let app = RuntimeApp::new(template(Node::bite(1, "sleeper", "sleep 100")), config).unwrap();
+        let pid = app.panes[&1].child.process_id().unwrap();
+        let mut terminal = ScriptedTerminal::new(
+            80,
+            24,
+            vec![
+                ScriptedTerminal::key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+                ScriptedTerminal::key(KeyCode::Char('y'), KeyModifiers::NONE),
+            ],
+        );
+
+        app.run_with(&mut terminal).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        // A killed but unreaped child would linger as a zombie, so check its state too.
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).unwrap_or_default();
+        let alive = stat
+            .rsplit_once(") ")
+            .is_some_and(|(_, rest)| !rest.starts_with('Z'));
+        let _ = std::process::Command::new("kill")
+            .arg(pid.to_string())
+            .status();
+
+        assert!(alive, "pane child exited with pudding: {stat}");
+    }
+
+    #[test]
+    fn delete_confirms_only_running_panes_when_configured() {
+        let config = Config {
+            default_command: "cat".to_string(),
+            confirm_delete: ConfirmDelete::Running,
+            ..Config::default()
+        };
+        let mut app = test_app(
+            Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.5,
+                first: Box::new(Node::bite(2, "live", "cat")),
+                second: Box::new(Node::bite(3, "done", "true")),
+            },
+            config,
+        );
+        app.active_id = 2;
+        app.handle_action(Action::DeletePane);
+        let prompted = app.prompt.take().is_some();
+
+        app.active_id = 3;
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while app.panes.get_mut(&3).unwrap().is_running() && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        app.handle_action(Action::DeletePane);
+
+        assert!(prompted);
+        assert!(app.prompt.is_none());
+        assert!(matches!(app.template.layout, Node::Bite { id: 2, .. }));
+    }
+
+    #[test]
+    fn quit_confirms_only_while_children_run() {
+        let mut app = cat_app(Node::bite(1, "main", "cat"));
+        let asked = !app.handle_action(Action::Quit);
+        let label = app.prompt.as_ref().map(|prompt| prompt.label.clone());
+        let kept = !app
+            .handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE))
+            .unwrap();
+        let dismissed = app.prompt.is_none();
+        app.handle_action(Action::Quit);
+        let confirmed = app
+            .handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE))
+            .unwrap();
+        app.config.confirm_quit = false;
+        let opted_out = app.handle_action(Action::Quit);
+        app.config.confirm_quit = true;
+        for pane in app.all_panes_mut() {
+            pane.kill();
+        }
+        let after_exit = app.handle_action(Action::Quit);
+
+        assert!(asked);
+        assert_eq!(label.as_deref(), Some("保存せず終了しますか？ (y/n)"));
+        assert!(kept && dismissed);
+        assert!(confirmed);
+        assert!(opted_out);
+        assert!(after_exit);
+    }
+
+    #[test]
+    fn swapping_panes_resizes_their_ptys() {
+        let mut app = cat_app(Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.25,
+            first: Box::new(Node::bite(2, "narrow", "cat")),
+            second: Box::new(Node::bite(3, "wide", "cat")),
+        });
+        let cols = |app: &TestApp, id| app.panes[&id].master.get_size().unwrap().cols;
+        let (narrow, wide) = (cols(&app, 2), cols(&app, 3));
+        app.active_id = 2;
+        app.handle_action(Action::SwapVertical);
+
+        assert_ne!(narrow, wide);
+        assert_eq!((cols(&app, 2), cols(&app, 3)), (wide, narrow));
+    }
+
+    #[test]
+    fn directional_focus_follows_the_grid() {
+        let column = |id, top, bottom| Node::Spoon {
+            id,
+            orientation: Orientation::Horizontal,
+            ratio: 0.5,
+            first: Box::new(Node::bite(top, "top", "cat")),
+            second: Box::new(Node::bite(bottom, "bottom", "cat")),
+        };
+        let mut app = cat_app(Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(column(2, 3, 4)),
+            second: Box::new(column(5, 6, 7)),
+        });
+        app.active_id = 3;
+        let mut visited = Vec::new();
+        for action in [
+            Action::FocusLeft,
+            Action::FocusRight,
+            Action::FocusDown,
+            Action::FocusLeft,
+            Action::FocusUp,
+            Action::FocusUp,
+        ] {
+            app.handle_action(action);
+            visited.push(app.active_id);
+        }
+
+        assert_eq!(visited, vec![3, 6, 7, 4, 3, 3]);
+    }
+
+    #[test]
+    fn missing_cwd_leaves_the_pane_unspawned_with_a_status() {
+        let mut missing = Node::bite(3, "gone", "cat");
+        if let Node::Bite { cwd, .. } = &mut missing {
+            *cwd = Some("/pudding/does/not/exist".to_string());
+        }
+        let mut home = Node::bite(2, "home", "cat");
+        if let Node::Bite { cwd, .. } = &mut home {
+            *cwd = Some("~".to_string());
+        }
+        let app = cat_app(Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(home.clone()),
+            second: Box::new(missing),
+        });
+        let spawned: Vec<u64> = app.panes.keys().copied().collect();
+
+        assert_eq!(spawned, vec![2]);
+        assert_eq!(
+            app.status,
+            "作業ディレクトリがありません: /pudding/does/not/exist"
+        );
+        assert_eq!(bite_cwd(&home).unwrap(), Some(expand_home("~")));
+    }
+
+    #[test]
+    fn save_pane_output_writes_the_scrollback_once() {
+        let mut app = cat_app(Node::bite(1, "main", "echo pudding-dump"));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.pane_text(1).is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let path = std::env::temp_dir().join(format!("pudding-dump-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        app.save_active_output(path.to_str().unwrap());
+        let saved = app.status.clone();
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        app.save_active_output(path.to_str().unwrap());
+        let refused = app.status.clone();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(saved.starts_with("出力を保存しました"), "{saved}");
+        assert_eq!(content, "pudding-dump\n");
+        assert!(
+            refused.starts_with("既にあるファイルには保存しません"),
+            "{refused}"
+        );
+    }
+
+    #[test]
+    fn exited_panes_show_their_code_and_stop_taking_keys() {
+        let mut app = cat_app(Node::bite(1, "main", "sh -c 'exit 3'"));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.panes.get_mut(&1).unwrap().exit_state().is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let state = app.panes.get_mut(&1).unwrap().exit_state();
+        let mut terminal = Terminal::new(TestBackend::new(40, 8)).unwrap();
+        terminal.draw(|f| app.draw(f)).unwrap();
+        let top: String = (0..40)
+            .map(|x| terminal.backend().buffer().get(x, 0).symbol().to_string())
+            .collect();
+        app.handle_key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE))
+            .unwrap();
+
+        assert_eq!(state, Some(PaneExit::Code(3)));
+        // Wide characters leave a blank filler cell behind them.
+        assert!(top.replace(' ', "").contains("[終了(コード3)]"), "{top}");
+        assert_eq!(app.status, "このペインのプロセスは終了しています");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn closing_the_terminal_counts_as_exited_before_the_child_is_reaped() {
+        let config = Config::default();
+        let bite = Node::bite(1, "closer", "sh -c 'exec 0<&- 1>&- 2>&-; sleep 5'");
+        let mut pane =
+            PaneProcess::spawn(&bite, portable_pty::PtySize::default(), &config, None).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pane.exit_state().is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let state = pane.exit_state();
+        pane.kill();
+
+        assert_eq!(state, Some(PaneExit::Closed));
+    }
+
+    #[test]
+    fn restart_clears_the_scrollback_and_reports_a_missing_cwd() {
+        let mut app = cat_app(Node::bite(1, "main", "echo once"));
+        let wait_for_output = |app: &RuntimeApp| {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while app.pane_text(1).is_empty() && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        };
+        wait_for_output(&app);
+        app.start_search("once", false);
+        app.handle_action(Action::RestartPane);
+        let restarted = app.status.clone();
+        let search_cleared = app.search.is_none();
+        wait_for_output(&app);
+        std::thread::sleep(Duration::from_millis(50));
+        let text = app.pane_text(1);
+
+        if let Node::Bite { cwd, .. } = &mut app.template.layout {
+            *cwd = Some("/pudding/does/not/exist".to_string());
+        }
+        app.handle_action(Action::RestartPane);
+        let failed = app.status.clone();
+
+        assert_eq!(restarted, "再起動しました: main");
+        assert!(search_cleared);
+        assert_eq!(text, vec!["once"]);
+        assert_eq!(
+            failed,
+            "再起動に失敗: 作業ディレクトリがありません: /pudding/does/not/exist"
+        );
+    }
+
+    #[test]
+    fn unopenable_pane_log_is_reported_in_the_status() {
+        let blocker = std::env::temp_dir().join(format!("pudding-log-{}", std::process::id()));
+        std::fs::write(&blocker, "").unwrap();
+        let config = Config {
+            default_command: "cat".to_string(),
+            pane_log_dir: Some(blocker.join("logs").to_string_lossy().into_owned()),
+            ..Config::default()
+        };
+        let app = test_app(Node::bite(1, "main", "cat"), config);
+        let started = app.panes.contains_key(&1);
+        std::fs::remove_file(&blocker).unwrap();
+
+        assert!(started);
+        assert!(
+            app.status.starts_with("ログを開けません: "),
+            "{}",
+            app.status
+        );
     }
 }
@@ -0,0 +1,45 @@
+use ratatui::layout::Rect;
+
+use crate::{layout::find_bite_at, model::Node};
+
+pub(super) fn click_target(layout: &Node, main: Rect, x: u16, y: u16) -> Option<u64> {
+    let inside = x >= main.x && x < main.x + main.width && y >= main.y && y < main.y + main.height;
+    if !inside {
+        return None;
+    }
+    find_bite_at(layout, main, x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::click_target;
+    use crate::model::{Node, Orientation};
+    use ratatui::layout::Rect;
+
+    fn layout() -> Node {
+        Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(Node::bite(2, "left", "sh")),
+            second: Box::new(Node::bite(3, "right", "sh")),
+        }
+    }
+
+    #[test]
+    fn picks_pane_under_click() {
+        let main = Rect::new(0, 0, 80, 22);
+        assert_eq!(click_target(&layout(), main, 5, 5), Some(2));
+        assert_eq!(click_target(&layout(), main, 60, 21), Some(3));
+    }
+
+    #[test]
+    fn ignores_clicks_outside_main_area() {
+        let main = Rect::new(0, 0, 80, 22);
+        assert_eq!(click_target(&layout(), main, 5, 22), None);
+        assert_eq!(
+            click_target(&Node::bite(1, "only", "sh"), main, 90, 5),
+            None
+        );
+    }
+}
@@ -0,0 +1,13 @@
+// Behind the `clipboard` feature so headless builds don't link a display-server client.
+#[cfg(feature = "clipboard")]
+pub(super) fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|err| err.to_string())?;
+    clipboard
+        .set_text(text.to_string())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "clipboard"))]
+pub(super) fn copy_to_clipboard(_text: &str) -> Result<(), String> {
+    Err("clipboard feature が無効なビルドです".to_string())
+}
@@ -0,0 +1,37 @@
+use anyhow::{anyhow, bail, Result};
+
+const GRID_MAX: usize = 6;
+
+pub(super) fn parse_grid_size(input: &str) -> Result<(usize, usize)> {
+    let (rows, cols) = input
+        .trim()
+        .split_once(['x', 'X'])
+        .ok_or_else(|| anyhow!("use ROWSxCOLS, e.g. 2x2"))?;
+    let rows: usize = rows.trim().parse().map_err(|_| anyhow!("invalid rows"))?;
+    let cols: usize = cols.trim().parse().map_err(|_| anyhow!("invalid cols"))?;
+    if !(1..=GRID_MAX).contains(&rows) || !(1..=GRID_MAX).contains(&cols) {
+        bail!("rows and cols must be 1..={GRID_MAX}");
+    }
+    if rows * cols < 2 {
+        bail!("grid must have at least 2 panes");
+    }
+    Ok((rows, cols))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_grid_size;
+
+    #[test]
+    fn parses_rows_by_cols() {
+        assert_eq!(parse_grid_size("2x3").unwrap(), (2, 3));
+        assert_eq!(parse_grid_size(" 1 X 4 ").unwrap(), (1, 4));
+    }
+
+    #[test]
+    fn rejects_bad_dimensions() {
+        for input in ["", "2", "0x2", "1x1", "7x1", "ax2", "2x-1"] {
+            assert!(parse_grid_size(input).is_err(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::{Duration, Instant};
+
+// Only key and mouse input count as activity; pane output never resets the timer.
+pub(super) fn idle_expired(last_input: Instant, now: Instant, limit_secs: Option<u64>) -> bool {
+    match limit_secs {
+        Some(secs) if secs > 0 => {
+            now.saturating_duration_since(last_input) >= Duration::from_secs(secs)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::idle_expired;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn expires_after_limit_without_input() {
+        let start = Instant::now();
+        assert!(!idle_expired(
+            start,
+            start + Duration::from_secs(59),
+            Some(60)
+        ));
+        assert!(idle_expired(
+            start,
+            start + Duration::from_secs(60),
+            Some(60)
+        ));
+    }
+
+    #[test]
+    fn recent_input_resets_and_unset_limit_never_expires() {
+        let start = Instant::now();
+        let input = start + Duration::from_secs(50);
+        assert!(!idle_expired(
+            input,
+            start + Duration::from_secs(100),
+            Some(60)
+        ));
+        assert!(!idle_expired(
+            start,
+            start + Duration::from_secs(9999),
+            None
+        ));
+        assert!(!idle_expired(
+            start,
+            start + Duration::from_secs(9999),
+            Some(0)
+        ));
+    }
+}
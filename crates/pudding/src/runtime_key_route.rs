@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyEvent;
+
+use super::runtime_key_to_bytes::key_to_bytes;
+use crate::{action::Action, keybind::KeyBinding};
+
+#[derive(Debug, PartialEq)]
+pub(super) enum KeyRoute {
+    Prompt,
+    Action(Action),
+    Pane(Vec<u8>),
+    Ignore,
+}
+
+// An open prompt consumes every key (Enter, Esc included); nothing reaches the PTY.
+pub(super) fn route_key(
+    key: KeyEvent,
+    prompt_open: bool,
+    actions: &HashMap<KeyBinding, Action>,
+) -> KeyRoute {
+    if prompt_open {
+        return KeyRoute::Prompt;
+    }
+    if let Some((_, action)) = actions.iter().find(|(binding, _)| binding.matches(key)) {
+        return KeyRoute::Action(*action);
+    }
+    match key_to_bytes(key) {
+        Some(bytes) => KeyRoute::Pane(bytes),
+        None => KeyRoute::Ignore,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::{route_key, KeyRoute};
+    use crate::action::runtime_actions_from_config;
+    use crate::config::Config;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn open_prompt_swallows_every_key() {
+        let actions = runtime_actions_from_config(&Config::default().keybinds);
+        for code in [
+            KeyCode::Enter,
+            KeyCode::Esc,
+            KeyCode::Char('a'),
+            KeyCode::Char('v'),
+            KeyCode::Backspace,
+        ] {
+            assert_eq!(route_key(key(code), true, &actions), KeyRoute::Prompt);
+        }
+    }
+
+    #[test]
+    fn closed_prompt_forwards_unbound_keys_to_pane() {
+        let actions = HashMap::new();
+        assert_eq!(
+            route_key(key(KeyCode::Enter), false, &actions),
+            KeyRoute::Pane(vec![b'\r'])
+        );
+    }
+
+    #[test]
+    fn closed_prompt_dispatches_bound_keys() {
+        let actions = runtime_actions_from_config(&Config::default().keybinds);
+        assert_eq!(
+            route_key(key(KeyCode::Tab), false, &actions),
+            KeyRoute::Action(crate::action::Action::FocusNext)
+        );
+    }
+
+    #[test]
+    fn unbound_ctrl_chords_reach_the_pane_as_control_bytes() {
+        let actions = runtime_actions_from_config(&Config::default().keybinds);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert_eq!(
+            route_key(ctrl('d'), false, &actions),
+            KeyRoute::Pane(vec![0x04])
+        );
+        assert_eq!(
+            route_key(ctrl('c'), false, &actions),
+            KeyRoute::Action(crate::action::Action::Quit)
+        );
+    }
+}
@@ -1,6 +1,13 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
+// Only reached for keys that are not bound to an action, so Ctrl chords free of
+// bindings arrive at the child as their C0 control bytes.
 pub(super) fn key_to_bytes(key: KeyEvent) -> Option<Vec<u8>> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        if let KeyCode::Char(c) = key.code {
+            return control_byte(c).map(|byte| vec![byte]);
+        }
+    }
     match key.code {
         KeyCode::Char(c) if c.is_ascii() => Some(vec![c as u8]),
         KeyCode::Enter => Some(vec![b'\r']),
@@ -11,6 +18,42 @@ pub(super) fn key_to_bytes(key: KeyEvent) -> Option<Vec<u8>> {
         KeyCode::Right => Some(b"\x1b[C".to_vec()),
         KeyCode::Up => Some(b"\x1b[A".to_vec()),
         KeyCode::Down => Some(b"\x1b[B".to_vec()),
+        KeyCode::Home => Some(b"\x1b[H".to_vec()),
+        KeyCode::End => Some(b"\x1b[F".to_vec()),
+        KeyCode::PageUp => Some(b"\x1b[5~".to_vec()),
+        KeyCode::PageDown => Some(b"\x1b[6~".to_vec()),
+        KeyCode::Insert => Some(b"\x1b[2~".to_vec()),
+        KeyCode::Delete => Some(b"\x1b[3~".to_vec()),
+        KeyCode::F(n) => function_key(n).map(|seq| seq.as_bytes().to_vec()),
+        _ => None,
+    }
+}
+
+// xterm sequences: F1-F4 use SS3, the rest numbered CSI codes with gaps.
+fn function_key(n: u8) -> Option<&'static str> {
+    Some(match n {
+        1 => "\x1bOP",
+        2 => "\x1bOQ",
+        3 => "\x1bOR",
+        4 => "\x1bOS",
+        5 => "\x1b[15~",
+        6 => "\x1b[17~",
+        7 => "\x1b[18~",
+        8 => "\x1b[19~",
+        9 => "\x1b[20~",
+        10 => "\x1b[21~",
+        11 => "\x1b[23~",
+        12 => "\x1b[24~",
+        _ => return None,
+    })
+}
+
+fn control_byte(c: char) -> Option<u8> {
+    match c.to_ascii_lowercase() {
+        c @ 'a'..='z' => Some(c as u8 - b'a' + 1),
+        ' ' | '@' | '2' => Some(0x00),
+        c @ ('[' | '\\' | ']' | '^' | '_') => Some(c as u8 & 0x1f),
+        '?' => Some(0x7f),
         _ => None,
     }
 }
@@ -34,8 +77,62 @@ mod tests {
         assert_eq!(key_to_bytes(key(KeyCode::Char('あ'))), None);
     }
 
+    #[test]
+    fn converts_ctrl_letters_to_control_bytes() {
+        for (offset, c) in ('a'..='z').enumerate() {
+            let expected = Some(vec![offset as u8 + 1]);
+            let lower = KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+            let upper = KeyEvent::new(
+                KeyCode::Char(c.to_ascii_uppercase()),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+            );
+            assert_eq!(key_to_bytes(lower), expected);
+            assert_eq!(key_to_bytes(upper), expected);
+        }
+    }
+
+    #[test]
+    fn converts_ctrl_space_and_punctuation() {
+        let ctrl = |c| key_to_bytes(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+        assert_eq!(ctrl(' '), Some(vec![0x00]));
+        assert_eq!(ctrl('['), Some(vec![0x1b]));
+        assert_eq!(ctrl('\\'), Some(vec![0x1c]));
+        assert_eq!(ctrl('1'), None);
+    }
+
     #[test]
     fn converts_arrow_key() {
         assert_eq!(key_to_bytes(key(KeyCode::Left)), Some(b"\x1b[D".to_vec()));
     }
+
+    #[test]
+    fn converts_navigation_keys() {
+        let cases: [(KeyCode, &[u8]); 6] = [
+            (KeyCode::Home, b"\x1b[H"),
+            (KeyCode::End, b"\x1b[F"),
+            (KeyCode::PageUp, b"\x1b[5~"),
+            (KeyCode::PageDown, b"\x1b[6~"),
+            (KeyCode::Insert, b"\x1b[2~"),
+            (KeyCode::Delete, b"\x1b[3~"),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(key_to_bytes(key(code)), Some(expected.to_vec()), "{code:?}");
+        }
+    }
+
+    #[test]
+    fn converts_function_keys() {
+        let expected = [
+            "\x1bOP", "\x1bOQ", "\x1bOR", "\x1bOS", "\x1b[15~", "\x1b[17~", "\x1b[18~", "\x1b[19~",
+            "\x1b[20~", "\x1b[21~", "\x1b[23~", "\x1b[24~",
+        ];
+        for (n, seq) in (1..=12).zip(expected) {
+            assert_eq!(
+                key_to_bytes(key(KeyCode::F(n))),
+                Some(seq.as_bytes().to_vec()),
+                "F{n}"
+            );
+        }
+        assert_eq!(key_to_bytes(key(KeyCode::F(13))), None);
+    }
 }
@@ -0,0 +1,36 @@
+use ratatui::layout::Rect;
+
+// A label takes the first row inside the border, but only while a row of output is left.
+pub(super) fn label_rows(rect: Rect, labelled: bool) -> u16 {
+    u16::from(labelled && rect.height >= 4)
+}
+
+// The pane rect minus the label row, so the PTY and the output area shrink together.
+pub(super) fn content_rect(rect: Rect, labelled: bool) -> Rect {
+    let rows = label_rows(rect, labelled);
+    Rect {
+        y: rect.y + rows,
+        height: rect.height - rows,
+        ..rect
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_rect, label_rows};
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn label_takes_one_row_from_the_content() {
+        let rect = Rect::new(0, 0, 40, 10);
+        assert_eq!(content_rect(rect, true), Rect::new(0, 1, 40, 9));
+        assert_eq!(content_rect(rect, false), rect);
+    }
+
+    #[test]
+    fn label_is_dropped_when_no_output_row_would_remain() {
+        assert_eq!(label_rows(Rect::new(0, 0, 40, 4), true), 1);
+        assert_eq!(label_rows(Rect::new(0, 0, 40, 3), true), 0);
+        assert_eq!(label_rows(Rect::new(0, 0, 40, 10), false), 0);
+    }
+}
@@ -0,0 +1,206 @@
+use std::{collections::VecDeque, io::Write};
+
+const PENDING_CHAR_LIMIT: usize = 8192;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct OutputLine {
+    pub(super) text: String,
+    pub(super) lossy: bool,
+}
+
+pub(super) struct OutputBuffer {
+    lines: VecDeque<OutputLine>,
+    pending: String,
+    pending_lossy: bool,
+    // Start of a UTF-8 sequence cut off by the end of the last read.
+    partial: Vec<u8>,
+    limit: usize,
+    log: Option<Box<dyn Write + Send>>,
+}
+
+impl OutputBuffer {
+    pub(super) fn new(limit: usize, log: Option<Box<dyn Write + Send>>) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            pending: String::new(),
+            pending_lossy: false,
+            partial: Vec::new(),
+            limit,
+            log,
+        }
+    }
+
+    pub(super) fn lines(&self) -> &VecDeque<OutputLine> {
+        &self.lines
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.lines.clear();
+        self.pending.clear();
+        self.pending_lossy = false;
+        self.partial.clear();
+    }
+
+    pub(super) fn find_matches(&self, query: &str, case_sensitive: bool) -> Vec<usize> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let needle = if case_sensitive {
+            query.to_string()
+        } else {
+            query.to_lowercase()
+        };
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| {
+                if case_sensitive {
+                    line.text.contains(&needle)
+                } else {
+                    line.text.to_lowercase().contains(&needle)
+                }
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    // The log receives the bytes untouched; only the display copy is decoded lossily, and a
+    // character split across reads is held back until its remaining bytes arrive.
+    pub(super) fn push_bytes(&mut self, bytes: &[u8]) {
+        if let Some(log) = self.log.as_mut() {
+            let _ = log.write_all(bytes);
+            let _ = log.flush();
+        }
+        let mut data = std::mem::take(&mut self.partial);
+        data.extend_from_slice(bytes);
+        let complete = data.len() - incomplete_tail(&data);
+        self.partial = data.split_off(complete);
+        let lossy = self.pending_lossy || std::str::from_utf8(&data).is_err();
+        let chunk = String::from_utf8_lossy(&data);
+        let stripped = strip_ansi_escapes::strip(chunk.as_bytes());
+        let text = String::from_utf8_lossy(&stripped).replace('\r', "");
+        let mut combined = std::mem::take(&mut self.pending);
+        combined.push_str(&text);
+        let mut parts: Vec<&str> = combined.split('\n').collect();
+        let last = parts.pop().unwrap_or("");
+        for part in parts {
+            let line_lossy = lossy && part.contains(char::REPLACEMENT_CHARACTER);
+            self.push_line(part.to_string(), line_lossy);
+        }
+        self.pending = tail_chars(last, PENDING_CHAR_LIMIT);
+        self.pending_lossy = lossy && self.pending.contains(char::REPLACEMENT_CHARACTER);
+    }
+
+    fn push_line(&mut self, text: String, lossy: bool) {
+        self.lines.push_back(OutputLine { text, lossy });
+        while self.lines.len() > self.limit {
+            self.lines.pop_front();
+        }
+    }
+}
+
+// Length of a trailing sequence that is valid so far but still waiting for bytes.
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    let start = bytes.len().saturating_sub(3);
+    for lead in (start..bytes.len()).rev() {
+        if bytes[lead] & 0xc0 != 0x80 {
+            return match std::str::from_utf8(&bytes[lead..]) {
+                Err(err) if err.valid_up_to() == 0 && err.error_len().is_none() => {
+                    bytes.len() - lead
+                }
+                _ => 0,
+            };
+        }
+    }
+    0
+}
+
+fn tail_chars(text: &str, limit: usize) -> String {
+    let count = text.chars().count();
+    text.chars().skip(count.saturating_sub(limit)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use super::OutputBuffer;
+
+    #[derive(Clone, Default)]
+    struct SharedLog(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedLog {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_is_lossy_on_screen_but_raw_in_log() {
+        let log = SharedLog::default();
+        let mut buffer = OutputBuffer::new(10, Some(Box::new(log.clone())));
+        let bytes = b"ok\nbad \xff\xfe end\n";
+        buffer.push_bytes(bytes);
+        let lines = buffer.lines();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text, "ok");
+        assert!(!lines[0].lossy);
+        assert!(lines[1].text.contains('\u{FFFD}'));
+        assert!(lines[1].lossy);
+        assert_eq!(log.0.lock().unwrap().as_slice(), bytes);
+    }
+
+    #[test]
+    fn joins_multibyte_chars_split_across_reads() {
+        let log = SharedLog::default();
+        let mut buffer = OutputBuffer::new(10, Some(Box::new(log.clone())));
+        let bytes = "日本─\n".as_bytes();
+        buffer.push_bytes(&bytes[..1]);
+        buffer.push_bytes(&bytes[1..4]);
+        buffer.push_bytes(&bytes[4..8]);
+        buffer.push_bytes(&bytes[8..]);
+        assert_eq!(buffer.lines()[0].text, "日本─");
+        assert!(!buffer.lines()[0].lossy);
+        assert_eq!(log.0.lock().unwrap().as_slice(), bytes);
+
+        buffer.push_bytes(b"x\xff\xe6");
+        buffer.push_bytes(b"\x97\xa5\n");
+        assert_eq!(buffer.lines()[1].text, "x\u{FFFD}日");
+        assert!(buffer.lines()[1].lossy);
+    }
+
+    #[test]
+    fn keeps_partial_line_until_newline_and_trims_to_limit() {
+        let mut buffer = OutputBuffer::new(2, None);
+        buffer.push_bytes(b"a\nb\nc");
+        assert_eq!(buffer.lines().len(), 2);
+        buffer.push_bytes(b"d\n");
+        let texts: Vec<&str> = buffer.lines().iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, vec!["b", "cd"]);
+        assert!(buffer.lines().iter().all(|l| !l.lossy));
+    }
+
+    #[test]
+    fn strips_ansi_and_carriage_returns() {
+        let mut buffer = OutputBuffer::new(5, None);
+        buffer.push_bytes(b"\x1b[31mred\x1b[0m\r\n");
+        assert_eq!(buffer.lines()[0].text, "red");
+    }
+
+    #[test]
+    fn find_matches_is_case_insensitive_unless_asked() {
+        let mut buffer = OutputBuffer::new(10, None);
+        buffer.push_bytes(b"Error one\nok\nerror two\n");
+        assert_eq!(buffer.find_matches("error", false), vec![0, 2]);
+        assert_eq!(buffer.find_matches("error", true), vec![2]);
+        assert!(buffer.find_matches("", false).is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+use portable_pty::PtySize;
+use ratatui::layout::Rect;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(super) struct ScreenPixels {
+    pub(super) cols: u16,
+    pub(super) rows: u16,
+    pub(super) width: u16,
+    pub(super) height: u16,
+}
+
+// Terminals that do not report pixel dimensions yield zeros, which PTYs treat as "unknown".
+pub(super) fn screen_pixels() -> ScreenPixels {
+    match crossterm::terminal::window_size() {
+        Ok(size) => ScreenPixels {
+            cols: size.columns,
+            rows: size.rows,
+            width: size.width,
+            height: size.height,
+        },
+        Err(_) => ScreenPixels::default(),
+    }
+}
+
+pub(super) fn pty_size(rect: Rect, screen: ScreenPixels) -> PtySize {
+    let rows = rect.height.saturating_sub(2);
+    let cols = rect.width.saturating_sub(2);
+    PtySize {
+        rows,
+        cols,
+        pixel_width: scale(cols, screen.cols, screen.width),
+        pixel_height: scale(rows, screen.rows, screen.height),
+    }
+}
+
+fn scale(cells: u16, total_cells: u16, total_pixels: u16) -> u16 {
+    if total_cells == 0 {
+        return 0;
+    }
+    (u32::from(cells) * u32::from(total_pixels) / u32::from(total_cells)) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pty_size, ScreenPixels};
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn subtracts_borders() {
+        let size = pty_size(Rect::new(0, 0, 40, 10), ScreenPixels::default());
+        assert_eq!((size.rows, size.cols), (8, 38));
+        assert_eq!((size.pixel_width, size.pixel_height), (0, 0));
+    }
+
+    #[test]
+    fn saturates_on_tiny_rect() {
+        let size = pty_size(Rect::new(0, 0, 1, 1), ScreenPixels::default());
+        assert_eq!((size.rows, size.cols), (0, 0));
+    }
+
+    #[test]
+    fn pixels_are_proportional_to_cells() {
+        let screen = ScreenPixels {
+            cols: 100,
+            rows: 50,
+            width: 1000,
+            height: 1000,
+        };
+        let size = pty_size(Rect::new(0, 0, 42, 27), screen);
+        assert_eq!((size.cols, size.rows), (40, 25));
+        assert_eq!((size.pixel_width, size.pixel_height), (400, 500));
+    }
+}
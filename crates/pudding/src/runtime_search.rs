@@ -0,0 +1,85 @@
+// Line indices point into the pane's scrollback; they are refreshed on every step
+// because the buffer drops old lines as output arrives.
+pub(super) struct Search {
+    pub(super) pane: u64,
+    pub(super) query: String,
+    pub(super) case_sensitive: bool,
+    matches: Vec<usize>,
+    current: usize,
+}
+
+impl Search {
+    // Starts on the newest match, which is nearest the bottom of the pane.
+    pub(super) fn new(pane: u64, query: String, case_sensitive: bool, matches: Vec<usize>) -> Self {
+        let current = matches.len().saturating_sub(1);
+        Self {
+            pane,
+            query,
+            case_sensitive,
+            matches,
+            current,
+        }
+    }
+
+    pub(super) fn refresh(&mut self, matches: Vec<usize>) {
+        self.matches = matches;
+        self.current = self.current.min(self.matches.len().saturating_sub(1));
+    }
+
+    pub(super) fn step(&mut self, forward: bool) {
+        let len = self.matches.len();
+        if len == 0 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % len
+        } else {
+            (self.current + len - 1) % len
+        };
+    }
+
+    pub(super) fn current_line(&self) -> Option<usize> {
+        self.matches.get(self.current).copied()
+    }
+
+    pub(super) fn is_match(&self, line: usize) -> bool {
+        self.matches.binary_search(&line).is_ok()
+    }
+
+    pub(super) fn counter(&self) -> String {
+        if self.matches.is_empty() {
+            "[0/0]".to_string()
+        } else {
+            format!("[{}/{}]", self.current + 1, self.matches.len())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Search;
+
+    #[test]
+    fn steps_cycle_through_matches() {
+        let mut search = Search::new(1, "err".to_string(), false, vec![2, 5, 9]);
+        assert_eq!(search.current_line(), Some(9));
+        assert_eq!(search.counter(), "[3/3]");
+        search.step(true);
+        assert_eq!(search.current_line(), Some(2));
+        search.step(false);
+        search.step(false);
+        assert_eq!(search.current_line(), Some(5));
+        assert!(search.is_match(9) && !search.is_match(3));
+    }
+
+    #[test]
+    fn refresh_keeps_current_in_range() {
+        let mut search = Search::new(1, "x".to_string(), false, vec![1, 2, 3]);
+        search.refresh(vec![4]);
+        assert_eq!(search.current_line(), Some(4));
+        search.refresh(Vec::new());
+        assert_eq!(search.current_line(), None);
+        assert_eq!(search.counter(), "[0/0]");
+        search.step(true);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::timestamp::utc_from_unix;
+
+// Used when the save prompt is confirmed empty; stays within `[A-Za-z0-9_-]`.
+pub(super) fn session_name(unix_secs: u64) -> String {
+    let t = utc_from_unix(unix_secs);
+    format!(
+        "session-{:04}{:02}{:02}-{:02}{:02}{:02}",
+        t.year, t.month, t.day, t.hour, t.minute, t.second
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::session_name;
+    use crate::template::validate_store_name;
+
+    #[test]
+    fn generated_name_is_a_valid_store_name() {
+        let name = session_name(1_700_000_000);
+        assert_eq!(name, "session-20231114-221320");
+        assert!(validate_store_name(&name).is_ok());
+        assert!(validate_store_name(&session_name(0)).is_ok());
+    }
+}
@@ -0,0 +1,49 @@
+use ratatui::layout::Rect;
+
+use crate::{
+    layout::{find_bite, layout_rects},
+    model::Node,
+};
+
+pub(super) fn visible_rects(layout: &Node, main: Rect, zoomed: Option<u64>) -> Vec<(u64, Rect)> {
+    if let Some(id) = zoomed {
+        if find_bite(layout, id).is_some() {
+            return vec![(id, main)];
+        }
+    }
+    let mut rects = Vec::new();
+    layout_rects(layout, main, &mut rects);
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::visible_rects;
+    use crate::model::{Node, Orientation};
+    use ratatui::layout::Rect;
+
+    fn layout() -> Node {
+        Node::Spoon {
+            id: 1,
+            orientation: Orientation::Vertical,
+            ratio: 0.5,
+            first: Box::new(Node::bite(2, "left", "sh")),
+            second: Box::new(Node::bite(3, "right", "sh")),
+        }
+    }
+
+    #[test]
+    fn zoomed_bite_fills_main_area() {
+        let main = Rect::new(0, 0, 80, 22);
+        let layout = layout();
+        assert_eq!(visible_rects(&layout, main, Some(3)), vec![(3, main)]);
+        assert!(matches!(layout, Node::Spoon { ratio, .. } if ratio == 0.5));
+    }
+
+    #[test]
+    fn tiles_when_not_zoomed_or_target_missing() {
+        let main = Rect::new(0, 0, 80, 22);
+        assert_eq!(visible_rects(&layout(), main, None).len(), 2);
+        assert_eq!(visible_rects(&layout(), main, Some(9)).len(), 2);
+    }
+}
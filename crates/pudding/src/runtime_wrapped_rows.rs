@@ -0,0 +1,45 @@
+use ratatui::{
+    text::Line,
+    widgets::{Paragraph, Wrap},
+};
+
+pub(super) const PANE_WRAP: Wrap = Wrap { trim: false };
+
+// A pane never shows more than `width * height` characters of one line, so anything past
+// that is dropped before layout; a runaway line without newlines stays cheap to draw.
+pub(super) fn clip_for_wrap(text: &str, width: u16, height: usize) -> &str {
+    let limit = (width as usize).saturating_mul(height);
+    match text.char_indices().nth(limit) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+// Rows `text` takes in a pane `width` columns wide, laid out exactly as the draw wraps it.
+pub(super) fn wrapped_rows(text: &str, width: u16, height: usize) -> usize {
+    Paragraph::new(Line::raw(clip_for_wrap(text, width, height)))
+        .wrap(PANE_WRAP)
+        .line_count(width)
+        .clamp(1, height.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clip_for_wrap, wrapped_rows};
+
+    #[test]
+    fn counts_rows_of_wrapped_lines() {
+        assert_eq!(wrapped_rows("", 10, 5), 1);
+        assert_eq!(wrapped_rows("0123456789", 10, 5), 1);
+        assert_eq!(wrapped_rows("0123456789a", 10, 5), 2);
+        assert_eq!(wrapped_rows("日本語日本語", 4, 5), 3);
+    }
+
+    #[test]
+    fn very_long_lines_are_capped_to_the_pane() {
+        let long = "x".repeat(1_000_000);
+        assert_eq!(clip_for_wrap(&long, 10, 3).len(), 30);
+        assert_eq!(wrapped_rows(&long, 10, 3), 3);
+        assert_eq!(clip_for_wrap("short", 10, 3), "short");
+    }
+}
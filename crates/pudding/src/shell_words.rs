@@ -0,0 +1,141 @@
+use std::env;
+
+// Splits plain argv-style commands; anything needing a real shell returns None.
+pub fn split_words(command: &str) -> Option<Vec<String>> {
+    if starts_with_assignment(command) {
+        return None;
+    }
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => current.push(c),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => current.push(chars.next()?),
+                        '$' | '`' => return None,
+                        c => current.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                current.push(chars.next()?);
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c if "|&;<>()$`*?[]{}~#".contains(c) => return None,
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    Some(words)
+}
+
+// `FOO=bar cmd` sets a variable for `cmd`; run directly it would look for a program
+// called `FOO=bar`. Quoting the name (`'FOO'=bar`) makes it a plain word again.
+fn starts_with_assignment(command: &str) -> bool {
+    let mut chars = command.trim_start().chars();
+    if !chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+    {
+        return false;
+    }
+    chars
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '_'))
+        .is_some_and(|c| c == '=')
+}
+
+// The argv a pane runs. Plain commands are split like a shell would and run directly;
+// anything using shell syntax (pipes, redirects, variables, globs) goes through $SHELL.
+pub fn command_argv(command: &str) -> Vec<String> {
+    match split_words(command) {
+        Some(words) if !words.is_empty() => words,
+        _ => vec![user_shell(), "-c".to_string(), command.to_string()],
+    }
+}
+
+fn user_shell() -> String {
+    env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command_argv, split_words, user_shell};
+
+    #[test]
+    fn split_words_handles_quotes_and_rejects_shell_syntax() {
+        assert_eq!(
+            split_words(r#"echo "a b" c\ d ''"#).unwrap(),
+            vec!["echo", "a b", "c d", ""]
+        );
+        assert!(split_words("echo $HOME").is_none());
+        assert!(split_words("echo 'open").is_none());
+    }
+
+    #[test]
+    fn leading_assignment_needs_the_shell() {
+        assert!(split_words("RUST_LOG=debug cargo run").is_none());
+        assert!(split_words("_A1= make").is_none());
+        assert_eq!(
+            split_words("env RUST_LOG=debug cargo run").unwrap(),
+            vec!["env", "RUST_LOG=debug", "cargo", "run"]
+        );
+        assert_eq!(split_words("1A=b c").unwrap(), vec!["1A=b", "c"]);
+        assert_eq!(split_words("'A'=b c").unwrap(), vec!["A=b", "c"]);
+    }
+
+    #[test]
+    fn quoted_paths_with_spaces_stay_one_argument() {
+        assert_eq!(
+            command_argv("tail -f '/var/log/my app/out.log'"),
+            vec!["tail", "-f", "/var/log/my app/out.log"]
+        );
+        assert_eq!(
+            command_argv(r#"less "/tmp/a b.txt" /tmp/c\ d.txt"#),
+            vec!["less", "/tmp/a b.txt", "/tmp/c d.txt"]
+        );
+    }
+
+    #[test]
+    fn shell_syntax_runs_through_the_shell() {
+        for command in [
+            "ls | wc -l",
+            "make && make test",
+            "echo hi > out.txt",
+            "echo 'open",
+            "RUST_LOG=debug cargo run",
+        ] {
+            assert_eq!(
+                command_argv(command),
+                vec![user_shell(), "-c".to_string(), command.to_string()]
+            );
+        }
+    }
+}
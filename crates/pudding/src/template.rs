@@ -1,5 +1,4 @@
 use std::{
-    collections::HashSet,
     fs::{self, OpenOptions},
     io::{self, Write},
     path::{Path, PathBuf},
@@ -8,11 +7,26 @@ use std::{
 #[cfg(unix)]
 use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
 
+use crate::layout::{collect_bites, find_bite, initial_focus};
 use crate::model::{default_template, Node, Template};
 use crate::paths::{states_dir, templates_dir};
 
+mod kdl;
+mod kdl_import;
+mod kdl_parse;
+mod last_used;
+mod problems;
+mod tab_name;
+
+pub use self::kdl::{to_kdl_document, to_zellij_kdl};
+pub use self::kdl_import::from_kdl_document;
+pub use self::last_used::{record_last_used, resolve_last_used};
+pub use self::problems::{cwd_is_rooted, template_problems, Problem, Severity};
+pub use self::tab_name::new_tab_name;
+
 pub fn load_template(name: &str) -> Result<Template> {
     validate_store_name(name)?;
     let path = template_path(name);
@@ -26,6 +40,15 @@ pub fn load_template(name: &str) -> Result<Template> {
     }
 }
 
+// Unlike `load_template`, a missing file is an error rather than the default layout.
+pub fn load_stored_template(name: &str) -> Result<Template> {
+    validate_store_name(name)?;
+    if !template_path(name).exists() {
+        bail!("template not found: {name}");
+    }
+    load_template(name).with_context(|| format!("failed to load template: {name}"))
+}
+
 pub fn save_template(template: &Template) -> Result<()> {
     validate_store_name(&template.name)?;
     validate_template(template)?;
@@ -42,32 +65,124 @@ pub fn template_path(name: &str) -> PathBuf {
     templates_dir().join(format!("{}.json", name))
 }
 
+pub fn load_template_file(path: &Path, default_command: &str) -> Result<(Template, Vec<String>)> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("failed to read template file: {}", path.display()))?;
+    let (template, warnings) = if path.extension().is_some_and(|ext| ext == "kdl") {
+        from_kdl_document(&data, default_command)
+            .with_context(|| format!("failed to parse layout: {}", path.display()))?
+            .into_template(&name_from_file(path))
+    } else {
+        let mut template = serde_json::from_str::<Template>(&data)
+            .with_context(|| format!("invalid template json: {}", path.display()))?;
+        template.name = name_from_file(path);
+        (template, Vec::new())
+    };
+    validate_template(&template)
+        .with_context(|| format!("invalid template file: {}", path.display()))?;
+    Ok((template, warnings))
+}
+
+fn name_from_file(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name: String = stem
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .take(64)
+        .collect();
+    if name.is_empty() {
+        "file".to_string()
+    } else {
+        name
+    }
+}
+
+pub fn list_templates() -> Result<Vec<String>> {
+    store_names(&templates_dir())
+}
+
+// A state is the template plus the focused bite; files saved before `active_id` still load.
+#[derive(Serialize, Deserialize)]
+struct StateFile {
+    #[serde(flatten)]
+    template: Template,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active_id: Option<u64>,
+}
+
 pub fn load_state(name: &str) -> Result<Template> {
+    Ok(load_state_with_focus(name)?.0)
+}
+
+pub fn load_state_with_focus(name: &str) -> Result<(Template, u64)> {
     validate_store_name(name)?;
     let path = state_path(name);
     let data = fs::read_to_string(&path)?;
-    let tpl = serde_json::from_str::<Template>(&data)?;
-    validate_template(&tpl)?;
-    Ok(tpl)
+    parse_state(&data)
+}
+
+fn parse_state(data: &str) -> Result<(Template, u64)> {
+    let state = serde_json::from_str::<StateFile>(data)?;
+    validate_template(&state.template)?;
+    let focus = saved_focus(state.template.active_layout(), state.active_id);
+    Ok((state.template, focus))
+}
+
+// A stale id falls back to the first bite; no id at all keeps the usual primary-first focus.
+fn saved_focus(layout: &Node, active_id: Option<u64>) -> u64 {
+    match active_id {
+        Some(id) if find_bite(layout, id).is_some() => id,
+        Some(_) => {
+            let mut ids = Vec::new();
+            collect_bites(layout, &mut ids);
+            ids.first().copied().unwrap_or_else(|| layout.id())
+        }
+        None => initial_focus(layout),
+    }
 }
 
-pub fn save_state(name: &str, template: &Template) -> Result<()> {
+pub fn save_state(name: &str, template: &Template, active_id: u64) -> Result<()> {
     validate_store_name(name)?;
     validate_template(template)?;
     let path = state_path(name);
     if let Some(parent) = path.parent() {
         ensure_dir_secure(parent)?;
     }
-    let data = serde_json::to_string_pretty(template)?;
+    let state = StateFile {
+        template: template.clone(),
+        active_id: Some(active_id),
+    };
+    let data = serde_json::to_string_pretty(&state)?;
     write_private_file(&path, &data)?;
     Ok(())
 }
 
+pub fn list_states() -> Result<Vec<String>> {
+    store_names(&states_dir())
+}
+
+pub fn delete_state(name: &str) -> Result<()> {
+    validate_store_name(name)?;
+    let path = state_path(name);
+    fs::remove_file(&path)
+        .with_context(|| format!("failed to delete state: {}", path.display()))?;
+    Ok(())
+}
+
 pub fn state_path(name: &str) -> PathBuf {
     states_dir().join(format!("{}.json", name))
 }
 
-fn validate_store_name(name: &str) -> Result<()> {
+pub(crate) fn validate_store_name(name: &str) -> Result<()> {
     if name.is_empty() || name.len() > 64 {
         bail!("name must be 1..=64 chars");
     }
@@ -80,49 +195,44 @@ fn validate_store_name(name: &str) -> Result<()> {
     Ok(())
 }
 
-fn validate_template(template: &Template) -> Result<()> {
-    validate_store_name(&template.name)?;
-    let mut ids = HashSet::new();
-    validate_node(&template.layout, &mut ids)
-}
-
-fn validate_node(node: &Node, ids: &mut HashSet<u64>) -> Result<()> {
-    if !ids.insert(node.id()) {
-        return Err(anyhow!("node id must be unique"));
-    }
-    match node {
-        Node::Bite { name, command, .. } => {
-            if name.trim().is_empty() {
-                bail!("bite name must not be empty");
-            }
-            if command.trim().is_empty() {
-                bail!("bite command must not be empty");
-            }
-            Ok(())
+fn store_names(dir: &Path) -> Result<Vec<String>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut names = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
         }
-        Node::Spoon {
-            ratio,
-            first,
-            second,
-            ..
-        } => {
-            if !(*ratio > 0.0 && *ratio < 1.0) {
-                bail!("spoon ratio must be in (0,1)");
+        if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+            if validate_store_name(stem).is_ok() {
+                names.push(stem.to_string());
             }
-            validate_node(first, ids)?;
-            validate_node(second, ids)
         }
     }
+    names.sort();
+    Ok(names)
 }
 
-fn ensure_dir_secure(path: &Path) -> io::Result<()> {
+fn validate_template(template: &Template) -> Result<()> {
+    let problems = template_problems(template);
+    match problems.iter().find(|p| p.severity == Severity::Error) {
+        Some(problem) => Err(anyhow!(problem.message.clone())),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn ensure_dir_secure(path: &Path) -> io::Result<()> {
     fs::create_dir_all(path)?;
     #[cfg(unix)]
     fs::set_permissions(path, fs::Permissions::from_mode(0o700))?;
     Ok(())
 }
 
-fn write_private_file(path: &Path, content: &str) -> io::Result<()> {
+pub(crate) fn write_private_file(path: &Path, content: &str) -> io::Result<()> {
     let mut options = OpenOptions::new();
     options.create(true).truncate(true).write(true);
     #[cfg(unix)]
@@ -138,7 +248,87 @@ fn write_private_file(path: &Path, content: &str) -> io::Result<()> {
 #[cfg(test)]
 mod tests {
     use crate::model::{Node, Orientation, Template};
-    use crate::template::{validate_store_name, validate_template};
+    use crate::template::{
+        delete_state, load_template_file, parse_state, store_names, validate_store_name,
+        validate_template,
+    };
+
+    #[test]
+    fn state_restores_saved_focus_or_falls_back_to_first_bite() {
+        let layout = r#"{"type":"spoon","id":1,"orientation":"vertical","ratio":0.5,
+            "first":{"type":"bite","id":2,"name":"a","command":"sh"},
+            "second":{"type":"bite","id":3,"name":"b","command":"sh"}}"#;
+        let state = |active: &str| format!(r#"{{"name":"s","layout":{layout}{active}}}"#);
+
+        assert_eq!(parse_state(&state(r#","active_id":3"#)).unwrap().1, 3);
+        assert_eq!(parse_state(&state(r#","active_id":9"#)).unwrap().1, 2);
+        assert_eq!(parse_state(&state(r#","active_id":1"#)).unwrap().1, 2);
+        assert_eq!(parse_state(&state("")).unwrap().1, 2);
+    }
+
+    #[test]
+    fn state_focus_is_looked_up_in_the_active_tab() {
+        let state = r#"{"name":"s","layout":{"type":"bite","id":1,"name":"a","command":"sh"},
+            "tabs":[{"name":"logs","layout":{"type":"spoon","id":1,"orientation":"vertical",
+            "ratio":0.5,"first":{"type":"bite","id":2,"name":"b","command":"sh"},
+            "second":{"type":"bite","id":3,"name":"c","command":"sh"}}}],
+            "active_tab":1,"active_id":3}"#;
+        let (template, focus) = parse_state(state).unwrap();
+        assert_eq!((template.active_tab, focus), (1, 3));
+        assert_eq!(template.tab_name(1), "logs");
+        let saved = serde_json::to_value(&template).unwrap();
+        assert_eq!(saved["tabs"][0]["name"], "logs");
+        assert_eq!(saved["active_tab"], 1);
+        let single = serde_json::to_value(
+            &parse_state(
+                r#"{"name":"s","layout":
+            {"type":"bite","id":1,"name":"a","command":"sh"}}"#,
+            )
+            .unwrap()
+            .0,
+        )
+        .unwrap();
+        assert!(single.get("tabs").is_none() && single.get("active_tab").is_none());
+    }
+
+    #[test]
+    fn store_names_lists_valid_json_stems_sorted() {
+        let dir = std::env::temp_dir().join(format!("pudding-store-names-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ["work.json", "a-b.json", "notes.txt", "bad name.json"] {
+            std::fs::write(dir.join(file), "{}").unwrap();
+        }
+        let names = store_names(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(names, vec!["a-b".to_string(), "work".to_string()]);
+        assert!(store_names(&dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn template_file_is_named_after_its_stem() {
+        let dir =
+            std::env::temp_dir().join(format!("pudding-template-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let kdl = dir.join("my layout.kdl");
+        std::fs::write(&kdl, "layout {\n pane\n pane\n}").unwrap();
+        let broken = dir.join("broken.json");
+        std::fs::write(&broken, "{").unwrap();
+        let (template, warnings) = load_template_file(&kdl, "sh").unwrap();
+        let broken_err = load_template_file(&broken, "sh").unwrap_err();
+        let missing_err = load_template_file(&dir.join("missing.json"), "sh").unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(template.name, "my-layout");
+        assert!(warnings.is_empty());
+        assert!(matches!(template.layout, Node::Spoon { .. }));
+        assert!(format!("{broken_err:#}").contains("invalid template json"));
+        assert!(format!("{missing_err:#}").contains("failed to read template file"));
+    }
+
+    #[test]
+    fn delete_state_rejects_path_like_name() {
+        let err = delete_state("../config").unwrap_err();
+        assert!(err.to_string().contains("[A-Za-z0-9_-]"));
+    }
 
     #[test]
     fn reject_path_like_name() {
@@ -150,20 +340,14 @@ mod tests {
     fn reject_invalid_template_ratio() {
         let template = Template {
             name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
             layout: Node::Spoon {
                 id: 1,
                 orientation: Orientation::Vertical,
                 ratio: 1.0,
-                first: Box::new(Node::Bite {
-                    id: 2,
-                    name: "a".to_string(),
-                    command: "bash".to_string(),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 3,
-                    name: "b".to_string(),
-                    command: "bash".to_string(),
-                }),
+                first: Box::new(Node::bite(2, "a", "bash")),
+                second: Box::new(Node::bite(3, "b", "bash")),
             },
         };
         assert!(validate_template(&template).is_err());
@@ -173,20 +357,14 @@ mod tests {
     fn reject_duplicate_node_id() {
         let template = Template {
             name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
             layout: Node::Spoon {
                 id: 1,
                 orientation: Orientation::Vertical,
                 ratio: 0.5,
-                first: Box::new(Node::Bite {
-                    id: 2,
-                    name: "a".to_string(),
-                    command: "bash".to_string(),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 2,
-                    name: "b".to_string(),
-                    command: "bash".to_string(),
-                }),
+                first: Box::new(Node::bite(2, "a", "bash")),
+                second: Box::new(Node::bite(2, "b", "bash")),
             },
         };
         assert!(validate_template(&template).is_err());
@@ -196,21 +374,17 @@ mod tests {
     fn reject_empty_bite_name_and_command() {
         let with_empty_name = Template {
             name: "ok".to_string(),
-            layout: Node::Bite {
-                id: 1,
-                name: "   ".to_string(),
-                command: "bash".to_string(),
-            },
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::bite(1, "   ", "bash"),
         };
         assert!(validate_template(&with_empty_name).is_err());
 
         let with_empty_command = Template {
             name: "ok".to_string(),
-            layout: Node::Bite {
-                id: 1,
-                name: "valid".to_string(),
-                command: "   ".to_string(),
-            },
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::bite(1, "valid", "   "),
         };
         assert!(validate_template(&with_empty_command).is_err());
     }
@@ -234,40 +408,28 @@ mod tests {
     fn reject_template_ratio_outside_open_interval() {
         let below_zero = Template {
             name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
             layout: Node::Spoon {
                 id: 1,
                 orientation: Orientation::Vertical,
                 ratio: -0.01,
-                first: Box::new(Node::Bite {
-                    id: 2,
-                    name: "a".to_string(),
-                    command: "bash".to_string(),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 3,
-                    name: "b".to_string(),
-                    command: "bash".to_string(),
-                }),
+                first: Box::new(Node::bite(2, "a", "bash")),
+                second: Box::new(Node::bite(3, "b", "bash")),
             },
         };
         assert!(validate_template(&below_zero).is_err());
 
         let above_one = Template {
             name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
             layout: Node::Spoon {
                 id: 1,
                 orientation: Orientation::Vertical,
                 ratio: 1.01,
-                first: Box::new(Node::Bite {
-                    id: 2,
-                    name: "a".to_string(),
-                    command: "bash".to_string(),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 3,
-                    name: "b".to_string(),
-                    command: "bash".to_string(),
-                }),
+                first: Box::new(Node::bite(2, "a", "bash")),
+                second: Box::new(Node::bite(3, "b", "bash")),
             },
         };
         assert!(validate_template(&above_one).is_err());
@@ -277,20 +439,14 @@ mod tests {
     fn reject_duplicate_node_id_across_levels() {
         let template = Template {
             name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
             layout: Node::Spoon {
                 id: 1,
                 orientation: Orientation::Vertical,
                 ratio: 0.5,
-                first: Box::new(Node::Bite {
-                    id: 1,
-                    name: "a".to_string(),
-                    command: "bash".to_string(),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 3,
-                    name: "b".to_string(),
-                    command: "bash".to_string(),
-                }),
+                first: Box::new(Node::bite(1, "a", "bash")),
+                second: Box::new(Node::bite(3, "b", "bash")),
             },
         };
         assert!(validate_template(&template).is_err());
@@ -300,89 +456,119 @@ mod tests {
     fn validate_template_ratio_boundaries() {
         let at_zero = Template {
             name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
             layout: Node::Spoon {
                 id: 1,
                 orientation: Orientation::Vertical,
                 ratio: 0.0,
-                first: Box::new(Node::Bite {
-                    id: 2,
-                    name: "a".to_string(),
-                    command: "bash".to_string(),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 3,
-                    name: "b".to_string(),
-                    command: "bash".to_string(),
-                }),
+                first: Box::new(Node::bite(2, "a", "bash")),
+                second: Box::new(Node::bite(3, "b", "bash")),
             },
         };
         assert!(validate_template(&at_zero).is_err());
 
         let near_zero = Template {
             name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
             layout: Node::Spoon {
                 id: 1,
                 orientation: Orientation::Vertical,
                 ratio: 0.0001,
-                first: Box::new(Node::Bite {
-                    id: 2,
-                    name: "a".to_string(),
-                    command: "bash".to_string(),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 3,
-                    name: "b".to_string(),
-                    command: "bash".to_string(),
-                }),
+                first: Box::new(Node::bite(2, "a", "bash")),
+                second: Box::new(Node::bite(3, "b", "bash")),
             },
         };
         assert!(validate_template(&near_zero).is_ok());
 
         let at_one = Template {
             name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
             layout: Node::Spoon {
                 id: 1,
                 orientation: Orientation::Vertical,
                 ratio: 1.0,
-                first: Box::new(Node::Bite {
-                    id: 2,
-                    name: "a".to_string(),
-                    command: "bash".to_string(),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 3,
-                    name: "b".to_string(),
-                    command: "bash".to_string(),
-                }),
+                first: Box::new(Node::bite(2, "a", "bash")),
+                second: Box::new(Node::bite(3, "b", "bash")),
             },
         };
         assert!(validate_template(&at_one).is_err());
 
         let near_one = Template {
             name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
             layout: Node::Spoon {
                 id: 1,
                 orientation: Orientation::Vertical,
                 ratio: 0.9999,
-                first: Box::new(Node::Bite {
-                    id: 2,
-                    name: "a".to_string(),
-                    command: "bash".to_string(),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 3,
-                    name: "b".to_string(),
-                    command: "bash".to_string(),
-                }),
+                first: Box::new(Node::bite(2, "a", "bash")),
+                second: Box::new(Node::bite(3, "b", "bash")),
             },
         };
         assert!(validate_template(&near_one).is_ok());
     }
 
+    #[test]
+    fn optional_bite_fields_are_omitted_when_unset() {
+        let json = serde_json::to_string(&Node::bite(1, "main", "bash")).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"bite","id":1,"name":"main","command":"bash"}"#
+        );
+        let parsed: Node = serde_json::from_str(
+            r#"{"type":"bite","id":1,"name":"m","command":"bash","cwd":"/tmp"}"#,
+        )
+        .unwrap();
+        assert!(matches!(parsed, Node::Bite { cwd: Some(ref c), .. } if c == "/tmp"));
+
+        let mut with_env = Node::bite(1, "main", "bash");
+        if let Node::Bite { env, .. } = &mut with_env {
+            env.insert("RUST_LOG".to_string(), "debug".to_string());
+        }
+        let json = serde_json::to_string(&with_env).unwrap();
+        assert!(json.ends_with(r#""env":{"RUST_LOG":"debug"}}"#));
+        let parsed: Node = serde_json::from_str(&json).unwrap();
+        assert!(matches!(parsed, Node::Bite { ref env, .. } if env["RUST_LOG"] == "debug"));
+    }
+
+    #[test]
+    fn reject_multiple_primary_bites() {
+        let mut first = Node::bite(2, "a", "bash");
+        let mut second = Node::bite(3, "b", "bash");
+        for node in [&mut first, &mut second] {
+            if let Node::Bite { primary, .. } = node {
+                *primary = true;
+            }
+        }
+        let mut template = Template {
+            name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.5,
+                first: Box::new(first),
+                second: Box::new(second.clone()),
+            },
+        };
+        assert!(validate_template(&template).is_err());
+
+        if let Node::Spoon { first, .. } = &mut template.layout {
+            **first = Node::bite(2, "a", "bash");
+        }
+        assert!(validate_template(&template).is_ok());
+    }
+
     #[test]
     fn accept_nested_valid_template() {
         let template = Template {
             name: "valid_name_01".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
             layout: Node::Spoon {
                 id: 10,
                 orientation: Orientation::Horizontal,
@@ -391,22 +577,14 @@ mod tests {
                     id: 11,
                     orientation: Orientation::Vertical,
                     ratio: 0.6,
-                    first: Box::new(Node::Bite {
-                        id: 12,
-                        name: "left-top".to_string(),
-                        command: "bash".to_string(),
-                    }),
-                    second: Box::new(Node::Bite {
-                        id: 13,
-                        name: "left-bottom".to_string(),
-                        command: "bash".to_string(),
-                    }),
-                }),
-                second: Box::new(Node::Bite {
-                    id: 14,
-                    name: "right".to_string(),
-                    command: "bash".to_string(),
+                    first: Box::new(Node::bite(12, "left-top", "bash")),
+                    second: Box::new(Node::bite(
+                        13,
+                        "left-bottom".to_string(),
+                        "bash".to_string(),
+                    )),
                 }),
+                second: Box::new(Node::bite(14, "right", "bash")),
             },
         };
         assert!(validate_template(&template).is_ok());
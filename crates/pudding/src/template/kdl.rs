@@ -0,0 +1,217 @@
+use std::fmt::Write;
+
+use crate::model::{Node, Orientation, Template};
+use crate::shell_words::split_words;
+
+#[derive(Clone, Copy)]
+enum CommandStyle {
+    Inline,
+    Zellij,
+}
+
+pub fn to_kdl_document(template: &Template) -> String {
+    write_document(template, CommandStyle::Inline)
+}
+
+// Unlike `to_kdl_document`, commands are split into zellij's `command` + `args` pair.
+pub fn to_zellij_kdl(template: &Template) -> String {
+    write_document(template, CommandStyle::Zellij)
+}
+
+fn write_document(template: &Template, style: CommandStyle) -> String {
+    let mut out = String::from("layout {\n");
+    if template.tabs.is_empty() {
+        write_node(&template.layout, None, 1, style, &mut out);
+    } else {
+        // zellij opens the tab marked `focus=true`, matching the template's active tab.
+        for (index, layout) in template.layouts().enumerate() {
+            let focus = if index == template.active_tab {
+                " focus=true"
+            } else {
+                ""
+            };
+            let name = quote(template.tab_name(index));
+            let _ = writeln!(out, "    tab name={name}{focus} {{");
+            write_node(layout, None, 2, style, &mut out);
+            out.push_str("    }\n");
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn write_node(node: &Node, size: Option<u32>, depth: usize, style: CommandStyle, out: &mut String) {
+    let indent = "    ".repeat(depth);
+    let size_attr = size
+        .map(|s| format!(" size=\"{}%\"", s))
+        .unwrap_or_default();
+    match node {
+        Node::Bite {
+            name, command, cwd, ..
+        } => {
+            let cwd_attr = cwd
+                .as_deref()
+                .map(|c| format!(" cwd={}", quote(c)))
+                .unwrap_or_default();
+            let (program, args) = match style {
+                CommandStyle::Inline => (command.clone(), Vec::new()),
+                CommandStyle::Zellij => zellij_command(command),
+            };
+            let _ = write!(
+                out,
+                "{indent}pane{size_attr} name={} command={}{cwd_attr}",
+                quote(name),
+                quote(&program)
+            );
+            if args.is_empty() {
+                out.push('\n');
+            } else {
+                let args: Vec<String> = args.iter().map(|arg| quote(arg)).collect();
+                let _ = writeln!(out, " {{\n{indent}    args {}\n{indent}}}", args.join(" "));
+            }
+        }
+        Node::Spoon {
+            orientation,
+            ratio,
+            first,
+            second,
+            ..
+        } => {
+            let direction = match orientation {
+                Orientation::Vertical => "vertical",
+                Orientation::Horizontal => "horizontal",
+            };
+            let first_size = (ratio * 100.0).round().clamp(1.0, 99.0) as u32;
+            let _ = writeln!(
+                out,
+                "{indent}pane{size_attr} split_direction=\"{direction}\" {{"
+            );
+            write_node(first, Some(first_size), depth + 1, style, out);
+            write_node(second, Some(100 - first_size), depth + 1, style, out);
+            let _ = writeln!(out, "{indent}}}");
+        }
+    }
+}
+
+fn zellij_command(command: &str) -> (String, Vec<String>) {
+    match split_words(command) {
+        Some(mut words) if !words.is_empty() => {
+            let program = words.remove(0);
+            (program, words)
+        }
+        _ => (
+            "sh".to_string(),
+            vec!["-c".to_string(), command.to_string()],
+        ),
+    }
+}
+
+pub(crate) fn quote(value: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{quote, to_kdl_document, to_zellij_kdl};
+    use crate::layout::find_bite;
+    use crate::model::{Node, Orientation, Tab, Template};
+    use crate::template::from_kdl_document;
+
+    #[test]
+    fn writes_nested_spoons_as_panes() {
+        let template = Template {
+            name: "t".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 0.3,
+                first: Box::new(Node::bite(2, "left", "bash")),
+                second: Box::new(Node::bite(3, "right", "htop")),
+            },
+        };
+        let expected = "layout {\n    pane split_direction=\"vertical\" {\n        pane size=\"30%\" name=\"left\" command=\"bash\"\n        pane size=\"70%\" name=\"right\" command=\"htop\"\n    }\n}\n";
+        assert_eq!(to_kdl_document(&template), expected);
+    }
+
+    #[test]
+    fn writes_each_tab_and_focuses_the_active_one() {
+        let template = Template {
+            name: "dev".to_string(),
+            tabs: vec![Tab {
+                name: "logs".to_string(),
+                layout: Node::bite(1, "tail", "tail"),
+            }],
+            active_tab: 1,
+            layout: Node::bite(1, "shell", "bash"),
+        };
+        let expected = "layout {\n    tab name=\"dev\" {\n        pane name=\"shell\" command=\"bash\"\n    }\n    tab name=\"logs\" focus=true {\n        pane name=\"tail\" command=\"tail\"\n    }\n}\n";
+        assert_eq!(to_kdl_document(&template), expected);
+    }
+
+    #[test]
+    fn tabs_survive_export_and_import() {
+        let template = Template {
+            name: "dev".to_string(),
+            tabs: vec![Tab {
+                name: "logs".to_string(),
+                layout: Node::bite(1, "tail", "tail"),
+            }],
+            active_tab: 1,
+            layout: Node::bite(1, "shell", "bash"),
+        };
+        let (imported, _) = from_kdl_document(&to_kdl_document(&template), "sh")
+            .unwrap()
+            .into_template("dev");
+        assert_eq!(imported.tab_count(), 2);
+        assert_eq!(imported.tab_name(1), "logs");
+        assert_eq!(imported.active_tab, 1);
+        assert!(matches!(
+            imported.tab_layout(1),
+            Some(Node::Bite { command, .. }) if command == "tail"
+        ));
+    }
+
+    #[test]
+    fn escapes_quotes_and_backslashes() {
+        assert_eq!(quote(r#"echo "a\b""#), r#""echo \"a\\b\"""#);
+    }
+
+    #[test]
+    fn zellij_layout_splits_command_into_args() {
+        let template = Template {
+            name: "t".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Horizontal,
+                ratio: 0.5,
+                first: Box::new(Node::bite(2, "logs", "tail -f 'my file.log'")),
+                second: Box::new(Node::bite(3, "pipe", "ls | wc -l")),
+            },
+        };
+        let doc = to_zellij_kdl(&template);
+        assert!(doc.contains(
+            "pane size=\"50%\" name=\"logs\" command=\"tail\" {\n            args \"-f\" \"my file.log\"\n        }\n"
+        ));
+        assert!(doc.contains("command=\"sh\" {\n            args \"-c\" \"ls | wc -l\"\n"));
+        let imported = from_kdl_document(&doc, "bash").unwrap().layout;
+        assert!(matches!(
+            find_bite(&imported, 1),
+            Some(Node::Bite { command, .. }) if command == "tail -f 'my file.log'"
+        ));
+    }
+}
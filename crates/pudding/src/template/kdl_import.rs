@@ -0,0 +1,354 @@
+use anyhow::{anyhow, Result};
+
+use super::kdl_parse::{parse_kdl, KdlNode, KdlValue};
+use crate::layout::clamp_ratio;
+use crate::model::{Node, Orientation, Tab, Template};
+
+const HANDLED_PANE_PROPS: [&str; 6] =
+    ["split_direction", "size", "name", "command", "cwd", "focus"];
+
+// Each `tab` becomes one tab of the template; a layout without tabs is a single tab.
+pub struct KdlImport {
+    pub layout: Node,
+    // Tabs after the first, in document order.
+    pub tabs: Vec<Tab>,
+    pub active_tab: usize,
+    pub warnings: Vec<String>,
+}
+
+impl KdlImport {
+    // The first tab takes the template's name, as `to_kdl_document` writes it.
+    pub fn into_template(self, name: &str) -> (Template, Vec<String>) {
+        let template = Template {
+            name: name.to_string(),
+            tabs: self.tabs,
+            active_tab: self.active_tab,
+            layout: self.layout,
+        };
+        (template, self.warnings)
+    }
+}
+
+pub fn from_kdl_document(input: &str, default_command: &str) -> Result<KdlImport> {
+    let nodes = parse_kdl(input)?;
+    let root = nodes
+        .iter()
+        .find(|n| n.name == "layout")
+        .ok_or_else(|| anyhow!("missing top-level layout node"))?;
+    let mut ctx = Importer {
+        next_id: 1,
+        has_primary: false,
+        default_command,
+        warnings: Vec::new(),
+    };
+    let mut root_panes = Vec::new();
+    let mut tabs = Vec::new();
+    let mut active_tab = 0;
+    for child in &root.children {
+        match child.name.as_str() {
+            "pane" => root_panes.push(child),
+            "tab" => {
+                if child.prop("focus") == Some(&KdlValue::Bool(true)) {
+                    active_tab = tabs.len();
+                }
+                let name = child
+                    .prop_str("name")
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("tab-{}", tabs.len() + 1));
+                let panes: Vec<&KdlNode> =
+                    child.children.iter().filter(|c| c.name == "pane").collect();
+                tabs.push(Tab {
+                    name,
+                    layout: ctx.tab_layout(&panes, direction(child)),
+                });
+            }
+            other => ctx.warn(format!("ignored layout node: {other}")),
+        }
+    }
+    if tabs.is_empty() {
+        let layout = ctx.tab_layout(&root_panes, Orientation::Horizontal);
+        tabs.push(Tab {
+            name: String::new(),
+            layout,
+        });
+    } else if !root_panes.is_empty() {
+        ctx.warn("ignored panes outside of tabs".to_string());
+    }
+    let first = tabs.remove(0);
+    Ok(KdlImport {
+        layout: first.layout,
+        tabs,
+        active_tab,
+        warnings: ctx.warnings,
+    })
+}
+
+struct Importer<'a> {
+    next_id: u64,
+    has_primary: bool,
+    default_command: &'a str,
+    warnings: Vec<String>,
+}
+
+impl Importer<'_> {
+    // Ids and the focused pane are per tab, so both start over for every tab.
+    fn tab_layout(&mut self, panes: &[&KdlNode], orientation: Orientation) -> Node {
+        self.next_id = 1;
+        self.has_primary = false;
+        self.group(panes, orientation)
+            .unwrap_or_else(|| self.bite("main".to_string(), None))
+    }
+
+    fn group(&mut self, panes: &[&KdlNode], orientation: Orientation) -> Option<Node> {
+        let mut items = Vec::new();
+        for pane in panes {
+            if let Some(item) = self.pane(pane) {
+                items.push(item);
+            }
+        }
+        let weights = fill_weights(items.iter().map(|(_, w)| *w).collect());
+        let nodes: Vec<Node> = items.into_iter().map(|(node, _)| node).collect();
+        self.fold(nodes, &weights, orientation)
+    }
+
+    fn fold(
+        &mut self,
+        mut nodes: Vec<Node>,
+        weights: &[f32],
+        orientation: Orientation,
+    ) -> Option<Node> {
+        if nodes.len() <= 1 {
+            return nodes.pop();
+        }
+        let first = nodes.remove(0);
+        let total: f32 = weights.iter().sum();
+        let second = self.fold(nodes, &weights[1..], orientation)?;
+        let id = self.take_id();
+        Some(Node::Spoon {
+            id,
+            orientation,
+            ratio: clamp_ratio(weights[0] / total),
+            first: Box::new(first),
+            second: Box::new(second),
+        })
+    }
+
+    fn pane(&mut self, pane: &KdlNode) -> Option<(Node, Option<f32>)> {
+        if pane.children.iter().any(|c| c.name == "plugin") {
+            self.warn("ignored plugin pane".to_string());
+            return None;
+        }
+        for (key, _) in &pane.props {
+            if !HANDLED_PANE_PROPS.contains(&key.as_str()) {
+                self.warn(format!("ignored pane attribute: {key}"));
+            }
+        }
+        let weight = self.size(pane);
+        let children: Vec<&KdlNode> = pane.children.iter().filter(|c| c.name == "pane").collect();
+        for other in pane
+            .children
+            .iter()
+            .filter(|c| c.name != "pane" && c.name != "args")
+        {
+            self.warn(format!("ignored pane child: {}", other.name));
+        }
+        if !children.is_empty() {
+            return self.group(&children, direction(pane)).map(|n| (n, weight));
+        }
+        Some((self.leaf(pane), weight))
+    }
+
+    fn leaf(&mut self, pane: &KdlNode) -> Node {
+        let command = pane.prop_str("command").map(|cmd| join_command(cmd, pane));
+        let name = pane
+            .prop_str("name")
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("pane-{}", self.next_id));
+        let mut bite = self.bite(name, command);
+        if let Node::Bite { primary, cwd, .. } = &mut bite {
+            *cwd = pane.prop_str("cwd").map(str::to_string);
+            if pane.prop("focus") == Some(&KdlValue::Bool(true)) && !self.has_primary {
+                *primary = true;
+                self.has_primary = true;
+            }
+        }
+        bite
+    }
+
+    fn size(&mut self, pane: &KdlNode) -> Option<f32> {
+        match pane.prop("size") {
+            Some(KdlValue::String(size)) => match size.trim_end_matches('%').parse::<f32>() {
+                Ok(percent) if size.ends_with('%') && percent > 0.0 => Some(percent),
+                _ => {
+                    self.warn(format!("ignored pane size: {size}"));
+                    None
+                }
+            },
+            Some(KdlValue::Number(size)) => {
+                self.warn(format!("ignored fixed pane size: {size}"));
+                None
+            }
+            _ => None,
+        }
+    }
+
+    // Panes without a command fall back to `default_command`.
+    fn bite(&mut self, name: String, command: Option<String>) -> Node {
+        let id = self.take_id();
+        match command {
+            Some(command) => Node::bite(id, name, command),
+            None => Node::default_bite(id, name, self.default_command),
+        }
+    }
+
+    fn take_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn warn(&mut self, message: String) {
+        self.warnings.push(message);
+    }
+}
+
+fn direction(node: &KdlNode) -> Orientation {
+    match node.prop_str("split_direction") {
+        Some("vertical") => Orientation::Vertical,
+        _ => Orientation::Horizontal,
+    }
+}
+
+fn fill_weights(weights: Vec<Option<f32>>) -> Vec<f32> {
+    let known: f32 = weights.iter().flatten().sum();
+    let unknown = weights.iter().filter(|w| w.is_none()).count() as f32;
+    let share = if known < 100.0 && unknown > 0.0 {
+        (100.0 - known) / unknown
+    } else {
+        100.0 / weights.len().max(1) as f32
+    };
+    weights.into_iter().map(|w| w.unwrap_or(share)).collect()
+}
+
+fn join_command(command: &str, pane: &KdlNode) -> String {
+    let mut parts = vec![quote_arg(command)];
+    for args in pane.children.iter().filter(|c| c.name == "args") {
+        for arg in &args.args {
+            if let KdlValue::String(arg) = arg {
+                parts.push(quote_arg(arg));
+            }
+        }
+    }
+    parts.join(" ")
+}
+
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "'\"\\".contains(c)) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::from_kdl_document;
+    use crate::layout::collect_bites;
+    use crate::model::{Node, Orientation};
+
+    #[test]
+    fn imports_zellij_layout_with_warnings() {
+        let doc = r#"
+            layout {
+                default_tab_template { children }
+                pane size=1 borderless=true {
+                    plugin location="zellij:tab-bar"
+                }
+                pane split_direction="vertical" {
+                    pane size="30%" name="logs" command="tail" cwd="/tmp" {
+                        args "-f" "my file.log"
+                    }
+                    pane focus=true
+                }
+            }
+        "#;
+        let imported = from_kdl_document(doc, "bash").unwrap();
+        let Node::Spoon {
+            orientation,
+            ratio,
+            first,
+            second,
+            ..
+        } = &imported.layout
+        else {
+            panic!("expected spoon");
+        };
+        assert_eq!(*orientation, Orientation::Vertical);
+        assert!((ratio - 0.3).abs() < 1e-6);
+        assert!(matches!(
+            first.as_ref(),
+            Node::Bite { name, command, cwd: Some(cwd), .. }
+                if name == "logs" && command == "tail -f 'my file.log'" && cwd == "/tmp"
+        ));
+        assert!(matches!(
+            second.as_ref(),
+            Node::Bite { command, primary: true, .. } if command == "bash"
+        ));
+        assert_eq!(imported.warnings.len(), 2);
+    }
+
+    #[test]
+    fn splits_three_panes_evenly() {
+        let doc = "layout {\n pane\n pane\n pane\n}";
+        let imported = from_kdl_document(doc, "sh").unwrap();
+        let mut ids = Vec::new();
+        collect_bites(&imported.layout, &mut ids);
+        assert_eq!(ids.len(), 3);
+        let Node::Spoon { ratio, .. } = imported.layout else {
+            panic!("expected spoon");
+        };
+        assert!((ratio - 1.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn imports_every_tab_with_its_own_ids() {
+        let doc = r#"
+            layout {
+                tab name="code" {
+                    pane
+                    pane
+                }
+                tab name="logs" focus=true split_direction="vertical" {
+                    pane command="tail"
+                    pane
+                }
+                tab
+            }
+        "#;
+        let (template, warnings) = from_kdl_document(doc, "sh").unwrap().into_template("dev");
+        assert!(warnings.is_empty());
+        assert_eq!(template.tab_count(), 3);
+        assert_eq!(template.tab_name(1), "logs");
+        assert_eq!(template.tab_name(2), "tab-3");
+        assert_eq!(template.active_tab, 1);
+        let Some(Node::Spoon { orientation, .. }) = template.tab_layout(1) else {
+            panic!("expected spoon");
+        };
+        assert_eq!(*orientation, Orientation::Vertical);
+        let mut ids = Vec::new();
+        collect_bites(template.tab_layout(1).unwrap(), &mut ids);
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn warns_about_panes_outside_of_tabs() {
+        let doc = "layout {\n pane\n tab name=\"a\" {\n pane\n }\n}";
+        let imported = from_kdl_document(doc, "sh").unwrap();
+        assert_eq!(imported.warnings, vec!["ignored panes outside of tabs"]);
+        assert!(imported.tabs.is_empty());
+    }
+
+    #[test]
+    fn requires_layout_node() {
+        assert!(from_kdl_document("pane", "sh").is_err());
+    }
+}
@@ -0,0 +1,400 @@
+use anyhow::{anyhow, bail, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KdlValue {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KdlNode {
+    pub name: String,
+    pub args: Vec<KdlValue>,
+    pub props: Vec<(String, KdlValue)>,
+    pub children: Vec<KdlNode>,
+}
+
+impl KdlNode {
+    pub fn prop(&self, key: &str) -> Option<&KdlValue> {
+        self.props
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn prop_str(&self, key: &str) -> Option<&str> {
+        match self.prop(key) {
+            Some(KdlValue::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse_kdl(input: &str) -> Result<Vec<KdlNode>> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+    };
+    parser.nodes(false)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn line(&self) -> usize {
+        self.chars[..self.pos.min(self.chars.len())]
+            .iter()
+            .filter(|c| **c == '\n')
+            .count()
+            + 1
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        anyhow!("kdl line {}: {}", self.line(), message)
+    }
+
+    fn nodes(&mut self, in_block: bool) -> Result<Vec<KdlNode>> {
+        let mut out = Vec::new();
+        loop {
+            self.skip_blank(true)?;
+            match self.peek() {
+                None if in_block => return Err(self.error("unclosed block")),
+                None => return Ok(out),
+                Some('}') if in_block => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some('}') => return Err(self.error("unexpected '}'")),
+                Some(';') => self.pos += 1,
+                Some('/') if self.peek_at(1) == Some('-') => {
+                    self.pos += 2;
+                    self.skip_blank(true)?;
+                    self.node()?;
+                }
+                Some(_) => out.push(self.node()?),
+            }
+        }
+    }
+
+    fn node(&mut self) -> Result<KdlNode> {
+        self.skip_type_annotation()?;
+        let name = match self.peek() {
+            Some('"') | Some('r') if self.starts_string() => self.string()?,
+            _ => self.bare_word(),
+        };
+        if name.is_empty() {
+            return Err(self.error("expected node name"));
+        }
+        let mut node = KdlNode {
+            name,
+            ..KdlNode::default()
+        };
+        loop {
+            self.skip_blank(false)?;
+            match self.peek() {
+                None | Some('}') => return Ok(node),
+                Some('\n') | Some(';') => {
+                    self.pos += 1;
+                    return Ok(node);
+                }
+                Some('{') => {
+                    self.pos += 1;
+                    node.children = self.nodes(true)?;
+                }
+                Some('/') if self.peek_at(1) == Some('-') => {
+                    self.pos += 2;
+                    self.skip_blank(false)?;
+                    if self.peek() == Some('{') {
+                        self.pos += 1;
+                        self.nodes(true)?;
+                    } else {
+                        self.entry(&mut KdlNode::default())?;
+                    }
+                }
+                Some(_) => self.entry(&mut node)?,
+            }
+        }
+    }
+
+    fn entry(&mut self, node: &mut KdlNode) -> Result<()> {
+        self.skip_type_annotation()?;
+        let (token, quoted) = if self.starts_string() {
+            (self.string()?, true)
+        } else {
+            (self.bare_word(), false)
+        };
+        if token.is_empty() {
+            return Err(self.error("unexpected character"));
+        }
+        if self.peek() == Some('=') {
+            self.pos += 1;
+            self.skip_type_annotation()?;
+            let value = self.value()?;
+            node.props.push((token, value));
+        } else if quoted {
+            node.args.push(KdlValue::String(token));
+        } else {
+            node.args.push(keyword_or_number(&token));
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<KdlValue> {
+        if self.starts_string() {
+            return Ok(KdlValue::String(self.string()?));
+        }
+        let word = self.bare_word();
+        if word.is_empty() {
+            return Err(self.error("expected value"));
+        }
+        Ok(keyword_or_number(&word))
+    }
+
+    fn starts_string(&self) -> bool {
+        match self.peek() {
+            Some('"') => true,
+            Some('r') => matches!(self.peek_at(1), Some('"') | Some('#')),
+            _ => false,
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        if self.peek() == Some('r') {
+            return self.raw_string();
+        }
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    out.push(self.escape()?);
+                }
+                Some(ch) => {
+                    self.pos += 1;
+                    out.push(ch);
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char> {
+        let ch = self.peek().ok_or_else(|| self.error("bad escape"))?;
+        self.pos += 1;
+        Ok(match ch {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            '"' | '\\' | '/' => ch,
+            'u' => {
+                if self.peek() != Some('{') {
+                    bail!("kdl line {}: bad unicode escape", self.line());
+                }
+                let end = self.chars[self.pos..]
+                    .iter()
+                    .position(|c| *c == '}')
+                    .ok_or_else(|| self.error("bad unicode escape"))?;
+                let hex: String = self.chars[self.pos + 1..self.pos + end].iter().collect();
+                self.pos += end + 1;
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("bad unicode escape"))?
+            }
+            _ => return Err(self.error("bad escape")),
+        })
+    }
+
+    fn raw_string(&mut self) -> Result<String> {
+        self.pos += 1;
+        let mut hashes = 0;
+        while self.peek() == Some('#') {
+            hashes += 1;
+            self.pos += 1;
+        }
+        if self.peek() != Some('"') {
+            return Err(self.error("bad raw string"));
+        }
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') if (1..=hashes).all(|i| self.peek_at(i) == Some('#')) => {
+                    self.pos += 1 + hashes;
+                    return Ok(out);
+                }
+                Some(ch) => {
+                    self.pos += 1;
+                    out.push(ch);
+                }
+            }
+        }
+    }
+
+    fn bare_word(&mut self) -> String {
+        let mut out = String::new();
+        while let Some(ch) = self.peek() {
+            if ch.is_whitespace() || "=;{}()\"/\\".contains(ch) {
+                break;
+            }
+            out.push(ch);
+            self.pos += 1;
+        }
+        out
+    }
+
+    fn skip_type_annotation(&mut self) -> Result<()> {
+        if self.peek() != Some('(') {
+            return Ok(());
+        }
+        while let Some(ch) = self.peek() {
+            self.pos += 1;
+            if ch == ')' {
+                return Ok(());
+            }
+        }
+        Err(self.error("unterminated type annotation"))
+    }
+
+    fn skip_blank(&mut self, newlines: bool) -> Result<()> {
+        loop {
+            match self.peek() {
+                Some('\n') if newlines => self.pos += 1,
+                Some(ch) if ch != '\n' && ch.is_whitespace() => self.pos += 1,
+                Some('\\') => {
+                    self.pos += 1;
+                    self.skip_blank(false)?;
+                    if self.peek() == Some('/') && self.peek_at(1) == Some('/') {
+                        self.skip_line_comment();
+                    }
+                    if self.peek() == Some('\n') {
+                        self.pos += 1;
+                    }
+                }
+                Some('/') if self.peek_at(1) == Some('/') => self.skip_line_comment(),
+                Some('/') if self.peek_at(1) == Some('*') => self.skip_block_comment()?,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_line_comment(&mut self) {
+        while let Some(ch) = self.peek() {
+            if ch == '\n' {
+                return;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn skip_block_comment(&mut self) -> Result<()> {
+        let mut depth = 0;
+        loop {
+            match (self.peek(), self.peek_at(1)) {
+                (Some('/'), Some('*')) => {
+                    depth += 1;
+                    self.pos += 2;
+                }
+                (Some('*'), Some('/')) => {
+                    depth -= 1;
+                    self.pos += 2;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                (Some(_), _) => self.pos += 1,
+                (None, _) => return Err(self.error("unterminated comment")),
+            }
+        }
+    }
+}
+
+fn keyword_or_number(word: &str) -> KdlValue {
+    match word.trim_start_matches('#') {
+        "true" => return KdlValue::Bool(true),
+        "false" => return KdlValue::Bool(false),
+        "null" => return KdlValue::Null,
+        _ => {}
+    }
+    let cleaned = word.replace('_', "");
+    if let Some(hex) = cleaned.strip_prefix("0x") {
+        if let Ok(value) = i64::from_str_radix(hex, 16) {
+            return KdlValue::Number(value as f64);
+        }
+    }
+    match cleaned.parse::<f64>() {
+        Ok(value) => KdlValue::Number(value),
+        Err(_) => KdlValue::String(word.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_kdl, KdlValue};
+
+    #[test]
+    fn parses_nodes_props_and_children() {
+        let doc = r#"
+            layout {
+                pane split_direction="vertical" {
+                    pane size="30%" command="htop"; pane borderless=true
+                }
+            }
+        "#;
+        let nodes = parse_kdl(doc).unwrap();
+        assert_eq!(nodes.len(), 1);
+        let split = &nodes[0].children[0];
+        assert_eq!(split.prop_str("split_direction"), Some("vertical"));
+        assert_eq!(split.children.len(), 2);
+        assert_eq!(split.children[0].prop_str("command"), Some("htop"));
+        assert_eq!(
+            split.children[1].prop("borderless"),
+            Some(&KdlValue::Bool(true))
+        );
+    }
+
+    #[test]
+    fn handles_comments_escapes_and_args() {
+        let doc = "// header\nargs \"-f\" \"a \\\"b\\\"\" r#\"raw \"x\"\"# 42 /* inline */\n/-pane\nafter";
+        let nodes = parse_kdl(doc).unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(
+            nodes[0].args,
+            vec![
+                KdlValue::String("-f".to_string()),
+                KdlValue::String("a \"b\"".to_string()),
+                KdlValue::String("raw \"x\"".to_string()),
+                KdlValue::Number(42.0),
+            ]
+        );
+        assert_eq!(nodes[1].name, "after");
+    }
+
+    #[test]
+    fn reports_unclosed_block_with_line() {
+        let err = parse_kdl("layout {\n  pane\n").unwrap_err();
+        assert!(err.to_string().contains("unclosed block"));
+    }
+}
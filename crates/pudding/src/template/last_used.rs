@@ -0,0 +1,71 @@
+use std::{fs, path::Path, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+
+use super::{ensure_dir_secure, validate_store_name, write_private_file};
+use crate::paths::config_dir;
+
+pub fn last_used_path() -> PathBuf {
+    config_dir().join("pudding").join("last_template")
+}
+
+pub fn record_last_used(name: &str) -> Result<()> {
+    write_last_used(&last_used_path(), name)
+}
+
+pub fn resolve_last_used() -> Result<String> {
+    read_last_used(&last_used_path())
+}
+
+fn write_last_used(path: &Path, name: &str) -> Result<()> {
+    validate_store_name(name)?;
+    if let Some(parent) = path.parent() {
+        ensure_dir_secure(parent)?;
+    }
+    write_private_file(path, name)?;
+    Ok(())
+}
+
+fn read_last_used(path: &Path) -> Result<String> {
+    let data = fs::read_to_string(path)
+        .map_err(|_| anyhow!("no recently used template: {}", path.display()))?;
+    let name = data.trim().to_string();
+    validate_store_name(&name)?;
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_last_used, write_last_used};
+
+    fn temp_path(tag: &str) -> std::path::PathBuf {
+        std::env::temp_dir()
+            .join(format!("pudding-last-{}-{}", tag, std::process::id()))
+            .join("last_template")
+    }
+
+    #[test]
+    fn round_trips_last_name() {
+        let path = temp_path("roundtrip");
+        write_last_used(&path, "work_1").unwrap();
+        assert_eq!(read_last_used(&path).unwrap(), "work_1");
+        write_last_used(&path, "other").unwrap();
+        assert_eq!(read_last_used(&path).unwrap(), "other");
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn missing_marker_is_error() {
+        assert!(read_last_used(&temp_path("missing")).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_name() {
+        let path = temp_path("invalid");
+        assert!(write_last_used(&path, "../evil").is_err());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "bad name").unwrap();
+        assert!(read_last_used(&path).is_err());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
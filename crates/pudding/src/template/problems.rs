@@ -0,0 +1,397 @@
+use std::{collections::HashSet, fmt, path::Path};
+
+use crate::layout::{clamp_ratio, MAX_RATIO, MIN_RATIO};
+use crate::model::{Node, Template};
+use crate::paths::expand_home;
+
+use super::validate_store_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub severity: Severity,
+    // Index into `Template::tab_count`; template-wide problems use 0.
+    pub tab: usize,
+    pub node_id: Option<u64>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match (self.tab, self.node_id) {
+            (tab, Some(id)) => write!(f, "{label}: tab {tab}: node {id}: {}", self.message),
+            (0, None) => write!(f, "{label}: {}", self.message),
+            (tab, None) => write!(f, "{label}: tab {tab}: {}", self.message),
+        }
+    }
+}
+
+pub fn template_problems(template: &Template) -> Vec<Problem> {
+    let mut out = Vec::new();
+    if let Err(err) = validate_store_name(&template.name) {
+        out.push(error(0, None, err.to_string()));
+    }
+    // Ids and the primary bite are per tab; each tab is spawned and focused on its own.
+    for (tab, layout) in template.layouts().enumerate() {
+        let mut ids = HashSet::new();
+        let mut primaries = 0;
+        node_problems(tab, layout, &mut ids, &mut primaries, &mut out);
+        // No primary is fine: focus then falls back to the tab's first bite.
+        if primaries > 1 {
+            out.push(error(
+                tab,
+                None,
+                format!("at most one bite can be primary, found {primaries}"),
+            ));
+        }
+    }
+    let mut tab_names = HashSet::new();
+    for (index, tab) in template.tabs.iter().enumerate() {
+        if tab.name.trim().is_empty() {
+            out.push(error(
+                index + 1,
+                None,
+                "tab name must not be empty".to_string(),
+            ));
+        } else if tab.name == template.name || !tab_names.insert(tab.name.as_str()) {
+            out.push(error(
+                index + 1,
+                None,
+                format!("tab name must be unique: {}", tab.name),
+            ));
+        }
+    }
+    if template.active_tab >= template.tab_count() {
+        out.push(error(0, None, "active_tab is out of range".to_string()));
+    }
+    out
+}
+
+fn node_problems(
+    tab: usize,
+    node: &Node,
+    ids: &mut HashSet<u64>,
+    primaries: &mut usize,
+    out: &mut Vec<Problem>,
+) {
+    let id = Some(node.id());
+    if !ids.insert(node.id()) {
+        out.push(error(tab, id, "node id must be unique".to_string()));
+    }
+    match node {
+        Node::Bite {
+            name,
+            command,
+            primary,
+            cwd,
+            env,
+            label,
+            ..
+        } => {
+            if name.trim().is_empty() {
+                out.push(error(tab, id, "bite name must not be empty".to_string()));
+            }
+            if command.trim().is_empty() {
+                out.push(error(tab, id, "bite command must not be empty".to_string()));
+            }
+            if *primary {
+                *primaries += 1;
+            }
+            if label
+                .as_deref()
+                .is_some_and(|l| l.chars().any(char::is_control))
+            {
+                out.push(error(
+                    tab,
+                    id,
+                    "bite label must be a single line".to_string(),
+                ));
+            }
+            for key in env.keys().filter(|key| !valid_env_key(key)) {
+                out.push(error(tab, id, format!("invalid env key: {key:?}")));
+            }
+            if let Some(cwd) = cwd.as_deref() {
+                if !cwd_is_rooted(cwd) {
+                    out.push(error(
+                        tab,
+                        id,
+                        format!("cwd must be absolute or start with ~/: {cwd}"),
+                    ));
+                } else if !expand_home(cwd).is_dir() {
+                    out.push(Problem {
+                        severity: Severity::Warning,
+                        tab,
+                        node_id: id,
+                        message: format!("cwd does not exist: {cwd}"),
+                    });
+                }
+            }
+        }
+        Node::Spoon {
+            ratio,
+            first,
+            second,
+            ..
+        } => {
+            if !(*ratio > 0.0 && *ratio < 1.0) {
+                out.push(error(tab, id, "spoon ratio must be in (0,1)".to_string()));
+            } else if clamp_ratio(*ratio) != *ratio {
+                out.push(Problem {
+                    severity: Severity::Warning,
+                    tab,
+                    node_id: id,
+                    message: format!(
+                        "spoon ratio {ratio} is clamped to {MIN_RATIO}..={MAX_RATIO} at runtime"
+                    ),
+                });
+            }
+            node_problems(tab, first, ids, primaries, out);
+            node_problems(tab, second, ids, primaries, out);
+        }
+    }
+}
+
+fn valid_env_key(key: &str) -> bool {
+    !key.is_empty() && !key.contains('=') && !key.contains('\0')
+}
+
+// Relative paths would depend on where pudding happens to be started.
+pub fn cwd_is_rooted(cwd: &str) -> bool {
+    cwd == "~" || cwd.starts_with("~/") || Path::new(cwd).is_absolute()
+}
+
+fn error(tab: usize, node_id: Option<u64>, message: String) -> Problem {
+    Problem {
+        severity: Severity::Error,
+        tab,
+        node_id,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{template_problems, Severity};
+    use crate::model::{Node, Orientation, Tab, Template};
+
+    #[test]
+    fn reports_every_problem_with_node_id() {
+        let template = Template {
+            name: "bad name".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Vertical,
+                ratio: 1.5,
+                first: Box::new(Node::bite(2, "a", " ")),
+                second: Box::new(Node::bite(2, "", "bash")),
+            },
+        };
+        let problems = template_problems(&template);
+        let rendered: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "error: name supports only [A-Za-z0-9_-]",
+                "error: tab 0: node 1: spoon ratio must be in (0,1)",
+                "error: tab 0: node 2: bite command must not be empty",
+                "error: tab 0: node 2: node id must be unique",
+                "error: tab 0: node 2: bite name must not be empty",
+            ]
+        );
+    }
+
+    #[test]
+    fn allows_at_most_one_primary_bite_per_tab() {
+        let with_primaries = |count: usize| {
+            let mut bites: Vec<Node> = (2..4).map(|id| Node::bite(id, "a", "bash")).collect();
+            for bite in bites.iter_mut().take(count) {
+                if let Node::Bite { primary, .. } = bite {
+                    *primary = true;
+                }
+            }
+            let second = bites.pop().unwrap();
+            Template {
+                name: "ok".to_string(),
+                tabs: Vec::new(),
+                active_tab: 0,
+                layout: Node::Spoon {
+                    id: 1,
+                    orientation: Orientation::Vertical,
+                    ratio: 0.5,
+                    first: Box::new(bites.pop().unwrap()),
+                    second: Box::new(second),
+                },
+            }
+        };
+        assert!(template_problems(&with_primaries(0)).is_empty());
+        assert!(template_problems(&with_primaries(1)).is_empty());
+        let rendered: Vec<String> = template_problems(&with_primaries(2))
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(
+            rendered,
+            vec!["error: at most one bite can be primary, found 2"]
+        );
+    }
+
+    #[test]
+    fn rejects_env_keys_with_equals_or_nul() {
+        for (key, ok) in [
+            ("RUST_LOG", true),
+            ("A=B", false),
+            ("A\0", false),
+            ("", false),
+        ] {
+            let mut bite = Node::bite(1, "a", "bash");
+            if let Node::Bite { env, .. } = &mut bite {
+                env.insert(key.to_string(), "debug".to_string());
+            }
+            let template = Template {
+                name: "ok".to_string(),
+                tabs: Vec::new(),
+                active_tab: 0,
+                layout: bite,
+            };
+            assert_eq!(template_problems(&template).is_empty(), ok, "{key:?}");
+        }
+    }
+
+    #[test]
+    fn tab_names_must_be_unique_but_ids_may_repeat_across_tabs() {
+        let tabs = |names: &[&str]| Template {
+            name: "main".to_string(),
+            tabs: names
+                .iter()
+                .map(|name| Tab {
+                    name: name.to_string(),
+                    layout: Node::bite(1, "a", "bash"),
+                })
+                .collect(),
+            active_tab: names.len(),
+            layout: Node::bite(1, "a", "bash"),
+        };
+        assert!(template_problems(&tabs(&["logs", "build"])).is_empty());
+        let duplicate = template_problems(&tabs(&["logs", "logs"]));
+        assert_eq!(duplicate.len(), 1);
+        assert_eq!(duplicate[0].message, "tab name must be unique: logs");
+        assert!(!template_problems(&tabs(&["main"])).is_empty());
+        let mut out_of_range = tabs(&["logs"]);
+        out_of_range.active_tab = 2;
+        assert!(!template_problems(&out_of_range).is_empty());
+    }
+
+    #[test]
+    fn problems_name_the_tab_they_are_in() {
+        let template = Template {
+            name: "main".to_string(),
+            tabs: vec![
+                Tab {
+                    name: "logs".to_string(),
+                    layout: Node::Spoon {
+                        id: 1,
+                        orientation: Orientation::Vertical,
+                        ratio: 0.5,
+                        first: Box::new(Node::bite(2, "a", "bash")),
+                        second: Box::new(Node::bite(2, "b", "bash")),
+                    },
+                },
+                Tab {
+                    name: String::new(),
+                    layout: Node::bite(2, "a", "bash"),
+                },
+            ],
+            active_tab: 0,
+            layout: Node::bite(2, "a", "bash"),
+        };
+        let rendered: Vec<String> = template_problems(&template)
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "error: tab 1: node 2: node id must be unique",
+                "error: tab 2: tab name must not be empty",
+            ]
+        );
+    }
+
+    #[test]
+    fn label_must_be_a_single_line() {
+        for (label, ok) in [("PRODUCTION — be careful", true), ("two\nlines", false)] {
+            let mut bite = Node::bite(1, "a", "bash");
+            if let Node::Bite { label: slot, .. } = &mut bite {
+                *slot = Some(label.to_string());
+            }
+            let template = Template {
+                name: "ok".to_string(),
+                tabs: Vec::new(),
+                active_tab: 0,
+                layout: bite,
+            };
+            assert_eq!(template_problems(&template).is_empty(), ok, "{label:?}");
+        }
+    }
+
+    #[test]
+    fn cwd_must_be_rooted_and_should_exist() {
+        let with_cwd = |cwd: &str| {
+            let mut bite = Node::bite(1, "a", "bash");
+            if let Node::Bite { cwd: slot, .. } = &mut bite {
+                *slot = Some(cwd.to_string());
+            }
+            Template {
+                name: "ok".to_string(),
+                tabs: Vec::new(),
+                active_tab: 0,
+                layout: bite,
+            }
+        };
+        let tmp = std::env::temp_dir();
+        assert!(template_problems(&with_cwd(tmp.to_str().unwrap())).is_empty());
+        assert!(template_problems(&with_cwd("~")).is_empty());
+        let cases = [
+            ("relative/dir", Severity::Error),
+            ("./dir", Severity::Error),
+            ("/pudding/does/not/exist", Severity::Warning),
+            ("~/pudding/does/not/exist", Severity::Warning),
+        ];
+        for (cwd, severity) in cases {
+            let problems = template_problems(&with_cwd(cwd));
+            assert_eq!(problems.len(), 1, "{cwd}");
+            assert_eq!(problems[0].severity, severity, "{cwd}");
+        }
+    }
+
+    #[test]
+    fn ratio_outside_runtime_bounds_is_warning() {
+        let template = Template {
+            name: "ok".to_string(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            layout: Node::Spoon {
+                id: 1,
+                orientation: Orientation::Horizontal,
+                ratio: 0.05,
+                first: Box::new(Node::bite(2, "a", "bash")),
+                second: Box::new(Node::bite(3, "b", "bash")),
+            },
+        };
+        let problems = template_problems(&template);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].severity, Severity::Warning);
+        assert_eq!(problems[0].node_id, Some(1));
+    }
+}
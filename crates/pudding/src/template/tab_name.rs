@@ -0,0 +1,68 @@
+use anyhow::{bail, Result};
+
+use crate::model::Template;
+
+// `{n}` becomes the new tab's number and `{template}` the template name.
+pub fn format_tab_name(format: &str, template: &str, n: usize) -> String {
+    format
+        .replace("{template}", template)
+        .replace("{n}", &n.to_string())
+}
+
+// Numbers start at the new tab's position and count up past names already taken.
+pub fn new_tab_name(template: &Template, format: &str) -> Result<String> {
+    let count = template.tab_count();
+    let taken = |name: &str| (0..count).any(|i| template.tab_name(i) == name);
+    // One more candidate than there are tabs, so a format using `{n}` always finds a name.
+    for n in count + 1..=2 * count + 1 {
+        let name = format_tab_name(format, &template.name, n);
+        if name.trim().is_empty() || name.chars().any(char::is_control) {
+            bail!("tab_name_format gives an invalid tab name: {name:?}");
+        }
+        if !taken(&name) {
+            return Ok(name);
+        }
+    }
+    bail!("tab_name_format gives no unused tab name: {format}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_tab_name, new_tab_name};
+    use crate::model::{Node, Tab, Template};
+
+    fn with_tabs(names: &[&str]) -> Template {
+        Template {
+            name: "dev".to_string(),
+            tabs: names
+                .iter()
+                .map(|name| Tab {
+                    name: name.to_string(),
+                    layout: Node::bite(1, "main", "sh"),
+                })
+                .collect(),
+            active_tab: 0,
+            layout: Node::bite(1, "main", "sh"),
+        }
+    }
+
+    #[test]
+    fn fills_number_and_template_name() {
+        assert_eq!(format_tab_name("tab-{n}", "dev", 2), "tab-2");
+        assert_eq!(format_tab_name("{template}-{n}", "dev", 3), "dev-3");
+        assert_eq!(format_tab_name("logs", "dev", 3), "logs");
+    }
+
+    #[test]
+    fn skips_taken_names_and_rejects_unusable_formats() {
+        assert_eq!(new_tab_name(&with_tabs(&[]), "tab-{n}").unwrap(), "tab-2");
+        assert_eq!(
+            new_tab_name(&with_tabs(&["dev-3"]), "{template}-{n}").unwrap(),
+            "dev-4"
+        );
+        assert_eq!(new_tab_name(&with_tabs(&[]), "logs").unwrap(), "logs");
+        assert!(new_tab_name(&with_tabs(&["logs"]), "logs").is_err());
+        assert!(new_tab_name(&with_tabs(&[]), "{template}").is_err());
+        assert!(new_tab_name(&with_tabs(&[]), " ").is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    any::Any,
+    io::{self, Stdout},
+    panic::{self, AssertUnwindSafe},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use crossterm::{
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event,
+    },
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, layout::Rect, Frame, Terminal};
+
+// The run loops only talk to the terminal through this trait, so tests can script input.
+pub trait TerminalBackend {
+    fn setup(&mut self) -> Result<()>;
+    fn teardown(&mut self) -> Result<()>;
+    fn draw<F: FnOnce(&mut Frame)>(&mut self, render: F) -> Result<()>;
+    fn poll(&mut self, timeout: Duration) -> Result<bool>;
+    fn read(&mut self) -> Result<Event>;
+    fn size(&self) -> Result<Rect>;
+}
+
+// Runs `body` between setup and teardown. A panic inside still restores the terminal first,
+// then its message is printed to the normal screen before unwinding continues.
+pub fn with_terminal<T: TerminalBackend, R>(
+    terminal: &mut T,
+    body: impl FnOnce(&mut T) -> Result<R>,
+) -> Result<R> {
+    terminal.setup()?;
+    let res = panic::catch_unwind(AssertUnwindSafe(|| body(terminal)));
+    let restored = terminal.teardown();
+    match res {
+        Ok(res) => {
+            restored?;
+            res
+        }
+        Err(payload) => {
+            eprintln!("pudding panicked: {}", panic_message(payload.as_ref()));
+            panic::resume_unwind(payload)
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+pub struct CrosstermTerminal {
+    mouse: bool,
+    terminal: Option<Terminal<CrosstermBackend<Stdout>>>,
+}
+
+impl CrosstermTerminal {
+    pub fn new(mouse: bool) -> Self {
+        Self {
+            mouse,
+            terminal: None,
+        }
+    }
+
+    fn terminal(&mut self) -> Result<&mut Terminal<CrosstermBackend<Stdout>>> {
+        self.terminal.as_mut().context("terminal is not set up")
+    }
+}
+
+impl TerminalBackend for CrosstermTerminal {
+    fn setup(&mut self) -> Result<()> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
+        if self.mouse {
+            execute!(stdout, EnableMouseCapture)?;
+        }
+        self.terminal = Some(Terminal::new(CrosstermBackend::new(stdout))?);
+        Ok(())
+    }
+
+    fn teardown(&mut self) -> Result<()> {
+        disable_raw_mode()?;
+        let mouse = self.mouse;
+        let terminal = self.terminal()?;
+        if mouse {
+            execute!(terminal.backend_mut(), DisableMouseCapture)?;
+        }
+        execute!(
+            terminal.backend_mut(),
+            DisableBracketedPaste,
+            LeaveAlternateScreen
+        )?;
+        terminal.show_cursor()?;
+        self.terminal = None;
+        Ok(())
+    }
+
+    fn draw<F: FnOnce(&mut Frame)>(&mut self, render: F) -> Result<()> {
+        self.terminal()?.draw(render)?;
+        Ok(())
+    }
+
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        Ok(event::poll(timeout)?)
+    }
+
+    fn read(&mut self) -> Result<Event> {
+        Ok(event::read()?)
+    }
+
+    fn size(&self) -> Result<Rect> {
+        let terminal = self.terminal.as_ref().context("terminal is not set up")?;
+        Ok(terminal.size()?)
+    }
+}
+
+#[cfg(test)]
+pub use self::scripted::ScriptedTerminal;
+
+#[cfg(test)]
+mod scripted {
+    use std::{collections::VecDeque, time::Duration};
+
+    use anyhow::{bail, Result};
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::{backend::TestBackend, layout::Rect, Frame, Terminal};
+
+    use super::TerminalBackend;
+
+    pub struct ScriptedTerminal {
+        terminal: Terminal<TestBackend>,
+        events: VecDeque<Event>,
+        // Whether the terminal is currently set up, i.e. raw mode would be on.
+        pub active: bool,
+    }
+
+    impl ScriptedTerminal {
+        pub fn new(width: u16, height: u16, events: Vec<Event>) -> Self {
+            Self {
+                terminal: Terminal::new(TestBackend::new(width, height)).unwrap(),
+                events: events.into(),
+                active: false,
+            }
+        }
+
+        pub fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+            Event::Key(KeyEvent::new(code, modifiers))
+        }
+    }
+
+    impl TerminalBackend for ScriptedTerminal {
+        fn setup(&mut self) -> Result<()> {
+            self.active = true;
+            Ok(())
+        }
+
+        fn teardown(&mut self) -> Result<()> {
+            self.active = false;
+            Ok(())
+        }
+
+        fn draw<F: FnOnce(&mut Frame)>(&mut self, render: F) -> Result<()> {
+            self.terminal.draw(render)?;
+            Ok(())
+        }
+
+        fn poll(&mut self, _timeout: Duration) -> Result<bool> {
+            Ok(true)
+        }
+
+        // Running out of events ends the loop with an error instead of spinning forever.
+        fn read(&mut self) -> Result<Event> {
+            match self.events.pop_front() {
+                Some(event) => Ok(event),
+                None => bail!("scripted events exhausted"),
+            }
+        }
+
+        fn size(&self) -> Result<Rect> {
+            Ok(self.terminal.size()?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::{with_terminal, ScriptedTerminal};
+
+    #[test]
+    fn terminal_is_restored_when_the_body_panics() {
+        let mut terminal = ScriptedTerminal::new(10, 5, Vec::new());
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            with_terminal(&mut terminal, |terminal| -> anyhow::Result<()> {
+                assert!(terminal.active);
+                panic!("boom");
+            })
+        }));
+        assert!(res.is_err());
+        assert!(!terminal.active);
+
+        let value = with_terminal(&mut terminal, |_| Ok(7)).unwrap();
+        assert_eq!(value, 7);
+        assert!(!terminal.active);
+    }
+}
@@ -0,0 +1,84 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Default,
+    HighContrast,
+}
+
+impl Theme {
+    pub fn border_style(self, active: bool) -> Style {
+        match (self, active) {
+            (Theme::Default, true) => Style::default().fg(Color::Yellow),
+            (Theme::Default, false) => Style::default(),
+            (Theme::HighContrast, true) => Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            (Theme::HighContrast, false) => Style::default().fg(Color::White),
+        }
+    }
+
+    pub fn title_style(self, active: bool) -> Style {
+        match (self, active) {
+            (Theme::HighContrast, true) => Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            _ => Style::default(),
+        }
+    }
+
+    // The bordered pane frame shared by the editor and runtime draws.
+    pub fn pane_block(self, title: String, active: bool) -> Block<'static> {
+        Block::default()
+            .title(title)
+            .title_style(self.title_style(active))
+            .borders(Borders::ALL)
+            .border_style(self.border_style(active))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::{Color, Modifier, Style};
+
+    use super::Theme;
+
+    #[test]
+    fn default_theme_keeps_the_yellow_highlight() {
+        assert_eq!(
+            Theme::Default.border_style(true),
+            Style::default().fg(Color::Yellow)
+        );
+        assert_eq!(Theme::Default.border_style(false), Style::default());
+        assert_eq!(Theme::Default.title_style(true), Style::default());
+    }
+
+    #[test]
+    fn high_contrast_brightens_borders_and_bolds_active_title() {
+        let theme = Theme::HighContrast;
+        assert_eq!(theme.border_style(false).fg, Some(Color::White));
+        let border = theme.border_style(true);
+        assert_eq!(border.fg, Some(Color::LightYellow));
+        assert!(border.add_modifier.contains(Modifier::BOLD));
+        let title = theme.title_style(true);
+        assert_eq!(
+            (title.fg, title.bg),
+            (Some(Color::Black), Some(Color::LightYellow))
+        );
+        assert!(title.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(theme.title_style(false), Style::default());
+    }
+
+    #[test]
+    fn parses_kebab_case_names() {
+        let theme: Theme = serde_json::from_str("\"high-contrast\"").unwrap();
+        assert_eq!(theme, Theme::HighContrast);
+    }
+}
@@ -0,0 +1,21 @@
+use std::{env, path::PathBuf};
+
+use crate::paths::config_dir;
+
+// zellij exports ZELLIJ (and the session name) to every process it runs.
+pub fn is_in_zellij_session() -> bool {
+    env::var_os("ZELLIJ").is_some()
+}
+
+pub fn zellij_session_name() -> Option<String> {
+    env::var("ZELLIJ_SESSION_NAME")
+        .ok()
+        .filter(|name| !name.is_empty())
+}
+
+pub fn layouts_dir() -> PathBuf {
+    match env::var("ZELLIJ_CONFIG_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir).join("layouts"),
+        _ => config_dir().join("zellij").join("layouts"),
+    }
+}